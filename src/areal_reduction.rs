@@ -0,0 +1,272 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::formula::IntensityFormula;
+
+/// 1平方マイル[km²]
+const SQUARE_MILE_KM2: f64 = 2.589988;
+
+/// 面積低減係数（ARF）の算定方法
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArealReduction {
+    /// 米国気象局 TP-29 曲線の近似式（Leclerc & Schaake, 1972）
+    /// ARF = 1 - exp(-1.1 t^0.25) + exp(-1.1 t^0.25 - 0.01 A)（t: 時間, A: 平方マイル）
+    Tp29,
+    /// 英国 Flood Studies Report の経験式（NERC, 1975, Vol. II）
+    /// ARF = 1 - b1 D^(-b2)、b1 = 0.0394 A^0.354、
+    /// b2 = 0.40 - 0.0208 ln(4.6 - ln A)（A < 20）、0.40 - 0.00382 (4.6 - ln A)²（A ≥ 20）
+    /// （D: 時間, A: km²）。短時間・大流域で負になる範囲は 0 とする。
+    Fsr,
+    /// ホートン型の経験式 ARF = exp(-k A^n)（A: km²）
+    /// 継続時間によらないため、係数 k, n は対象とする継続時間について求めたものを指定する。
+    Horton { k: f64, n: f64 },
+    /// ユーザー指定の面積・継続時間別の係数表
+    Table(ArfTable),
+}
+
+impl ArealReduction {
+    /// 流域面積 area[km²]、継続時間 duration[分] に対する面積低減係数
+    pub fn factor(&self, area: f64, duration: f64) -> f64 {
+        match self {
+            Self::Tp29 => {
+                let hours = duration / 60.0;
+                let miles = area / SQUARE_MILE_KM2;
+                let e = -1.1 * hours.powf(0.25);
+                1.0 - e.exp() + (e - 0.01 * miles).exp()
+            }
+            Self::Fsr => {
+                let hours = duration / 60.0;
+                let ln_area = area.ln();
+                let b1 = 0.0394 * area.powf(0.354);
+                let b2 = if area < 20.0 {
+                    0.40 - 0.0208 * (4.6 - ln_area).ln()
+                } else {
+                    0.40 - 0.00382 * (4.6 - ln_area).powi(2)
+                };
+                (1.0 - b1 * hours.powf(-b2)).max(0.0)
+            }
+            Self::Horton { k, n } => (-k * area.powf(*n)).exp(),
+            Self::Table(table) => table.factor(area, duration),
+        }
+    }
+}
+
+/// 面積低減係数表のCSVの1行
+#[derive(Debug, Deserialize)]
+struct ArfRow {
+    area_km2: f64,
+    duration_minutes: f64,
+    factor: f64,
+}
+
+/// 面積・継続時間の格子上の面積低減係数表
+/// 格子の範囲外は端の値を用い、格子内は双線形補間する。
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArfTable {
+    areas: Vec<f64>,
+    durations: Vec<f64>,
+    /// factors[面積の添字][継続時間の添字]
+    factors: Vec<Vec<f64>>,
+}
+
+impl ArfTable {
+    /// (面積[km²], 継続時間[分], 係数) の組から格子状の表を構築する
+    /// すべての面積と継続時間の組み合わせがちょうど1つずつ必要。
+    pub fn new(rows: &[(f64, f64, f64)]) -> Result<Self> {
+        if rows.is_empty() {
+            bail!("Areal reduction table has no rows");
+        }
+        let mut areas: Vec<f64> = rows.iter().map(|r| r.0).collect();
+        let mut durations: Vec<f64> = rows.iter().map(|r| r.1).collect();
+        for values in [&mut areas, &mut durations] {
+            values.sort_by(f64::total_cmp);
+            values.dedup();
+        }
+        if areas.len() * durations.len() != rows.len() {
+            bail!(
+                "Areal reduction table must cover every area and duration combination once: \
+                 {} areas x {} durations but {} rows",
+                areas.len(),
+                durations.len(),
+                rows.len()
+            );
+        }
+
+        let mut factors = vec![vec![f64::NAN; durations.len()]; areas.len()];
+        for (i, &(area, duration, factor)) in rows.iter().enumerate() {
+            if area < 0.0 || duration <= 0.0 {
+                bail!(
+                    "Row {}: area must be non-negative and duration positive, got ({}, {})",
+                    i + 1,
+                    area,
+                    duration
+                );
+            }
+            if !(0.0..=1.0).contains(&factor) || factor == 0.0 {
+                bail!("Row {}: factor must be in (0, 1], got {}", i + 1, factor);
+            }
+            let a = areas.iter().position(|&v| v == area).unwrap_or_default();
+            let d = durations.iter().position(|&v| v == duration).unwrap_or_default();
+            if !factors[a][d].is_nan() {
+                bail!(
+                    "Row {}: duplicate entry for area {} km² and duration {} min",
+                    i + 1,
+                    area,
+                    duration
+                );
+            }
+            factors[a][d] = factor;
+        }
+
+        Ok(Self {
+            areas,
+            durations,
+            factors,
+        })
+    }
+
+    fn factor(&self, area: f64, duration: f64) -> f64 {
+        let (a0, a1, wa) = bracket(&self.areas, area);
+        let (d0, d1, wd) = bracket(&self.durations, duration);
+        let low = self.factors[a0][d0] + wd * (self.factors[a0][d1] - self.factors[a0][d0]);
+        let high = self.factors[a1][d0] + wd * (self.factors[a1][d1] - self.factors[a1][d0]);
+        low + wa * (high - low)
+    }
+}
+
+/// 昇順の格子 grid 上で x を挟む添字と重み（範囲外は端に固定）
+fn bracket(grid: &[f64], x: f64) -> (usize, usize, f64) {
+    let last = grid.len() - 1;
+    if x <= grid[0] {
+        return (0, 0, 0.0);
+    }
+    if x >= grid[last] {
+        return (last, last, 0.0);
+    }
+    let upper = grid.iter().position(|&v| v >= x).unwrap_or(last);
+    let lower = upper - 1;
+    (lower, upper, (x - grid[lower]) / (grid[upper] - grid[lower]))
+}
+
+/// 面積低減係数表をCSVファイルから読み込む
+/// 列: area_km2, duration_minutes, factor
+pub fn load_table(path: &Path) -> Result<ArfTable> {
+    let mut rdr = csv::Reader::from_path(path)
+        .with_context(|| format!("Failed to open areal reduction table: {}", path.display()))?;
+    let mut rows = Vec::new();
+    for record in rdr.deserialize() {
+        let row: ArfRow = record.with_context(|| {
+            format!("Failed to read areal reduction table row in {}", path.display())
+        })?;
+        rows.push((row.area_km2, row.duration_minutes, row.factor));
+    }
+    ArfTable::new(&rows)
+        .with_context(|| format!("Invalid areal reduction table: {}", path.display()))
+}
+
+/// 面積低減係数を乗じた降雨強度式
+/// 継続時間ごとに係数を適用するため、各時間ステップの増分雨量に継続時間別の低減が反映される。
+pub struct Reduced<'a, F: ?Sized> {
+    pub formula: &'a F,
+    pub reduction: &'a ArealReduction,
+    /// 流域面積[km²]
+    pub area: f64,
+}
+
+impl<F: IntensityFormula + ?Sized> IntensityFormula for Reduced<'_, F> {
+    fn intensity(&self, t: f64) -> f64 {
+        self.reduction.factor(self.area, t) * self.formula.intensity(t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formula::Talbot;
+    use std::fs;
+
+    #[test]
+    fn tp29_is_one_for_point_rainfall() {
+        assert!((ArealReduction::Tp29.factor(0.0, 60.0) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn tp29_decreases_with_area_and_recovers_with_duration() {
+        let arf = ArealReduction::Tp29;
+        // 約100平方マイル、24時間で約0.94
+        let day = arf.factor(259.0, 1440.0);
+        assert!((day - 0.945).abs() < 0.01, "ARF = {}", day);
+        assert!(arf.factor(500.0, 60.0) < arf.factor(100.0, 60.0));
+        assert!(arf.factor(500.0, 60.0) < arf.factor(500.0, 1440.0));
+    }
+
+    #[test]
+    fn fsr_matches_published_one_hour_factors() {
+        // FSR Vol. II の1時間の係数: 1 km² 0.96, 10 km² 0.91, 100 km² 0.80
+        let arf = ArealReduction::Fsr;
+        for (area, expected) in [(1.0, 0.96), (10.0, 0.91), (100.0, 0.80)] {
+            let factor = arf.factor(area, 60.0);
+            assert!((factor - expected).abs() < 0.005, "A = {}: ARF = {}", area, factor);
+        }
+        // 20 km² で分岐する b2 は連続
+        assert!((arf.factor(20.0 - 1e-9, 180.0) - arf.factor(20.0, 180.0)).abs() < 1e-6);
+        assert!(arf.factor(500.0, 60.0) < arf.factor(500.0, 1440.0));
+    }
+
+    #[test]
+    fn horton_formula() {
+        let arf = ArealReduction::Horton { k: 0.1, n: 0.5 };
+        assert!((arf.factor(100.0, 60.0) - (-1.0_f64).exp()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn table_interpolates_bilinearly_and_clamps() {
+        let table = ArfTable::new(&[
+            (10.0, 60.0, 0.9),
+            (10.0, 120.0, 0.95),
+            (100.0, 60.0, 0.7),
+            (100.0, 120.0, 0.8),
+        ])
+        .unwrap();
+        let arf = ArealReduction::Table(table);
+        assert!((arf.factor(55.0, 90.0) - 0.8375).abs() < 1e-12);
+        assert_eq!(arf.factor(5.0, 30.0), 0.9);
+        assert_eq!(arf.factor(200.0, 240.0), 0.8);
+    }
+
+    #[test]
+    fn table_requires_full_grid() {
+        let err = ArfTable::new(&[(10.0, 60.0, 0.9), (10.0, 120.0, 0.95), (100.0, 60.0, 0.7)])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("every area and duration"), "Error: {}", err);
+    }
+
+    #[test]
+    fn load_table_from_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("arf.csv");
+        fs::write(
+            &path,
+            "area_km2,duration_minutes,factor\n10,60,0.9\n10,120,0.95\n100,60,0.7\n100,120,0.8\n",
+        )
+        .unwrap();
+        let arf = ArealReduction::Table(load_table(&path).unwrap());
+        assert_eq!(arf.factor(100.0, 120.0), 0.8);
+    }
+
+    #[test]
+    fn reduced_formula_scales_intensity() {
+        let formula = Talbot { a: 5000.0, b: 40.0 };
+        let reduction = ArealReduction::Horton { k: 0.1, n: 0.5 };
+        let reduced = Reduced {
+            formula: &formula,
+            reduction: &reduction,
+            area: 100.0,
+        };
+        let expected = (-1.0_f64).exp() * formula.intensity(60.0);
+        assert!((reduced.intensity(60.0) - expected).abs() < 1e-12);
+    }
+}
@@ -1,58 +1,529 @@
-use std::path::Path;
+use std::fs;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
+use plotters::coord::Shift;
 use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
 
-use crate::types::HyetographEntry;
+use crate::formula::IntensityFormula;
+use crate::types::{ChartFormat, HydrographEntry, HyetographEntry, RoutedEntry};
 
 const DEFAULT_WIDTH: u32 = 800;
 const DEFAULT_HEIGHT: u32 = 600;
 
-/// ハイエトグラフをPNG棒グラフとして描画する
-pub fn render(data: &[HyetographEntry], output_path: &Path, time_step: f64) -> Result<()> {
-    let max_intensity = data
-        .iter()
-        .map(|e| e.intensity)
-        .fold(0.0_f64, f64::max);
-    let max_time = data.last().map(|e| e.time_minutes).unwrap_or(0.0);
+/// 等倍とみなす解像度[dpi]（グラフの幅・高さ[px]はこの解像度での値）
+pub const BASE_DPI: f64 = 96.0;
 
-    let root = BitMapBackend::new(output_path, (DEFAULT_WIDTH, DEFAULT_HEIGHT)).into_drawing_area();
-    root.fill(&WHITE)
-        .with_context(|| format!("Failed to initialize chart at {}", output_path.display()))?;
+/// PDF出力で sans-serif が見つからないときに代わりに使うフォント
+const SANS_SERIF_FALLBACKS: [&str; 4] = ["DejaVu Sans", "Liberation Sans", "Noto Sans", "Helvetica"];
+
+/// 損失の棒の色
+const LOSS_COLOR: RGBColor = RGBColor(240, 160, 60);
+
+/// ハイドログラフの線の色
+const DISCHARGE_COLOR: RGBColor = RGBColor(0, 130, 60);
+
+/// 調整池放流量の線の色
+const OUTFLOW_COLOR: RGBColor = RGBColor(150, 60, 170);
+
+/// 当てはめ曲線の描画点数
+const CURVE_SAMPLES: usize = 200;
+
+/// グラフの出力形式・大きさ・解像度・文字の倍率
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChartOptions {
+    /// 出力形式（None なら出力ファイルの拡張子から判定し、判定できなければPNG）
+    pub format: Option<ChartFormat>,
+    /// 幅[px]
+    pub width: u32,
+    /// 高さ[px]
+    pub height: u32,
+    /// PNGの解像度[dpi]（SVG・PDFは BASE_DPI で実寸に換算する）
+    pub dpi: f64,
+    /// 文字の大きさの倍率
+    pub font_scale: f64,
+}
+
+impl Default for ChartOptions {
+    fn default() -> Self {
+        Self {
+            format: None,
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            dpi: BASE_DPI,
+            font_scale: 1.0,
+        }
+    }
+}
+
+impl ChartOptions {
+    /// 出力ファイルに対する出力形式
+    pub fn format_for(&self, output_path: &Path) -> ChartFormat {
+        self.format
+            .or_else(|| ChartFormat::from_extension(output_path))
+            .unwrap_or(ChartFormat::Png)
+    }
+
+    /// 出力先のパス（明示した形式と拡張子が異なれば拡張子を置き換える）
+    pub fn output_path(&self, output_path: &Path) -> PathBuf {
+        match self.format {
+            Some(format) if ChartFormat::from_extension(output_path) != Some(format) => {
+                output_path.with_extension(format.extension())
+            }
+            _ => output_path.to_path_buf(),
+        }
+    }
+}
+
+/// 描画寸法の倍率（線幅・余白は length 倍、文字は length × font 倍）
+#[derive(Debug, Clone, Copy)]
+struct Scale {
+    length: f64,
+    font: f64,
+}
+
+impl Scale {
+    fn px(&self, value: i32) -> i32 {
+        (value as f64 * self.length).round() as i32
+    }
+
+    fn size(&self, value: u32) -> u32 {
+        (value as f64 * self.length).round() as u32
+    }
+
+    fn font(&self, size: f64) -> f64 {
+        size * self.length * self.font
+    }
+
+    /// 軸ラベルを収める領域の大きさ（文字の倍率に合わせて広げる）
+    fn label_area(&self, value: u32) -> u32 {
+        (value as f64 * self.length * self.font).round() as u32
+    }
+}
+
+/// 描画バックエンドに依存しないグラフの描画
+trait Plot {
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>, scale: &Scale) -> Result<()>
+    where
+        DB::ErrorType: 'static;
+}
+
+/// ハイエトグラフに重ねて描く付加情報
+#[derive(Debug, Default)]
+pub struct ChartExtras<'a> {
+    /// 比較パターン（ハイエトグラフ, (描画するパターン名, 比較パターン名)）
+    pub comparison: Option<(&'a [HyetographEntry], (&'a str, &'a str))>,
+    /// 右上に表示する注記
+    pub annotation: Option<String>,
+    /// 右軸に流量として重ねるハイドログラフ
+    pub hydrograph: Option<&'a [HydrographEntry]>,
+    /// 右軸に重ねる調整池の放流量（ハイドログラフの指定時のみ描画）
+    pub routed: Option<&'a [RoutedEntry]>,
+}
+
+/// ハイエトグラフを棒グラフとして描画する
+/// 比較パターンは階段線で、注記は右上に、ハイドログラフは右軸の流量として重ねる。
+/// 出力形式（PNG・SVG・PDF）は options と出力ファイルの拡張子から決める。
+pub fn render(
+    data: &[HyetographEntry],
+    extras: &ChartExtras,
+    output_path: &Path,
+    time_step: f64,
+    options: &ChartOptions,
+) -> Result<()> {
+    let plot = Hyetograph {
+        data,
+        extras,
+        time_step,
+    };
+    output(&plot, output_path, options)
+}
+
+/// 観測点と当てはめた降雨強度曲線を描画する
+pub fn render_fit<F: IntensityFormula>(
+    durations: &[f64],
+    intensities: &[f64],
+    formula: &F,
+    output_path: &Path,
+    options: &ChartOptions,
+) -> Result<()> {
+    let plot = Fit {
+        durations,
+        intensities,
+        formula,
+    };
+    output(&plot, output_path, options)
+}
 
-    let y_max = max_intensity * 1.1;
-
-    let mut chart = ChartBuilder::on(&root)
-        .caption("Hyetograph", ("sans-serif", 24).into_font())
-        .margin(10)
-        .x_label_area_size(40)
-        .y_label_area_size(60)
-        .build_cartesian_2d(0.0..max_time, 0.0..y_max)
-        .with_context(|| "Failed to build chart")?;
-
-    chart
-        .configure_mesh()
-        .x_desc("Time [min]")
-        .y_desc("Intensity [mm/h]")
-        .x_label_formatter(&|x| format!("{:.0}", x))
-        .y_label_formatter(&|y| format!("{:.1}", y))
-        .draw()
-        .with_context(|| "Failed to draw mesh")?;
-
-    chart
-        .draw_series(data.iter().map(|entry| {
-            let x0 = entry.time_minutes - time_step;
-            let x1 = entry.time_minutes;
-            Rectangle::new([(x0, 0.0), (x1, entry.intensity)], BLUE.filled())
-        }))
-        .with_context(|| "Failed to draw bars")?;
+/// 出力形式に応じたバックエンドで描画してファイルに保存する
+fn output<P: Plot>(plot: &P, output_path: &Path, options: &ChartOptions) -> Result<()> {
+    let size = (options.width, options.height);
+    let vector = Scale {
+        length: 1.0,
+        font: options.font_scale,
+    };
+    match options.format_for(output_path) {
+        ChartFormat::Png => {
+            let scale = Scale {
+                length: options.dpi / BASE_DPI,
+                font: options.font_scale,
+            };
+            let pixels = (scale.size(options.width), scale.size(options.height));
+            let mut buffer = vec![0; pixels.0 as usize * pixels.1 as usize * 3];
+            {
+                let root = BitMapBackend::with_buffer(&mut buffer, pixels).into_drawing_area();
+                draw_on(plot, &root, &scale, output_path)?;
+            }
+            write_png(&buffer, pixels, options.dpi, output_path)
+        }
+        ChartFormat::Svg => {
+            let root = SVGBackend::new(output_path, size).into_drawing_area();
+            draw_on(plot, &root, &vector, output_path)
+        }
+        ChartFormat::Pdf => {
+            let mut svg = String::new();
+            {
+                let root = SVGBackend::with_string(&mut svg, size).into_drawing_area();
+                draw_on(plot, &root, &vector, output_path)?;
+            }
+            fs::write(output_path, svg_to_pdf(&svg)?)
+                .with_context(|| format!("Failed to save chart to {}", output_path.display()))
+        }
+    }
+}
 
+fn draw_on<P: Plot, DB: DrawingBackend>(
+    plot: &P,
+    root: &DrawingArea<DB, Shift>,
+    scale: &Scale,
+    output_path: &Path,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)
+        .with_context(|| format!("Failed to initialize chart at {}", output_path.display()))?;
+    plot.draw(root, scale)?;
     root.present()
         .with_context(|| format!("Failed to save chart to {}", output_path.display()))?;
+    Ok(())
+}
 
+/// RGB画像を解像度（pHYs チャンク）付きのPNGとして保存する
+fn write_png(buffer: &[u8], (width, height): (u32, u32), dpi: f64, output_path: &Path) -> Result<()> {
+    let save_error = || format!("Failed to save chart to {}", output_path.display());
+    let file = fs::File::create(output_path).with_context(save_error)?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    // pHYs の単位は 1 m あたりの画素数
+    let pixels_per_meter = (dpi / 0.0254).round() as u32;
+    encoder.set_pixel_dims(Some(png::PixelDimensions {
+        xppu: pixels_per_meter,
+        yppu: pixels_per_meter,
+        unit: png::Unit::Meter,
+    }));
+    let mut writer = encoder.write_header().with_context(save_error)?;
+    writer.write_image_data(buffer).with_context(save_error)?;
+    writer.finish().with_context(save_error)?;
     Ok(())
 }
 
+/// SVGをベクターのままPDFに変換する（文字はシステムフォントでアウトライン化する）
+fn svg_to_pdf(svg: &str) -> Result<Vec<u8>> {
+    use svg2pdf::usvg::{self, fontdb, PostProcessingSteps, TreeParsing, TreePostProc};
+
+    let mut tree = usvg::Tree::from_str(svg, &usvg::Options::default())
+        .with_context(|| "Failed to convert chart to PDF")?;
+    let mut fonts = fontdb::Database::new();
+    fonts.load_system_fonts();
+    let sans_serif = fontdb::Query {
+        families: &[fontdb::Family::SansSerif],
+        ..fontdb::Query::default()
+    };
+    if fonts.query(&sans_serif).is_none()
+        && let Some(family) = SANS_SERIF_FALLBACKS.iter().find(|name| {
+            fonts
+                .faces()
+                .any(|face| face.families.iter().any(|(family, _)| family == *name))
+        })
+    {
+        fonts.set_sans_serif_family(*family);
+    }
+    tree.postprocess(PostProcessingSteps::default(), &fonts);
+
+    let options = svg2pdf::Options {
+        dpi: BASE_DPI as f32,
+        ..svg2pdf::Options::default()
+    };
+    Ok(svg2pdf::convert_tree(&tree, options))
+}
+
+/// ハイエトグラフ（棒グラフ）
+struct Hyetograph<'a> {
+    data: &'a [HyetographEntry],
+    extras: &'a ChartExtras<'a>,
+    time_step: f64,
+}
+
+impl Plot for Hyetograph<'_> {
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>, scale: &Scale) -> Result<()>
+    where
+        DB::ErrorType: 'static,
+    {
+        let (data, extras, time_step) = (self.data, self.extras, self.time_step);
+        let comparison = extras.comparison;
+        let max_intensity = data
+            .iter()
+            .chain(comparison.iter().flat_map(|(other, _)| other.iter()))
+            .map(|e| e.intensity)
+            .fold(0.0_f64, f64::max);
+        let hydrograph = extras.hydrograph;
+        let max_time = data
+            .iter()
+            .chain(comparison.iter().flat_map(|(other, _)| other.iter()))
+            .map(|e| e.time_minutes)
+            .chain(hydrograph.iter().flat_map(|h| h.iter()).map(|e| e.time_minutes))
+            .fold(0.0_f64, f64::max);
+        let max_discharge = hydrograph
+            .iter()
+            .flat_map(|h| h.iter())
+            .map(|e| e.discharge)
+            .chain(extras.routed.iter().flat_map(|r| r.iter()).map(|e| e.outflow))
+            .fold(0.0_f64, f64::max);
+
+        let y_max = max_intensity * 1.1;
+        let (legend_width, legend_height) = (scale.px(15), scale.px(5));
+        let line_width = scale.size(2);
+        let label_font = ("sans-serif", scale.font(12.0));
+
+        let mut chart = ChartBuilder::on(root)
+            .caption("Hyetograph", ("sans-serif", scale.font(24.0)).into_font())
+            .margin(scale.size(10))
+            .x_label_area_size(scale.label_area(40))
+            .y_label_area_size(scale.label_area(60))
+            .right_y_label_area_size(if hydrograph.is_some() {
+                scale.label_area(60)
+            } else {
+                0
+            })
+            .build_cartesian_2d(0.0..max_time, 0.0..y_max)
+            .with_context(|| "Failed to build chart")?
+            .set_secondary_coord(0.0..max_time, 0.0..(max_discharge * 1.1).max(f64::MIN_POSITIVE));
+
+        chart
+            .configure_mesh()
+            .x_desc("Time [min]")
+            .y_desc("Intensity [mm/h]")
+            .label_style(label_font)
+            .x_label_formatter(&|x| format!("{:.0}", x))
+            .y_label_formatter(&|y| format!("{:.1}", y))
+            .draw()
+            .with_context(|| "Failed to draw mesh")?;
+
+        // 損失が計算されていれば有効降雨と損失を積み上げ棒で描く
+        let has_loss = data.iter().any(|e| e.effective.is_some());
+        let bar_label = if has_loss {
+            Some("effective rainfall")
+        } else if let Some((_, (label, _))) = comparison {
+            Some(label)
+        } else {
+            hydrograph.map(|_| "rainfall")
+        };
+        let bars = chart
+            .draw_series(data.iter().map(|entry| {
+                let x0 = entry.time_minutes - time_step;
+                let x1 = entry.time_minutes;
+                let top = if has_loss {
+                    entry.effective.unwrap_or(0.0)
+                } else {
+                    entry.intensity
+                };
+                Rectangle::new([(x0, 0.0), (x1, top)], BLUE.filled())
+            }))
+            .with_context(|| "Failed to draw bars")?;
+        if let Some(label) = bar_label {
+            bars.label(label).legend(move |(x, y)| {
+                Rectangle::new(
+                    [(x, y - legend_height), (x + legend_width, y + legend_height)],
+                    BLUE.filled(),
+                )
+            });
+        }
+        if has_loss {
+            chart
+                .draw_series(data.iter().map(|entry| {
+                    let x0 = entry.time_minutes - time_step;
+                    let x1 = entry.time_minutes;
+                    let bottom = entry.effective.unwrap_or(0.0);
+                    Rectangle::new([(x0, bottom), (x1, entry.intensity)], LOSS_COLOR.filled())
+                }))
+                .with_context(|| "Failed to draw loss bars")?
+                .label("loss")
+                .legend(move |(x, y)| {
+                    Rectangle::new(
+                        [(x, y - legend_height), (x + legend_width, y + legend_height)],
+                        LOSS_COLOR.filled(),
+                    )
+                });
+        }
+
+        if let Some(annotation) = &extras.annotation {
+            let (width, _) = root.dim_in_pixel();
+            root.draw(&Text::new(
+                annotation.as_str(),
+                (width as i32 - scale.px(20), scale.px(45)),
+                ("sans-serif", scale.font(14.0))
+                    .into_font()
+                    .color(&BLACK)
+                    .pos(Pos::new(HPos::Right, VPos::Top)),
+            ))
+            .with_context(|| "Failed to draw annotation")?;
+        }
+
+        if let Some((other, (_, other_label))) = comparison {
+            let steps = other.iter().flat_map(|entry| {
+                [
+                    (entry.time_minutes - time_step, entry.intensity),
+                    (entry.time_minutes, entry.intensity),
+                ]
+            });
+            chart
+                .draw_series(LineSeries::new(steps, RED.stroke_width(line_width)))
+                .with_context(|| "Failed to draw comparison")?
+                .label(other_label)
+                .legend(move |(x, y)| {
+                    PathElement::new([(x, y), (x + legend_width, y)], RED.stroke_width(line_width))
+                });
+        }
+
+        if let Some(hydrograph) = hydrograph {
+            chart
+                .configure_secondary_axes()
+                .y_desc("Discharge [m³/s]")
+                .label_style(label_font)
+                .y_label_formatter(&|q| format!("{:.1}", q))
+                .draw()
+                .with_context(|| "Failed to draw discharge axis")?;
+            let points = hydrograph.iter().map(|e| (e.time_minutes, e.discharge));
+            chart
+                .draw_secondary_series(LineSeries::new(
+                    points,
+                    DISCHARGE_COLOR.stroke_width(line_width),
+                ))
+                .with_context(|| "Failed to draw hydrograph")?
+                .label("discharge")
+                .legend(move |(x, y)| {
+                    PathElement::new(
+                        [(x, y), (x + legend_width, y)],
+                        DISCHARGE_COLOR.stroke_width(line_width),
+                    )
+                });
+            if let Some(routed) = extras.routed {
+                let points = routed.iter().map(|e| (e.time_minutes, e.outflow));
+                chart
+                    .draw_secondary_series(LineSeries::new(
+                        points,
+                        OUTFLOW_COLOR.stroke_width(line_width),
+                    ))
+                    .with_context(|| "Failed to draw pond outflow")?
+                    .label("pond outflow")
+                    .legend(move |(x, y)| {
+                        PathElement::new(
+                            [(x, y), (x + legend_width, y)],
+                            OUTFLOW_COLOR.stroke_width(line_width),
+                        )
+                    });
+            }
+        }
+
+        if bar_label.is_some() {
+            chart
+                .configure_series_labels()
+                .label_font(label_font)
+                .margin(scale.size(10))
+                .legend_area_size(scale.size(30))
+                .background_style(WHITE.mix(0.8))
+                .border_style(BLACK)
+                .draw()
+                .with_context(|| "Failed to draw legend")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 観測点と当てはめた降雨強度曲線
+struct Fit<'a, F> {
+    durations: &'a [f64],
+    intensities: &'a [f64],
+    formula: &'a F,
+}
+
+impl<F: IntensityFormula> Plot for Fit<'_, F> {
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>, scale: &Scale) -> Result<()>
+    where
+        DB::ErrorType: 'static,
+    {
+        let (durations, intensities) = (self.durations, self.intensities);
+        let max_time = durations.iter().copied().fold(0.0_f64, f64::max);
+        let curve: Vec<(f64, f64)> = (1..=CURVE_SAMPLES)
+            .map(|i| {
+                let t = max_time * i as f64 / CURVE_SAMPLES as f64;
+                (t, self.formula.intensity(t))
+            })
+            .collect();
+        let max_intensity = intensities
+            .iter()
+            .copied()
+            .chain(curve.iter().map(|&(_, k)| k).filter(|k| k.is_finite()))
+            .fold(0.0_f64, f64::max);
+
+        let y_max = max_intensity * 1.1;
+
+        let mut chart = ChartBuilder::on(root)
+            .caption(
+                "Intensity-Duration Fit",
+                ("sans-serif", scale.font(24.0)).into_font(),
+            )
+            .margin(scale.size(10))
+            .x_label_area_size(scale.label_area(40))
+            .y_label_area_size(scale.label_area(60))
+            .build_cartesian_2d(0.0..max_time * 1.05, 0.0..y_max)
+            .with_context(|| "Failed to build chart")?;
+
+        chart
+            .configure_mesh()
+            .x_desc("Duration [min]")
+            .y_desc("Intensity [mm/h]")
+            .label_style(("sans-serif", scale.font(12.0)))
+            .x_label_formatter(&|x| format!("{:.0}", x))
+            .y_label_formatter(&|y| format!("{:.1}", y))
+            .draw()
+            .with_context(|| "Failed to draw mesh")?;
+
+        chart
+            .draw_series(LineSeries::new(
+                curve.into_iter().filter(|&(_, k)| k <= y_max),
+                RED.stroke_width(scale.size(2)),
+            ))
+            .with_context(|| "Failed to draw fitted curve")?;
+
+        chart
+            .draw_series(
+                durations
+                    .iter()
+                    .zip(intensities.iter())
+                    .map(|(&t, &k)| Circle::new((t, k), scale.size(4), BLUE.filled())),
+            )
+            .with_context(|| "Failed to draw observations")?;
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -62,14 +533,23 @@ mod tests {
             HyetographEntry {
                 time_minutes: 10.0,
                 intensity: 13.465,
+                areal_reduction_factor: None,
+                loss: None,
+                effective: None,
             },
             HyetographEntry {
                 time_minutes: 20.0,
                 intensity: 68.369,
+                areal_reduction_factor: None,
+                loss: None,
+                effective: None,
             },
             HyetographEntry {
                 time_minutes: 30.0,
                 intensity: 141.179,
+                areal_reduction_factor: None,
+                loss: None,
+                effective: None,
             },
         ]
     }
@@ -79,17 +559,213 @@ mod tests {
         let dir = tempfile::tempdir().unwrap();
         let path = dir.path().join("test_chart.png");
 
-        render(&sample_data(), &path, 10.0).unwrap();
+        render(
+            &sample_data(),
+            &ChartExtras::default(),
+            &path,
+            10.0,
+            &ChartOptions::default(),
+        )
+        .unwrap();
 
         assert!(path.exists(), "PNG file should be created");
         let metadata = std::fs::metadata(&path).unwrap();
         assert!(metadata.len() > 0, "PNG file should not be empty");
     }
 
+    #[test]
+    fn render_extras_creates_png_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("compare.png");
+        let mut other = sample_data();
+        other.reverse();
+        for (entry, time) in other.iter_mut().zip([10.0, 20.0, 30.0]) {
+            entry.time_minutes = time;
+        }
+
+        let extras = ChartExtras {
+            comparison: Some((&other, ("triangular", "center"))),
+            annotation: Some("ARF(A=100 km², 120 min) = 0.95".to_string()),
+            ..ChartExtras::default()
+        };
+        render(&sample_data(), &extras, &path, 10.0, &ChartOptions::default()).unwrap();
+
+        assert!(path.exists(), "PNG file should be created");
+        assert!(std::fs::metadata(&path).unwrap().len() > 0);
+    }
+
+    #[test]
+    fn render_stacks_loss_and_effective_rainfall() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("loss.png");
+        let data: Vec<HyetographEntry> = sample_data()
+            .into_iter()
+            .map(|e| HyetographEntry {
+                loss: Some(e.intensity * 0.4),
+                effective: Some(e.intensity * 0.6),
+                ..e
+            })
+            .collect();
+
+        render(
+            &data,
+            &ChartExtras::default(),
+            &path,
+            10.0,
+            &ChartOptions::default(),
+        )
+        .unwrap();
+
+        assert!(std::fs::metadata(&path).unwrap().len() > 0);
+    }
+
+    #[test]
+    fn render_overlays_hydrograph_on_secondary_axis() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("runoff.png");
+        let hydrograph: Vec<HydrographEntry> = (1..=6)
+            .map(|i| HydrographEntry {
+                time_minutes: 10.0 * i as f64,
+                rainfall: 0.0,
+                discharge: 50.0 * (i as f64 / 3.0).min(6.0 / i as f64),
+            })
+            .collect();
+        let routed: Vec<RoutedEntry> = hydrograph
+            .iter()
+            .map(|e| RoutedEntry {
+                time_minutes: e.time_minutes,
+                inflow: e.discharge,
+                outflow: e.discharge * 0.5,
+                stage: 0.0,
+                storage: 0.0,
+            })
+            .collect();
+        let extras = ChartExtras {
+            hydrograph: Some(&hydrograph),
+            routed: Some(&routed),
+            ..ChartExtras::default()
+        };
+
+        render(&sample_data(), &extras, &path, 10.0, &ChartOptions::default()).unwrap();
+
+        assert!(std::fs::metadata(&path).unwrap().len() > 0);
+    }
+
     #[test]
     fn render_nonexistent_dir_fails() {
         let path = Path::new("/nonexistent/dir/chart.png");
-        let result = render(&sample_data(), path, 10.0);
+        let result = render(
+            &sample_data(),
+            &ChartExtras::default(),
+            path,
+            10.0,
+            &ChartOptions::default(),
+        );
         assert!(result.is_err());
     }
+
+    #[test]
+    fn render_fit_creates_png_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fit.png");
+        let formula = crate::formula::Cleveland {
+            a: 1557.825,
+            b: 5.411,
+            n: 0.75,
+        };
+
+        render_fit(
+            &[10.0, 30.0, 60.0],
+            &[141.2, 85.5, 59.1],
+            &formula,
+            &path,
+            &ChartOptions::default(),
+        )
+        .unwrap();
+
+        assert!(path.exists(), "PNG file should be created");
+    }
+
+    #[test]
+    fn format_follows_extension_unless_explicit() {
+        let options = ChartOptions::default();
+        assert_eq!(options.format_for(Path::new("a.svg")), ChartFormat::Svg);
+        assert_eq!(options.format_for(Path::new("a.PDF")), ChartFormat::Pdf);
+        assert_eq!(options.format_for(Path::new("a.dat")), ChartFormat::Png);
+        assert_eq!(options.output_path(Path::new("a.dat")), PathBuf::from("a.dat"));
+
+        let options = ChartOptions {
+            format: Some(ChartFormat::Svg),
+            ..ChartOptions::default()
+        };
+        assert_eq!(options.output_path(Path::new("out/a.png")), PathBuf::from("out/a.svg"));
+        assert_eq!(options.output_path(Path::new("a.svg")), PathBuf::from("a.svg"));
+    }
+
+    #[test]
+    fn render_svg_uses_vector_backend() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chart.svg");
+        let options = ChartOptions {
+            width: 640,
+            height: 480,
+            ..ChartOptions::default()
+        };
+
+        render(&sample_data(), &ChartExtras::default(), &path, 10.0, &options).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("<svg width=\"640\" height=\"480\""));
+        assert!(content.contains("Hyetograph"));
+    }
+
+    #[test]
+    fn render_pdf_sizes_page_at_base_dpi() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chart.pdf");
+
+        render(
+            &sample_data(),
+            &ChartExtras::default(),
+            &path,
+            10.0,
+            &ChartOptions::default(),
+        )
+        .unwrap();
+
+        let content = std::fs::read(&path).unwrap();
+        assert!(content.starts_with(b"%PDF-"));
+        // 800 × 600 px を 96 dpi で換算した 600 × 450 pt
+        let needle = b"/MediaBox [0 0 600 450]";
+        assert!(content.windows(needle.len()).any(|w| w == needle));
+    }
+
+    #[test]
+    fn render_png_scales_pixels_with_dpi() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chart.png");
+        let options = ChartOptions {
+            width: 400,
+            height: 300,
+            dpi: 192.0,
+            font_scale: 1.5,
+            ..ChartOptions::default()
+        };
+
+        render(&sample_data(), &ChartExtras::default(), &path, 10.0, &options).unwrap();
+
+        // IHDR チャンクの幅・高さ
+        let content = std::fs::read(&path).unwrap();
+        let width = u32::from_be_bytes(content[16..20].try_into().unwrap());
+        let height = u32::from_be_bytes(content[20..24].try_into().unwrap());
+        assert_eq!((width, height), (800, 600));
+
+        // pHYs チャンクに 192 dpi（7559 画素/m）を記録する
+        let reader = png::Decoder::new(std::fs::File::open(&path).unwrap())
+            .read_info()
+            .unwrap();
+        let dims = reader.info().pixel_dims.unwrap();
+        assert_eq!((dims.xppu, dims.yppu), (7559, 7559));
+        assert_eq!(dims.unit, png::Unit::Meter);
+    }
 }
@@ -1,8 +1,16 @@
+use std::ffi::OsString;
 use std::path::PathBuf;
 
-use clap::Parser;
+use chrono::NaiveDateTime;
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand};
 
-use crate::types::{DistributionPattern, OutputFormat};
+use crate::coefficient_table;
+
+use crate::types::{
+    AntecedentMoisture, ArfMethod, DistributionPattern, FormulaType, Interpolation, LandUse,
+    ChartFormat, LossMethod, OutputFormat, Peak, ProbabilityDistribution, RunoffMethod, SwmmRainFormat,
+    TcMethod, UnitHydrographMethod,
+};
 
 /// ハイエトグラフ（降雨時間分布図）生成ツール
 ///
@@ -12,35 +20,592 @@ use crate::types::{DistributionPattern, OutputFormat};
 /// 使用例:
 ///   hyetograph-cli 0.75 5.411 1557.825 10 2
 ///   hyetograph-cli 0.75 5.411 1557.825 10 2 --pattern center --format both
+///   hyetograph-cli 0 40 5000 10 1 --formula talbot
+///   hyetograph-cli 10 2 --idf-table idf.csv
+///   hyetograph-cli 10 2 --coefficients coefficients.toml --return-period 50
+///   hyetograph-cli 10 2 --preset tokyo-ku-50mm
+///   hyetograph-cli fit observed.csv --plot fit.png
+///   hyetograph-cli frequency annual_maxima.csv --return-period 50 --output idf50.csv
+///   hyetograph-cli rational 0.75 5.411 1557.825 --runoff-coefficient 0.8 --area 2 --tc 15
+///   hyetograph-cli pond --table pond.csv --inflow hyetograph_runoff.csv --target-release 3
 #[derive(Parser, Debug)]
-#[command(version, about, allow_negative_numbers = true)]
+#[command(
+    version,
+    about,
+    allow_negative_numbers = true,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true,
+    override_usage = "hyetograph-cli [OPTIONS] <A> <B> <C> <T> <TT>\n       \
+                      hyetograph-cli [OPTIONS] --idf-table <PATH> <T> <TT>\n       \
+                      hyetograph-cli [OPTIONS] --coefficients <PATH> --return-period <YEARS> <T> <TT>\n       \
+                      hyetograph-cli [OPTIONS] --preset <NAME> <T> <TT>\n       \
+                      hyetograph-cli <COMMAND>"
+)]
 pub struct Cli {
-    /// 降雨強度係数A（べき乗指数）
-    pub a: f64,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// 降雨強度係数 A（べき乗指数）B（加算定数）C（分子定数）、
+    /// 計算時間刻み T [分]、降雨継続時間 TT [時間] の順に指定する。
+    /// --idf-table・--coefficients・--preset 指定時は T TT のみを指定する
+    #[arg(value_name = "PARAMS", num_args = 2..=5, required = true)]
+    pub values: Vec<f64>,
+
+    /// IDF表CSV（列: duration_minutes, intensity_mm_per_h）。係数A B Cの代わりに使用する
+    #[arg(long, value_name = "PATH", conflicts_with = "coefficients")]
+    pub idf_table: Option<PathBuf>,
 
-    /// 降雨強度係数B（加算定数）
-    pub b: f64,
+    /// 再現期間別の係数表（TOMLまたはCSV）。係数A B Cの代わりに使用する
+    #[arg(long, value_name = "PATH")]
+    pub coefficients: Option<PathBuf>,
 
-    /// 降雨強度係数C（分子定数）
-    pub c: f64,
+    /// 係数表から選択する再現期間 [年]
+    #[arg(long, value_name = "YEARS")]
+    pub return_period: Option<u32>,
 
-    /// 計算時間刻み T [分]
-    pub t: f64,
+    /// 名前付きプリセットの係数と式を使用する（一覧は presets list）
+    #[arg(
+        long,
+        value_name = "NAME",
+        conflicts_with_all = ["idf_table", "coefficients", "formula", "exponent_m"]
+    )]
+    pub preset: Option<String>,
 
-    /// 降雨継続時間 TT [時間]
-    pub tt: f64,
+    /// IDF表の補間方法 (log-log: 両対数補間, linear: 線形補間)
+    #[arg(long, default_value = "log-log")]
+    pub interpolation: Interpolation,
 
-    /// 雨量分布パターン (front: 前方集中, center: 中央集中, rear: 後方集中)
+    /// 降雨強度式 (talbot: C/(t+B), sherman: C/t^A, cleveland: C/(t^A+B), kimijima: C/(t^A+B)^M)
+    #[arg(long, default_value = "cleveland")]
+    pub formula: FormulaType,
+
+    /// 君島型の分母指数 M（--formula kimijima のとき必須）
+    #[arg(long)]
+    pub exponent_m: Option<f64>,
+
+    /// 雨量分布パターン (front: 前方集中, center: 中央集中, rear: 後方集中,
+    /// scs-i / scs-ia / scs-ii / scs-iii: NRCS 24時間降雨, huff-q1〜huff-q4: Huff 四分位降雨,
+    /// custom: --mass-curve のユーザー定義曲線, multi-peak: --peaks の多峰型,
+    /// triangular: 三角形型, trapezoidal: 台形型)
     #[arg(long, default_value = "center")]
     pub pattern: DistributionPattern,
 
+    /// Huff 曲線の確率水準 [%]（10, 20, ..., 90）
+    #[arg(long, value_name = "PERCENT", default_value_t = 50)]
+    pub huff_probability: u8,
+
+    /// ユーザー定義の無次元累加雨量曲線（CSV/JSON、--pattern custom のとき必須）
+    #[arg(long, value_name = "FILE")]
+    pub mass_curve: Option<PathBuf>,
+
+    /// 多峰型のピーク（位置:配分 をカンマ区切り、例: 0.3:0.4,0.7:0.6）。
+    /// 位置は降雨継続時間に対する比率、配分は総雨量に対する比率で合計1とする
+    #[arg(long, value_name = "POSITION:SHARE", value_delimiter = ',')]
+    pub peaks: Vec<Peak>,
+
+    /// 三角形型降雨のピーク前の時間比 (0〜1)
+    #[arg(long, value_name = "R", default_value_t = 0.5)]
+    pub advancement: f64,
+
+    /// 台形型降雨の立ち上がり・一定・減衰の時間比（合計1）
+    #[arg(
+        long,
+        value_name = "RISE,PLATEAU,FALL",
+        value_delimiter = ',',
+        default_value = "0.25,0.5,0.25"
+    )]
+    pub trapezoid: Vec<f64>,
+
+    /// グラフに重ねて描く比較パターン
+    #[arg(long, value_name = "PATTERN")]
+    pub compare: Option<DistributionPattern>,
+
+    /// シカゴ型降雨のピーク位置係数 r (0〜1)。指定すると --pattern の代わりに
+    /// 降雨継続時間の r の位置にピークを置く交互ブロック法で配置する
+    #[arg(long, value_name = "R", conflicts_with = "pattern")]
+    pub peak_ratio: Option<f64>,
+
+    /// 総雨量 [mm]。指定すると降雨強度式による総雨量の代わりにこの値へ拡大縮小する
+    #[arg(long, value_name = "MM")]
+    pub total_depth: Option<f64>,
+
+    /// 流域面積 [km²]。指定すると継続時間ごとの面積低減係数を降雨強度に乗じる
+    #[arg(long, value_name = "KM2", conflicts_with = "total_depth")]
+    pub area: Option<f64>,
+
+    /// 面積低減係数の算定方法 (tp29: TP-29 近似式, fsr: 英国FSR式, horton: exp(-k A^n), table: 係数表)
+    #[arg(long, default_value = "tp29")]
+    pub arf_method: ArfMethod,
+
+    /// ホートン型の係数 k（--arf-method horton のとき必須）
+    #[arg(long)]
+    pub arf_k: Option<f64>,
+
+    /// ホートン型の指数 n（--arf-method horton のとき必須）
+    #[arg(long)]
+    pub arf_n: Option<f64>,
+
+    /// 面積低減係数表CSV（列 area_km2, duration_minutes, factor。--arf-method table のとき必須）
+    #[arg(long, value_name = "FILE")]
+    pub arf_table: Option<PathBuf>,
+
+    /// 損失モデル (scs-cn, horton, green-ampt, phi-index, initial-continuing)。
+    /// 省略時は --cn の指定があれば scs-cn
+    #[arg(long, value_name = "MODEL")]
+    pub loss: Option<LossMethod>,
+
+    /// SCS カーブナンバー（AMC II）。指定すると損失と有効降雨を計算する
+    #[arg(long, value_name = "CN")]
+    pub cn: Option<f64>,
+
+    /// 初期損失比 Ia / S
+    #[arg(long, value_name = "RATIO", default_value_t = 0.2)]
+    pub ia_ratio: f64,
+
+    /// 先行降雨条件 (dry: AMC I, normal: AMC II, wet: AMC III)
+    #[arg(long, default_value = "normal")]
+    pub amc: AntecedentMoisture,
+
+    /// Horton 式の初期浸透能 f0 [mm/h]（--loss horton のとき必須）
+    #[arg(long, value_name = "MM_PER_H")]
+    pub horton_f0: Option<f64>,
+
+    /// Horton 式の終期浸透能 fc [mm/h]（--loss horton のとき必須）
+    #[arg(long, value_name = "MM_PER_H")]
+    pub horton_fc: Option<f64>,
+
+    /// Horton 式の減衰係数 k [1/h]（--loss horton のとき必須）
+    #[arg(long, value_name = "PER_H")]
+    pub horton_k: Option<f64>,
+
+    /// Green-Ampt 式の飽和透水係数 [mm/h]（--loss green-ampt のとき必須）
+    #[arg(long, value_name = "MM_PER_H")]
+    pub ksat: Option<f64>,
+
+    /// Green-Ampt 式の湿潤前線の毛管水頭 [mm]（--loss green-ampt のとき必須）
+    #[arg(long, value_name = "MM")]
+    pub suction: Option<f64>,
+
+    /// Green-Ampt 式の含水率の不足分 Δθ（--loss green-ampt のとき必須）
+    #[arg(long, value_name = "FRACTION")]
+    pub moisture_deficit: Option<f64>,
+
+    /// φインデックス [mm/h]（--loss phi-index のとき必須）
+    #[arg(long, value_name = "MM_PER_H")]
+    pub phi: Option<f64>,
+
+    /// 初期損失 [mm]（--loss initial-continuing のとき必須）
+    #[arg(long, value_name = "MM")]
+    pub initial_loss: Option<f64>,
+
+    /// 継続損失強度 [mm/h]（--loss initial-continuing のとき必須）
+    #[arg(long, value_name = "MM_PER_H")]
+    pub continuing_loss: Option<f64>,
+
+    /// 流出モデル (storage-function: 貯留関数法, unit-hydrograph: 単位図法, tank: タンクモデル,
+    /// kinematic-wave: キネマティックウェーブ法)。
+    /// 指定するとハイドログラフを計算する
+    #[arg(long, value_name = "MODEL")]
+    pub runoff: Option<RunoffMethod>,
+
+    /// 流出計算の流域面積 [km²]（--area は面積低減係数専用で流出計算には使わない）
+    #[arg(long, value_name = "KM2")]
+    pub runoff_area: Option<f64>,
+
+    /// 流出計算の期間 [時間]（省略時は降雨継続時間 TT の3倍）
+    #[arg(long, value_name = "HOURS")]
+    pub runoff_duration: Option<f64>,
+
+    /// 貯留関数法の貯留係数 K（--runoff storage-function のとき必須）
+    #[arg(long, value_name = "K")]
+    pub sf_k: Option<f64>,
+
+    /// 貯留関数法の貯留指数 P（--runoff storage-function のとき必須）
+    #[arg(long, value_name = "P")]
+    pub sf_p: Option<f64>,
+
+    /// 貯留関数法の遅滞時間 Tl [時間]
+    #[arg(long, value_name = "HOURS", default_value_t = 0.0)]
+    pub sf_tl: f64,
+
+    /// 貯留関数法の一次流出率 f1 (0〜1)
+    #[arg(long, value_name = "F1", default_value_t = 1.0)]
+    pub sf_f1: f64,
+
+    /// 貯留関数法の飽和雨量 Rsa [mm]（f1 < 1 のとき必須）
+    #[arg(long, value_name = "MM")]
+    pub sf_rsa: Option<f64>,
+
+    /// 単位図の種類 (scs, snyder, clark, file)
+    #[arg(long, default_value = "scs")]
+    pub uh_method: UnitHydrographMethod,
+
+    /// SCS 単位図の遅れ時間 [時間]（省略時は 0.6 × --uh-tc）
+    #[arg(long, value_name = "HOURS")]
+    pub uh_lag: Option<f64>,
+
+    /// 単位図の到達時間 tc [時間]（SCS・Clark 単位図）
+    #[arg(long, value_name = "HOURS")]
+    pub uh_tc: Option<f64>,
+
+    /// Clark 単位図の貯留係数 R [時間]
+    #[arg(long, value_name = "HOURS")]
+    pub clark_r: Option<f64>,
+
+    /// Snyder 単位図の係数 Ct
+    #[arg(long, value_name = "CT")]
+    pub snyder_ct: Option<f64>,
+
+    /// Snyder 単位図の係数 Cp
+    #[arg(long, value_name = "CP")]
+    pub snyder_cp: Option<f64>,
+
+    /// Snyder 単位図の流路長 L [km]
+    #[arg(long, value_name = "KM")]
+    pub snyder_length: Option<f64>,
+
+    /// Snyder 単位図の流域重心までの流路長 Lc [km]
+    #[arg(long, value_name = "KM")]
+    pub snyder_lc: Option<f64>,
+
+    /// ユーザー指定の単位図CSV（列: time_minutes, discharge_m3_per_s。--uh-method file のとき必須）
+    #[arg(long, value_name = "FILE")]
+    pub uh_file: Option<PathBuf>,
+
+    /// タンクモデルのパラメータファイル（TOML。--runoff tank のとき必須）
+    #[arg(long, value_name = "FILE")]
+    pub tank_model: Option<PathBuf>,
+
+    /// 斜面勾配 S [m/m]（--runoff kinematic-wave のとき必須）
+    #[arg(long, value_name = "SLOPE")]
+    pub kw_slope: Option<f64>,
+
+    /// 斜面長 L [m]（--runoff kinematic-wave のとき必須）
+    #[arg(long, value_name = "M")]
+    pub kw_length: Option<f64>,
+
+    /// 斜面のマニング粗度係数 n（--runoff kinematic-wave のとき必須）
+    #[arg(long, value_name = "N")]
+    pub kw_n: Option<f64>,
+
+    /// 斜面幅 W [m]（--runoff kinematic-wave のとき必須）
+    #[arg(long, value_name = "M")]
+    pub kw_width: Option<f64>,
+
+    /// 基底流量 [m³/s]（貯留関数法・単位図法）
+    #[arg(long, value_name = "M3_PER_S", default_value_t = 0.0)]
+    pub base_flow: f64,
+
+    /// 調整池の水位-貯留量-放流量表CSV（列: stage_m, storage_m3, discharge_m3_per_s）。
+    /// 指定するとハイドログラフを修正プルス法で追跡する（--runoff が必要）
+    #[arg(long, value_name = "FILE")]
+    pub pond: Option<PathBuf>,
+
+    /// 調整池の許容放流量 [m³/s]。指定すると池の容量を自動設計する（--pond が必要）
+    #[arg(long, value_name = "M3_PER_S")]
+    pub target_release: Option<f64>,
+
     /// 出力ファイルパス
     #[arg(short, long, default_value = "hyetograph.png")]
     pub output: PathBuf,
 
-    /// 出力形式 (png, csv, both)
+    /// 出力形式 (png, csv, both, swmm, hec-hms, hec-ras, json, ndjson)
     #[arg(short, long, default_value = "png")]
     pub format: OutputFormat,
+
+    /// グラフの体裁
+    #[command(flatten)]
+    pub chart: ChartArgs,
+
+    /// 降雨開始日時（例: 2024-06-01T09:00）。--format hec-hms・hec-ras では必須、
+    /// swmm では省略時に開始からの相対時刻で出力する
+    #[arg(long, value_name = "DATETIME", value_parser = parse_start_time)]
+    pub start_time: Option<NaiveDateTime>,
+
+    /// HEC-DSS パス名のBパート（地点名。--format hec-hms・hec-ras）
+    #[arg(long, value_name = "NAME", default_value = "HYETOGRAPH")]
+    pub hec_location: String,
+
+    /// SWMM の時系列名（--format swmm）
+    #[arg(long, value_name = "NAME", default_value = "hyetograph")]
+    pub swmm_series: String,
+
+    /// SWMM の雨量計名（--format swmm）
+    #[arg(long, value_name = "NAME", default_value = "RG1")]
+    pub swmm_gage: String,
+
+    /// SWMM 雨量計の雨量形式 (intensity, volume, cumulative)
+    #[arg(long, default_value = "intensity")]
+    pub swmm_rain_format: SwmmRainFormat,
+
+    /// 書き換える既存の SWMM 入力ファイル（.inp）。同名の時系列と雨量計を置き換える
+    #[arg(long, value_name = "PATH")]
+    pub swmm_inp: Option<PathBuf>,
+}
+
+/// 日時の書式（YYYY-MM-DDTHH:MM[:SS]、T の代わりに空白も可）
+fn parse_start_time(s: &str) -> Result<NaiveDateTime, String> {
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+        .ok_or_else(|| format!("expected YYYY-MM-DDTHH:MM, got '{}'", s))
+}
+
+impl Cli {
+    /// コマンドライン引数を解析する
+    ///
+    /// --coefficients で係数表が指定されている場合、表に含まれる再現期間を
+    /// --return-period のヘルプに表示する。表が読めない場合の報告はバリデーションで行う。
+    pub fn try_parse_with_table_help<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>,
+    {
+        let args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+        let mut command = Self::command();
+
+        if let Some(table) = coefficients_arg(&args)
+            .and_then(|path| coefficient_table::load(&PathBuf::from(path)).ok())
+        {
+            let periods = table
+                .periods()
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            command = command.mut_arg("return_period", |arg| {
+                arg.help(format!("係数表から選択する再現期間 [年]（利用可能: {}）", periods))
+            });
+        }
+
+        let mut matches = command.try_get_matches_from(args)?;
+        Self::from_arg_matches_mut(&mut matches)
+    }
+}
+
+/// 生の引数列から --coefficients の値を取り出す
+fn coefficients_arg(args: &[OsString]) -> Option<OsString> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--coefficients" {
+            return iter.next().cloned();
+        }
+        if let Some(value) = arg.to_str().and_then(|a| a.strip_prefix("--coefficients=")) {
+            return Some(value.into());
+        }
+    }
+    None
+}
+
+/// サブコマンド
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// 観測された継続時間-降雨強度の組から係数A, B, Cを推定する
+    ///
+    /// 降雨強度式 K = C / (t^A + B) を非線形最小二乗法で当てはめ、
+    /// 係数・残差・適合度を出力する。
+    Fit(FitArgs),
+
+    /// 年最大降雨強度系列から確率降雨強度を求める
+    ///
+    /// 継続時間ごとにGumbel・GEV・対数正規・対数ピアソンIII型分布を当てはめ、
+    /// SLSCとジャックナイフ推定誤差を出力する。求めた確率降雨強度はIDF表CSVとして
+    /// 書き出し、--idf-table の入力に使用できる。
+    Frequency(FrequencyArgs),
+
+    /// 合理式によりピーク流量を求める
+    ///
+    /// 洪水到達時間 tc の降雨強度 K(tc) から Q = C·i·A/3.6 [m³/s] を計算する。
+    /// tc は直接指定するか、Kirpich式・Kraven式で流路長と勾配から推定する。
+    Rational(Box<RationalArgs>),
+
+    /// 調整池の洪水調節計算を行う
+    ///
+    /// 流入ハイドログラフCSVを水位-貯留量-放流量表で修正プルス法により追跡し、
+    /// 最大放流量・最高水位・必要容量を出力する。--target-release を指定すると
+    /// 許容放流量を満たす容量まで池を拡大縮小して自動設計する。
+    Pond(PondArgs),
+
+    /// 降雨強度式プリセットを一覧・表示する
+    ///
+    /// 同梱ライブラリに加え、設定ディレクトリの hyetograph-cli/presets.toml を読み込む。
+    Presets {
+        #[command(subcommand)]
+        action: PresetsAction,
+    },
+}
+
+/// presetsサブコマンドの操作
+#[derive(Subcommand, Debug)]
+pub enum PresetsAction {
+    /// プリセットの一覧を表示する
+    List,
+    /// プリセットの係数と出典を表示する
+    Show {
+        /// プリセット名
+        name: String,
+    },
+}
+
+/// グラフの出力形式・大きさ・解像度・文字の倍率（メインコマンドとfitサブコマンドで共通）
+#[derive(Args, Debug)]
+pub struct ChartArgs {
+    /// グラフの出力形式 (png, svg, pdf)。省略時は出力ファイルの拡張子から判定する
+    #[arg(long)]
+    pub chart_format: Option<ChartFormat>,
+
+    /// グラフの幅 [px]（96 dpi 換算）
+    #[arg(long, value_name = "PX", default_value_t = 800)]
+    pub chart_width: u32,
+
+    /// グラフの高さ [px]（96 dpi 換算）
+    #[arg(long, value_name = "PX", default_value_t = 600)]
+    pub chart_height: u32,
+
+    /// PNG の解像度 [dpi]。体裁を保ったまま画素数を dpi / 96 倍にし、PNG に解像度を記録する。
+    /// SVG・PDF では指定できない
+    #[arg(long, value_name = "DPI", default_value_t = 96.0)]
+    pub dpi: f64,
+
+    /// グラフの文字の大きさの倍率
+    #[arg(long, value_name = "SCALE", default_value_t = 1.0)]
+    pub font_scale: f64,
+}
+
+/// fitサブコマンドの引数
+#[derive(Args, Debug)]
+pub struct FitArgs {
+    /// 観測点CSV（列: duration_minutes, intensity_mm_per_h）
+    pub input: PathBuf,
+
+    /// 当てはめ曲線と観測点のグラフ出力先（PNG・SVG・PDF）
+    #[arg(long, value_name = "PATH")]
+    pub plot: Option<PathBuf>,
+
+    /// グラフの体裁
+    #[command(flatten)]
+    pub chart: ChartArgs,
+}
+
+/// rationalサブコマンドの引数
+#[derive(Args, Debug)]
+pub struct RationalArgs {
+    /// 降雨強度係数 A B C（--idf-table・--coefficients・--preset 指定時は省略する）
+    #[arg(value_name = "A B C", num_args = 0..=3)]
+    pub values: Vec<f64>,
+
+    /// IDF表CSV（列: duration_minutes, intensity_mm_per_h）。係数A B Cの代わりに使用する
+    #[arg(long, value_name = "PATH", conflicts_with = "coefficients")]
+    pub idf_table: Option<PathBuf>,
+
+    /// 再現期間別の係数表（TOMLまたはCSV）。係数A B Cの代わりに使用する
+    #[arg(long, value_name = "PATH")]
+    pub coefficients: Option<PathBuf>,
+
+    /// 係数表から選択する再現期間 [年]
+    #[arg(long, value_name = "YEARS")]
+    pub return_period: Option<u32>,
+
+    /// 名前付きプリセットの係数と式を使用する
+    #[arg(
+        long,
+        value_name = "NAME",
+        conflicts_with_all = ["idf_table", "coefficients", "formula", "exponent_m"]
+    )]
+    pub preset: Option<String>,
+
+    /// IDF表の補間方法 (log-log: 両対数補間, linear: 線形補間)
+    #[arg(long, default_value = "log-log")]
+    pub interpolation: Interpolation,
+
+    /// 降雨強度式 (talbot, sherman, cleveland, kimijima)
+    #[arg(long, default_value = "cleveland")]
+    pub formula: FormulaType,
+
+    /// 君島型の分母指数 M（--formula kimijima のとき必須）
+    #[arg(long)]
+    pub exponent_m: Option<f64>,
+
+    /// 流出係数 C (0〜1)
+    #[arg(long, value_name = "C", conflicts_with = "land_use")]
+    pub runoff_coefficient: Option<f64>,
+
+    /// 土地利用別の流出係数と面積（係数:面積 をカンマ区切り、例: 0.9:0.4,0.3:1.6）。
+    /// 面積加重平均を流出係数とする
+    #[arg(long, value_name = "C:AREA", value_delimiter = ',')]
+    pub land_use: Vec<LandUse>,
+
+    /// 流域面積 [km²]
+    #[arg(long, value_name = "KM2")]
+    pub area: f64,
+
+    /// 洪水到達時間 [分]
+    #[arg(long, value_name = "MINUTES", conflicts_with = "tc_method")]
+    pub tc: Option<f64>,
+
+    /// 洪水到達時間の推定式 (kirpich, kraven)。--length と --slope が必要
+    #[arg(long, value_name = "METHOD")]
+    pub tc_method: Option<TcMethod>,
+
+    /// 流路長 [m]
+    #[arg(long, value_name = "M")]
+    pub length: Option<f64>,
+
+    /// 流路勾配 [m/m]
+    #[arg(long, value_name = "SLOPE")]
+    pub slope: Option<f64>,
+
+    /// ピーク流量表を出力する継続時間 [分]（カンマ区切り）
+    #[arg(long, value_name = "MINUTES", value_delimiter = ',')]
+    pub durations: Vec<f64>,
+
+    /// ピーク流量表CSVの出力先（--durations が必要）
+    #[arg(long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+}
+
+/// pondサブコマンドの引数
+#[derive(Args, Debug)]
+pub struct PondArgs {
+    /// 水位-貯留量-放流量表CSV（列: stage_m, storage_m3, discharge_m3_per_s）
+    #[arg(long, value_name = "PATH")]
+    pub table: PathBuf,
+
+    /// 流入ハイドログラフCSV（列: time_minutes, discharge_m3_per_s。等間隔）。
+    /// --runoff で出力したハイドログラフCSVをそのまま使用できる
+    #[arg(long, value_name = "PATH")]
+    pub inflow: PathBuf,
+
+    /// 許容放流量 [m³/s]。指定すると池の容量を自動設計する
+    #[arg(long, value_name = "M3_PER_S")]
+    pub target_release: Option<f64>,
+
+    /// 追跡結果CSVの出力先
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+}
+
+/// frequencyサブコマンドの引数
+#[derive(Args, Debug)]
+pub struct FrequencyArgs {
+    /// 年最大値CSV（1列目: 年, 2列目以降: 継続時間[分]をヘッダーとする年最大降雨強度[mm/h]）
+    pub input: PathBuf,
+
+    /// 再現期間 [年]
+    #[arg(long)]
+    pub return_period: f64,
+
+    /// 採用する確率分布（省略時は継続時間ごとにSLSC最小の分布）
+    #[arg(long)]
+    pub distribution: Option<ProbabilityDistribution>,
+
+    /// 入力値を継続時間内の雨量[mm]として扱い、降雨強度[mm/h]に換算する
+    #[arg(long)]
+    pub depth: bool,
+
+    /// 確率降雨強度のIDF表CSV出力先
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
 }
 
 #[cfg(test)]
@@ -52,11 +617,33 @@ mod tests {
         let cli =
             Cli::try_parse_from(["hyetograph-cli", "0.75", "5.411", "1557.825", "10", "2"])
                 .unwrap();
-        assert_eq!(cli.a, 0.75);
-        assert_eq!(cli.b, 5.411);
-        assert_eq!(cli.c, 1557.825);
-        assert_eq!(cli.t, 10.0);
-        assert_eq!(cli.tt, 2.0);
+        assert_eq!(cli.values, vec![0.75, 5.411, 1557.825, 10.0, 2.0]);
+    }
+
+    #[test]
+    fn parse_idf_table_with_t_and_tt() {
+        let cli = Cli::try_parse_from([
+            "hyetograph-cli",
+            "10",
+            "2",
+            "--idf-table",
+            "idf.csv",
+            "--interpolation",
+            "linear",
+        ])
+        .unwrap();
+        assert_eq!(cli.values, vec![10.0, 2.0]);
+        assert_eq!(cli.idf_table, Some(PathBuf::from("idf.csv")));
+        assert_eq!(cli.interpolation, Interpolation::Linear);
+    }
+
+    #[test]
+    fn default_interpolation_is_log_log() {
+        let cli =
+            Cli::try_parse_from(["hyetograph-cli", "0.75", "5.411", "1557.825", "10", "2"])
+                .unwrap();
+        assert_eq!(cli.interpolation, Interpolation::LogLog);
+        assert_eq!(cli.idf_table, None);
     }
 
     #[test]
@@ -83,6 +670,34 @@ mod tests {
         assert_eq!(cli.format, OutputFormat::Png);
     }
 
+    #[test]
+    fn default_formula_is_cleveland() {
+        let cli =
+            Cli::try_parse_from(["hyetograph-cli", "0.75", "5.411", "1557.825", "10", "2"])
+                .unwrap();
+        assert_eq!(cli.formula, FormulaType::Cleveland);
+        assert_eq!(cli.exponent_m, None);
+    }
+
+    #[test]
+    fn parse_kimijima_formula() {
+        let cli = Cli::try_parse_from([
+            "hyetograph-cli",
+            "0.75",
+            "5.411",
+            "1557.825",
+            "10",
+            "2",
+            "--formula",
+            "kimijima",
+            "--exponent-m",
+            "1.2",
+        ])
+        .unwrap();
+        assert_eq!(cli.formula, FormulaType::Kimijima);
+        assert_eq!(cli.exponent_m, Some(1.2));
+    }
+
     #[test]
     fn parse_all_options() {
         let cli = Cli::try_parse_from([
@@ -141,4 +756,355 @@ mod tests {
         let err = result.unwrap_err();
         assert_eq!(err.kind(), clap::error::ErrorKind::DisplayVersion);
     }
+
+    #[test]
+    fn parse_fit_subcommand() {
+        let cli =
+            Cli::try_parse_from(["hyetograph-cli", "fit", "observed.csv", "--plot", "fit.png"])
+                .unwrap();
+        let Some(Command::Fit(args)) = cli.command else {
+            panic!("expected fit subcommand");
+        };
+        assert_eq!(args.input, PathBuf::from("observed.csv"));
+        assert_eq!(args.plot, Some(PathBuf::from("fit.png")));
+    }
+
+    #[test]
+    fn positional_run_has_no_subcommand() {
+        let cli =
+            Cli::try_parse_from(["hyetograph-cli", "0.75", "5.411", "1557.825", "10", "2"])
+                .unwrap();
+        assert!(cli.command.is_none());
+    }
+
+    #[test]
+    fn parse_frequency_subcommand() {
+        let cli = Cli::try_parse_from([
+            "hyetograph-cli",
+            "frequency",
+            "maxima.csv",
+            "--return-period",
+            "50",
+            "--distribution",
+            "gev",
+            "--output",
+            "idf50.csv",
+        ])
+        .unwrap();
+        let Some(Command::Frequency(args)) = cli.command else {
+            panic!("expected frequency subcommand");
+        };
+        assert_eq!(args.input, PathBuf::from("maxima.csv"));
+        assert_eq!(args.return_period, 50.0);
+        assert_eq!(args.distribution, Some(ProbabilityDistribution::Gev));
+        assert!(!args.depth);
+        assert_eq!(args.output, Some(PathBuf::from("idf50.csv")));
+    }
+
+    #[test]
+    fn parse_rational_subcommand() {
+        let cli = Cli::try_parse_from([
+            "hyetograph-cli",
+            "rational",
+            "0.75",
+            "5.411",
+            "1557.825",
+            "--land-use",
+            "0.9:0.4,0.3:1.6",
+            "--area",
+            "2",
+            "--tc-method",
+            "kirpich",
+            "--length",
+            "1200",
+            "--slope",
+            "0.02",
+            "--durations",
+            "10,30,60",
+        ])
+        .unwrap();
+        let Some(Command::Rational(args)) = cli.command else {
+            panic!("expected rational subcommand");
+        };
+        assert_eq!(args.values, vec![0.75, 5.411, 1557.825]);
+        assert_eq!(args.land_use.len(), 2);
+        assert_eq!(args.tc_method, Some(TcMethod::Kirpich));
+        assert_eq!(args.durations, vec![10.0, 30.0, 60.0]);
+    }
+
+    #[test]
+    fn parse_swmm_options() {
+        let cli = Cli::try_parse_from([
+            "hyetograph-cli",
+            "0.75",
+            "5.411",
+            "1557.825",
+            "10",
+            "2",
+            "--format",
+            "swmm",
+            "--start-time",
+            "2024-06-01 09:30",
+            "--swmm-rain-format",
+            "cumulative",
+        ])
+        .unwrap();
+        assert_eq!(cli.format, OutputFormat::Swmm);
+        assert_eq!(cli.swmm_rain_format, SwmmRainFormat::Cumulative);
+        assert_eq!(cli.swmm_series, "hyetograph");
+        assert_eq!(
+            cli.start_time.unwrap().format("%Y-%m-%dT%H:%M").to_string(),
+            "2024-06-01T09:30"
+        );
+        assert!(parse_start_time("06/01/2024").is_err());
+    }
+
+    #[test]
+    fn parse_pond_subcommand() {
+        let cli = Cli::try_parse_from([
+            "hyetograph-cli",
+            "pond",
+            "--table",
+            "pond.csv",
+            "--inflow",
+            "inflow.csv",
+            "--target-release",
+            "2.5",
+        ])
+        .unwrap();
+        let Some(Command::Pond(args)) = cli.command else {
+            panic!("expected pond subcommand");
+        };
+        assert_eq!(args.table, PathBuf::from("pond.csv"));
+        assert_eq!(args.target_release, Some(2.5));
+        assert_eq!(args.output, None);
+    }
+
+    #[test]
+    fn rational_tc_conflicts_with_tc_method() {
+        let result = Cli::try_parse_from([
+            "hyetograph-cli",
+            "rational",
+            "0.75",
+            "5.411",
+            "1557.825",
+            "--runoff-coefficient",
+            "0.8",
+            "--area",
+            "2",
+            "--tc",
+            "15",
+            "--tc-method",
+            "kraven",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_coefficients_and_return_period() {
+        let cli = Cli::try_parse_from([
+            "hyetograph-cli",
+            "10",
+            "2",
+            "--coefficients",
+            "coefficients.toml",
+            "--return-period",
+            "50",
+        ])
+        .unwrap();
+        assert_eq!(cli.coefficients, Some(PathBuf::from("coefficients.toml")));
+        assert_eq!(cli.return_period, Some(50));
+    }
+
+    #[test]
+    fn idf_table_conflicts_with_coefficients() {
+        let result = Cli::try_parse_from([
+            "hyetograph-cli",
+            "10",
+            "2",
+            "--idf-table",
+            "idf.csv",
+            "--coefficients",
+            "coefficients.toml",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn help_lists_periods_from_coefficient_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("coefficients.csv");
+        std::fs::write(
+            &path,
+            "return_period,a,b,c\n10,0.75,5.411,1557.825\n50,0.74,5.102,1983.551\n",
+        )
+        .unwrap();
+
+        let err = Cli::try_parse_with_table_help([
+            "hyetograph-cli",
+            "--coefficients",
+            path.to_str().unwrap(),
+            "--help",
+        ])
+        .unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::DisplayHelp);
+        assert!(err.to_string().contains("利用可能: 10, 50"), "{}", err);
+    }
+
+    #[test]
+    fn try_parse_with_table_help_parses_normal_args() {
+        let cli = Cli::try_parse_with_table_help([
+            "hyetograph-cli",
+            "0.75",
+            "5.411",
+            "1557.825",
+            "10",
+            "2",
+        ])
+        .unwrap();
+        assert_eq!(cli.values, vec![0.75, 5.411, 1557.825, 10.0, 2.0]);
+    }
+
+    #[test]
+    fn parse_scs_pattern_with_total_depth() {
+        let cli = Cli::try_parse_from([
+            "hyetograph-cli",
+            "0.75",
+            "5.411",
+            "1557.825",
+            "60",
+            "24",
+            "--pattern",
+            "scs-ii",
+            "--total-depth",
+            "250",
+        ])
+        .unwrap();
+        assert_eq!(cli.pattern, DistributionPattern::ScsII);
+        assert_eq!(cli.total_depth, Some(250.0));
+    }
+
+    #[test]
+    fn peak_ratio_conflicts_with_pattern() {
+        let cli = Cli::try_parse_from([
+            "hyetograph-cli",
+            "0.75",
+            "5.411",
+            "1557.825",
+            "10",
+            "2",
+            "--peak-ratio",
+            "0.4",
+        ])
+        .unwrap();
+        assert_eq!(cli.peak_ratio, Some(0.4));
+
+        let result = Cli::try_parse_from([
+            "hyetograph-cli",
+            "0.75",
+            "5.411",
+            "1557.825",
+            "10",
+            "2",
+            "--peak-ratio",
+            "0.4",
+            "--pattern",
+            "front",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_peaks_list() {
+        let cli = Cli::try_parse_from([
+            "hyetograph-cli",
+            "0.75",
+            "5.411",
+            "1557.825",
+            "10",
+            "2",
+            "--pattern",
+            "multi-peak",
+            "--peaks",
+            "0.3:0.4,0.7:0.6",
+        ])
+        .unwrap();
+        assert_eq!(cli.pattern, DistributionPattern::MultiPeak);
+        assert_eq!(cli.peaks.len(), 2);
+        assert_eq!(cli.peaks[1].share, 0.6);
+    }
+
+    #[test]
+    fn parse_trapezoid_and_compare() {
+        let cli = Cli::try_parse_from([
+            "hyetograph-cli",
+            "0.75",
+            "5.411",
+            "1557.825",
+            "10",
+            "2",
+            "--pattern",
+            "trapezoidal",
+            "--trapezoid",
+            "0.2,0.3,0.5",
+            "--compare",
+            "center",
+        ])
+        .unwrap();
+        assert_eq!(cli.pattern, DistributionPattern::Trapezoidal);
+        assert_eq!(cli.trapezoid, vec![0.2, 0.3, 0.5]);
+        assert_eq!(cli.compare, Some(DistributionPattern::Center));
+        assert_eq!(cli.advancement, 0.5);
+    }
+
+    #[test]
+    fn area_conflicts_with_total_depth() {
+        let result = Cli::try_parse_from([
+            "hyetograph-cli",
+            "0.75",
+            "5.411",
+            "1557.825",
+            "10",
+            "2",
+            "--area",
+            "50",
+            "--total-depth",
+            "100",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_preset_option() {
+        let cli =
+            Cli::try_parse_from(["hyetograph-cli", "10", "2", "--preset", "vba-sample"]).unwrap();
+        assert_eq!(cli.values, vec![10.0, 2.0]);
+        assert_eq!(cli.preset.as_deref(), Some("vba-sample"));
+    }
+
+    #[test]
+    fn preset_conflicts_with_idf_table() {
+        let result = Cli::try_parse_from([
+            "hyetograph-cli",
+            "10",
+            "2",
+            "--preset",
+            "vba-sample",
+            "--idf-table",
+            "idf.csv",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_presets_show() {
+        let cli = Cli::try_parse_from(["hyetograph-cli", "presets", "show", "vba-sample"]).unwrap();
+        let Some(Command::Presets {
+            action: PresetsAction::Show { name },
+        }) = cli.command
+        else {
+            panic!("expected presets show");
+        };
+        assert_eq!(name, "vba-sample");
+    }
 }
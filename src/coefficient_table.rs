@@ -0,0 +1,149 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+/// 再現期間ごとの降雨強度係数
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct CoefficientSet {
+    /// 再現期間[年]
+    pub return_period: u32,
+    /// べき乗指数
+    pub a: f64,
+    /// 加算定数
+    pub b: f64,
+    /// 分子定数
+    pub c: f64,
+    /// 君島型の分母指数（省略可）
+    #[serde(default)]
+    pub m: Option<f64>,
+}
+
+/// 再現期間をキーとする係数表
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CoefficientTable {
+    pub sets: Vec<CoefficientSet>,
+}
+
+impl CoefficientTable {
+    /// 表に含まれる再現期間（昇順）
+    pub fn periods(&self) -> Vec<u32> {
+        let mut periods: Vec<u32> = self.sets.iter().map(|s| s.return_period).collect();
+        periods.sort_unstable();
+        periods
+    }
+
+    /// 指定した再現期間の係数
+    pub fn get(&self, return_period: u32) -> Option<&CoefficientSet> {
+        self.sets.iter().find(|s| s.return_period == return_period)
+    }
+
+    fn check(self) -> Result<Self> {
+        if self.sets.is_empty() {
+            bail!("Coefficient table has no sets");
+        }
+        let mut periods = self.periods();
+        periods.dedup();
+        if periods.len() != self.sets.len() {
+            bail!("Coefficient table has duplicate return periods");
+        }
+        Ok(self)
+    }
+}
+
+/// 係数表を読み込む
+///
+/// 拡張子が .csv の場合は列 return_period, a, b, c（任意で m）のCSV、
+/// それ以外はTOMLとして読み込む。TOMLは sets 配列に同じキーを持つ表を並べる。
+///
+/// ```toml
+/// sets = [
+///   { return_period = 10, a = 0.75, b = 5.411, c = 1557.825 },
+///   { return_period = 50, a = 0.74, b = 5.102, c = 1983.551 },
+/// ]
+/// ```
+pub fn load(path: &Path) -> Result<CoefficientTable> {
+    let is_csv = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+
+    let table = if is_csv {
+        let mut rdr = csv::Reader::from_path(path)
+            .with_context(|| format!("Failed to open coefficient table: {}", path.display()))?;
+        let sets = rdr
+            .deserialize()
+            .collect::<Result<Vec<CoefficientSet>, _>>()
+            .with_context(|| format!("Failed to read coefficient table: {}", path.display()))?;
+        CoefficientTable { sets }
+    } else {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to open coefficient table: {}", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse coefficient table: {}", path.display()))?
+    };
+
+    table
+        .check()
+        .with_context(|| format!("Invalid coefficient table: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_toml_with_inline_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("coefficients.toml");
+        fs::write(
+            &path,
+            "sets = [\n\
+             { return_period = 50, a = 0.74, b = 5.102, c = 1983.551 },\n\
+             { return_period = 10, a = 0.75, b = 5.411, c = 1557.825 },\n\
+             ]\n",
+        )
+        .unwrap();
+
+        let table = load(&path).unwrap();
+        assert_eq!(table.periods(), vec![10, 50]);
+        let set = table.get(10).unwrap();
+        assert_eq!(set.c, 1557.825);
+        assert_eq!(set.m, None);
+    }
+
+    #[test]
+    fn load_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("coefficients.csv");
+        fs::write(
+            &path,
+            "return_period,a,b,c,m\n2,0.7,4.0,800,\n100,0.76,5.6,2300,1.1\n",
+        )
+        .unwrap();
+
+        let table = load(&path).unwrap();
+        assert_eq!(table.periods(), vec![2, 100]);
+        assert_eq!(table.get(2).unwrap().m, None);
+        assert_eq!(table.get(100).unwrap().m, Some(1.1));
+        assert!(table.get(50).is_none());
+    }
+
+    #[test]
+    fn duplicate_periods_fail() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("coefficients.csv");
+        fs::write(&path, "return_period,a,b,c\n10,0.7,4.0,800\n10,0.8,4.0,900\n").unwrap();
+
+        let err = format!("{:#}", load(&path).unwrap_err());
+        assert!(err.contains("duplicate"), "Error: {}", err);
+    }
+
+    #[test]
+    fn missing_file_fails() {
+        let err = load(Path::new("/nonexistent/coefficients.toml"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Failed to open coefficient table"), "Error: {}", err);
+    }
+}
@@ -37,14 +37,23 @@ mod tests {
             HyetographEntry {
                 time_minutes: 10.0,
                 intensity: 13.465,
+                areal_reduction_factor: None,
+                loss: None,
+                effective: None,
             },
             HyetographEntry {
                 time_minutes: 20.0,
                 intensity: 68.369,
+                areal_reduction_factor: None,
+                loss: None,
+                effective: None,
             },
             HyetographEntry {
                 time_minutes: 30.0,
                 intensity: 141.179,
+                areal_reduction_factor: None,
+                loss: None,
+                effective: None,
             },
         ]
     }
@@ -66,6 +75,44 @@ mod tests {
         assert_eq!(lines.len(), 4);
     }
 
+    #[test]
+    fn writes_areal_reduction_factor_column_when_present() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("arf.csv");
+        let data: Vec<HyetographEntry> = sample_data()
+            .into_iter()
+            .map(|e| HyetographEntry {
+                areal_reduction_factor: Some(0.9),
+                ..e
+            })
+            .collect();
+
+        write(&data, &path).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], "time_minutes,intensity_mm_per_h,areal_reduction_factor");
+        assert_eq!(lines[1], "10.0,13.465,0.9");
+    }
+
+    #[test]
+    fn writes_loss_columns_when_present() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("loss.csv");
+        let data = vec![HyetographEntry {
+            loss: Some(4.0),
+            effective: Some(9.465),
+            ..sample_data().remove(0)
+        }];
+
+        write(&data, &path).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], "time_minutes,intensity_mm_per_h,loss_mm_per_h,effective_mm_per_h");
+        assert_eq!(lines[1], "10.0,13.465,4.0,9.465");
+    }
+
     #[test]
     fn nonexistent_parent_dir_returns_error() {
         let path = Path::new("/nonexistent/dir/output.csv");
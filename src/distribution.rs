@@ -1,55 +1,235 @@
-use crate::types::{DistributionPattern, HyetographEntry};
+use crate::mass_curve::{self, CumulativeCurve, HuffQuartile, MassCurve, ScsType, Trapezoid};
+use crate::types::{DistributionPattern, HyetographEntry, Peak};
+
+/// パターンごとの追加設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternOptions {
+    /// Huff 曲線の確率水準[%]
+    pub huff_probability: u8,
+    /// ユーザー定義の累加雨量曲線（Custom パターン用）
+    pub mass_curve: Option<MassCurve>,
+    /// 多峰型のピーク（位置の昇順、配分の合計は1）
+    pub peaks: Vec<Peak>,
+    /// 三角形型降雨のピーク前の時間比（storm advancement coefficient）
+    pub advancement: f64,
+    /// 台形型降雨の立ち上がり・一定・減衰の時間比
+    pub trapezoid: Trapezoid,
+}
+
+impl Default for PatternOptions {
+    fn default() -> Self {
+        Self {
+            huff_probability: 50,
+            mass_curve: None,
+            peaks: Vec::new(),
+            advancement: 0.5,
+            trapezoid: Trapezoid {
+                rise: 0.25,
+                plateau: 0.5,
+                fall: 0.25,
+            },
+        }
+    }
+}
 
 /// 増分雨量を時間軸上に配置する
+/// 交互ブロック法のパターンは増分雨量を並べ替え、無次元累加雨量曲線のパターンは
+/// 増分雨量の合計を曲線に沿って配分する。
 /// increments: 降順の増分雨量（R[0]が最大）
 /// pattern: 配置パターン
+/// options: パターンごとの追加設定
 /// t: 計算時間刻み[分]
 /// 返却: 時系列順に並んだHyetographEntry配列
-pub fn arrange(increments: &[f64], pattern: DistributionPattern, t: f64) -> Vec<HyetographEntry> {
+pub fn arrange(
+    increments: &[f64],
+    pattern: DistributionPattern,
+    options: &PatternOptions,
+    t: f64,
+) -> Vec<HyetographEntry> {
     let nt = increments.len();
     let mut arranged = vec![0.0; nt];
 
     match pattern {
-        DistributionPattern::Front => {
-            // 前方集中型: 降順のまま先頭から配置
-            arranged.copy_from_slice(increments);
-        }
-        DistributionPattern::Rear => {
-            // 後方集中型: 逆順（昇順）で先頭から配置
-            for (i, val) in increments.iter().rev().enumerate() {
-                arranged[i] = *val;
-            }
+        // 前方集中型: 降順のまま先頭から配置（r = 0）
+        DistributionPattern::Front => arranged = alternating_block(increments, 0.0),
+        // 中央集中型: VBA Case 2 と同一の配置（r = 0.5）
+        DistributionPattern::Center => arranged = alternating_block(increments, 0.5),
+        // 後方集中型: 逆順（昇順）で先頭から配置（r = 1）
+        DistributionPattern::Rear => arranged = alternating_block(increments, 1.0),
+        DistributionPattern::ScsI => arranged = scs(ScsType::Type1, increments),
+        DistributionPattern::ScsIa => arranged = scs(ScsType::Type1a, increments),
+        DistributionPattern::ScsII => arranged = scs(ScsType::Type2, increments),
+        DistributionPattern::ScsIII => arranged = scs(ScsType::Type3, increments),
+        DistributionPattern::HuffQ1 => arranged = huff(HuffQuartile::First, options, increments),
+        DistributionPattern::HuffQ2 => arranged = huff(HuffQuartile::Second, options, increments),
+        DistributionPattern::HuffQ3 => arranged = huff(HuffQuartile::Third, options, increments),
+        DistributionPattern::HuffQ4 => arranged = huff(HuffQuartile::Fourth, options, increments),
+        DistributionPattern::Custom => {
+            let curve = options
+                .mass_curve
+                .as_ref()
+                .expect("Custom pattern requires a validated mass curve");
+            arranged = distribute(curve, increments);
         }
-        DistributionPattern::Center => {
-            // 中央集中型: VBA Case 2 と同一ロジック
-            // 0-based index j に対して:
-            //   j偶数 (I奇数): pos = nt/2 + j/2
-            //   j奇数 (I偶数): pos = nt/2 - 1 - j/2
-            let center = nt / 2;
-            for (j, val) in increments.iter().enumerate() {
-                let pos = if j % 2 == 0 {
-                    center + j / 2
-                } else {
-                    center - 1 - j / 2
-                };
-                arranged[pos] = *val;
-            }
+        DistributionPattern::MultiPeak => arranged = multi_peak(increments, &options.peaks),
+        DistributionPattern::Triangular => {
+            arranged = distribute(&Trapezoid::triangle(options.advancement), increments)
         }
+        DistributionPattern::Trapezoidal => arranged = distribute(&options.trapezoid, increments),
+    }
+
+    to_entries(arranged, t)
+}
+
+/// シカゴ型（Keifer-Chu）降雨: ピーク位置係数 r で交互ブロック法の配置を行う
+/// r: ピーク前の継続時間の比率（0: 前方集中, 1: 後方集中）
+pub fn arrange_with_peak_ratio(increments: &[f64], r: f64, t: f64) -> Vec<HyetographEntry> {
+    to_entries(alternating_block(increments, r), t)
+}
+
+/// 各ステップに置かれた増分雨量の降順の順位（0 が最大）
+/// 増分雨量を並べ替えるパターンのみ返し、累加雨量曲線で総量を配分するパターンは None を返す。
+pub fn ranks(
+    increments: &[f64],
+    pattern: DistributionPattern,
+    options: &PatternOptions,
+) -> Option<Vec<usize>> {
+    let order: Vec<usize> = (0..increments.len()).collect();
+    match pattern {
+        DistributionPattern::Front => Some(alternating_block(&order, 0.0)),
+        DistributionPattern::Center => Some(alternating_block(&order, 0.5)),
+        DistributionPattern::Rear => Some(alternating_block(&order, 1.0)),
+        DistributionPattern::MultiPeak => Some(multi_peak_order(increments, &options.peaks)),
+        _ => None,
     }
+}
 
+/// シカゴ型降雨の各ステップに置かれた増分雨量の降順の順位
+pub fn ranks_with_peak_ratio(nt: usize, r: f64) -> Vec<usize> {
+    alternating_block(&(0..nt).collect::<Vec<_>>(), r)
+}
+
+fn to_entries(arranged: Vec<f64>, t: f64) -> Vec<HyetographEntry> {
     arranged
         .into_iter()
         .enumerate()
         .map(|(i, intensity)| HyetographEntry {
             time_minutes: t * (i + 1) as f64,
             intensity,
+            areal_reduction_factor: None,
+            loss: None,
+            effective: None,
         })
         .collect()
 }
 
+/// 交互ブロック法
+/// 最大の増分をステップ floor(r * nt) に置き、以降は降順に、ピーク前に置いた個数の比率が
+/// r を下回れば前側、そうでなければ後側へ隣接して配置する。片側が埋まれば反対側に置く。
+/// r = 0.5 のとき VBA Case 2（j偶数: nt/2 + j/2, j奇数: nt/2 - 1 - j/2）と一致する。
+/// 配置は値によらず順位だけで決まるため、順位の列に適用すれば並べ替えの対応が得られる。
+fn alternating_block<T: Copy + Default>(increments: &[T], r: f64) -> Vec<T> {
+    let nt = increments.len();
+    let mut arranged = vec![T::default(); nt];
+    let Some((&peak, rest)) = increments.split_first() else {
+        return arranged;
+    };
+
+    let peak_pos = ((r * nt as f64).floor() as usize).min(nt - 1);
+    arranged[peak_pos] = peak;
+
+    let (mut before, mut after) = (0, 0);
+    for (j, val) in rest.iter().enumerate() {
+        let room_before = before < peak_pos;
+        let room_after = after < nt - 1 - peak_pos;
+        let wants_before = (before as f64) < r * (j + 1) as f64;
+        if room_before && (wants_before || !room_after) {
+            before += 1;
+            arranged[peak_pos - before] = *val;
+        } else {
+            after += 1;
+            arranged[peak_pos + after] = *val;
+        }
+    }
+
+    arranged
+}
+
+/// 多峰型: 時間軸をピークごとの区間に分け、各区間で交互ブロック法により配置する
+/// 区間の境界は隣り合うピーク位置の中点とし、各ピークは区間内のステップ round(位置 × NT) に置く。
+/// 増分は大きい順に、区間に空きがあるピークのうち配分比率に対する不足量が最大のものへ割り当てる。
+fn multi_peak(increments: &[f64], peaks: &[Peak]) -> Vec<f64> {
+    multi_peak_order(increments, peaks)
+        .into_iter()
+        .map(|rank| increments[rank])
+        .collect()
+}
+
+/// 多峰型の各ステップに置く増分雨量の順位
+fn multi_peak_order(increments: &[f64], peaks: &[Peak]) -> Vec<usize> {
+    let nt = increments.len();
+    if peaks.is_empty() || nt == 0 {
+        return (0..nt).collect();
+    }
+    let total: f64 = increments.iter().sum();
+    let step = |position: f64| ((position * nt as f64).round() as usize).min(nt - 1);
+
+    // 区間の境界（各区間に最低1ステップを確保する）
+    let mut bounds = vec![0; peaks.len() + 1];
+    bounds[peaks.len()] = nt;
+    for k in 1..peaks.len() {
+        let midpoint = ((peaks[k - 1].position + peaks[k].position) / 2.0 * nt as f64).round();
+        bounds[k] = (midpoint as usize).clamp(bounds[k - 1] + 1, nt - (peaks.len() - k));
+    }
+    let capacity: Vec<usize> = bounds.windows(2).map(|w| w[1] - w[0]).collect();
+
+    let mut groups: Vec<Vec<usize>> = vec![Vec::new(); peaks.len()];
+    let mut assigned = vec![0.0; peaks.len()];
+    for (rank, &val) in increments.iter().enumerate() {
+        let k = (0..peaks.len())
+            .filter(|&k| groups[k].len() < capacity[k])
+            .max_by(|&a, &b| {
+                let deficit = |k: usize| peaks[k].share * total - assigned[k];
+                deficit(a).total_cmp(&deficit(b))
+            })
+            .unwrap_or(0);
+        groups[k].push(rank);
+        assigned[k] += val;
+    }
+
+    let mut arranged = Vec::with_capacity(nt);
+    for ((peak, group), window) in peaks.iter().zip(&groups).zip(bounds.windows(2)) {
+        let (start, end) = (window[0], window[1]);
+        let local = step(peak.position).clamp(start, end - 1) - start;
+        // floor(r × 区間長) が区間内のピーク位置になる比率
+        let r = (local as f64 + 0.5) / group.len() as f64;
+        arranged.extend(alternating_block(group, r));
+    }
+    arranged
+}
+
+/// NRCS 24時間降雨: 時間軸を降雨継続時間で無次元化した累加曲線で総量を配分する
+fn scs(storm: ScsType, increments: &[f64]) -> Vec<f64> {
+    distribute(&mass_curve::scs(storm), increments)
+}
+
+/// Huff 曲線: 確率水準はバリデーション済みであること
+fn huff(quartile: HuffQuartile, options: &PatternOptions, increments: &[f64]) -> Vec<f64> {
+    let curve = mass_curve::huff(quartile, options.huff_probability)
+        .expect("Huff probability must be validated");
+    distribute(&curve, increments)
+}
+
+/// 増分雨量の合計を累加雨量曲線に沿って配分する
+fn distribute(curve: &impl CumulativeCurve, increments: &[f64]) -> Vec<f64> {
+    let total: f64 = increments.iter().sum();
+    curve.distribute(total, increments.len())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use clap::ValueEnum;
 
     const INCREMENTS: [f64; 12] = [
         141.179, 68.369, 46.819, 35.957, 29.354, 24.900, 21.684, 19.249, 17.339, 15.799, 14.530,
@@ -58,9 +238,32 @@ mod tests {
 
     const T: f64 = 10.0;
 
+    /// 全パターンを配置できる設定（Custom 用の曲線を含む）
+    fn all_pattern_options() -> PatternOptions {
+        PatternOptions {
+            mass_curve: Some(MassCurve::from_step_percent(&[20.0, 50.0, 30.0]).unwrap()),
+            peaks: vec![
+                Peak {
+                    position: 0.25,
+                    share: 0.4,
+                },
+                Peak {
+                    position: 0.75,
+                    share: 0.6,
+                },
+            ],
+            ..PatternOptions::default()
+        }
+    }
+
     #[test]
     fn front_pattern_places_descending_from_start() {
-        let result = arrange(&INCREMENTS, DistributionPattern::Front, T);
+        let result = arrange(
+            &INCREMENTS,
+            DistributionPattern::Front,
+            &PatternOptions::default(),
+            T,
+        );
         let expected = [
             141.179, 68.369, 46.819, 35.957, 29.354, 24.900, 21.684, 19.249, 17.339, 15.799,
             14.530, 13.465,
@@ -81,7 +284,12 @@ mod tests {
 
     #[test]
     fn center_pattern_matches_vba_case2() {
-        let result = arrange(&INCREMENTS, DistributionPattern::Center, T);
+        let result = arrange(
+            &INCREMENTS,
+            DistributionPattern::Center,
+            &PatternOptions::default(),
+            T,
+        );
         let expected = [
             13.465, 15.799, 19.249, 24.900, 35.957, 68.369, 141.179, 46.819, 29.354, 21.684,
             17.339, 14.530,
@@ -102,10 +310,15 @@ mod tests {
 
     #[test]
     fn rear_pattern_places_ascending_from_start() {
-        let result = arrange(&INCREMENTS, DistributionPattern::Rear, T);
+        let result = arrange(
+            &INCREMENTS,
+            DistributionPattern::Rear,
+            &PatternOptions::default(),
+            T,
+        );
         let expected = [
-            13.465, 14.530, 15.799, 17.339, 19.249, 21.684, 24.900, 29.354, 35.957, 46.819,
-            68.369, 141.179,
+            13.465, 14.530, 15.799, 17.339, 19.249, 21.684, 24.900, 29.354, 35.957, 46.819, 68.369,
+            141.179,
         ];
 
         assert_eq!(result.len(), 12);
@@ -125,12 +338,8 @@ mod tests {
     fn sum_is_preserved_across_all_patterns() {
         let original_sum: f64 = INCREMENTS.iter().sum();
 
-        for pattern in [
-            DistributionPattern::Front,
-            DistributionPattern::Center,
-            DistributionPattern::Rear,
-        ] {
-            let result = arrange(&INCREMENTS, pattern, T);
+        for &pattern in DistributionPattern::value_variants() {
+            let result = arrange(&INCREMENTS, pattern, &all_pattern_options(), T);
             let arranged_sum: f64 = result.iter().map(|e| e.intensity).sum();
             assert!(
                 (original_sum - arranged_sum).abs() < 1e-10,
@@ -144,15 +353,229 @@ mod tests {
 
     #[test]
     fn time_minutes_is_ascending() {
+        for &pattern in DistributionPattern::value_variants() {
+            let result = arrange(&INCREMENTS, pattern, &all_pattern_options(), T);
+            for i in 1..result.len() {
+                assert!(result[i].time_minutes > result[i - 1].time_minutes);
+            }
+        }
+    }
+
+    #[test]
+    fn scs_type_ii_follows_mass_curve() {
+        // 24時間を1時間刻みで配分
+        let increments = vec![10.0; 24];
+        let result = arrange(
+            &increments,
+            DistributionPattern::ScsII,
+            &PatternOptions::default(),
+            60.0,
+        );
+        assert_eq!(result.len(), 24);
+
+        // 11〜12時の増分は 240 * (0.663 - 0.235) = 102.72
+        assert!((result[11].intensity - 102.72).abs() < 1e-9);
+        assert_eq!(result[23].time_minutes, 1440.0);
+    }
+
+    #[test]
+    fn peak_ratio_extremes_match_front_and_rear() {
+        let front = arrange(
+            &INCREMENTS,
+            DistributionPattern::Front,
+            &PatternOptions::default(),
+            T,
+        );
+        let rear = arrange(
+            &INCREMENTS,
+            DistributionPattern::Rear,
+            &PatternOptions::default(),
+            T,
+        );
+        assert_eq!(arrange_with_peak_ratio(&INCREMENTS, 0.0, T), front);
+        assert_eq!(arrange_with_peak_ratio(&INCREMENTS, 1.0, T), rear);
+    }
+
+    #[test]
+    fn peak_ratio_half_matches_center_for_odd_and_even_nt() {
+        for nt in [5, 12] {
+            let increments = &INCREMENTS[..nt];
+            assert_eq!(
+                arrange_with_peak_ratio(increments, 0.5, T),
+                arrange(
+                    increments,
+                    DistributionPattern::Center,
+                    &PatternOptions::default(),
+                    T
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn peak_ratio_places_peak_and_keeps_blocks_adjacent() {
+        let result: Vec<f64> = arrange_with_peak_ratio(&INCREMENTS, 0.375, T)
+            .iter()
+            .map(|e| e.intensity)
+            .collect();
+        // floor(0.375 * 12) = 4
+        let peak = result
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, _)| i)
+            .unwrap();
+        assert_eq!(peak, 4);
+
+        // ピークから両側へ単調に減少する
+        for i in 0..peak {
+            assert!(result[i] <= result[i + 1]);
+        }
+        for i in peak..result.len() - 1 {
+            assert!(result[i] >= result[i + 1]);
+        }
+        let sum: f64 = result.iter().sum();
+        let original: f64 = INCREMENTS.iter().sum();
+        assert!((sum - original).abs() < 1e-10);
+    }
+
+    #[test]
+    fn huff_probability_changes_distribution() {
+        let steep = arrange(
+            &INCREMENTS,
+            DistributionPattern::HuffQ1,
+            &PatternOptions {
+                huff_probability: 10,
+                ..PatternOptions::default()
+            },
+            T,
+        );
+        let flat = arrange(
+            &INCREMENTS,
+            DistributionPattern::HuffQ1,
+            &PatternOptions {
+                huff_probability: 90,
+                ..PatternOptions::default()
+            },
+            T,
+        );
+        assert!(steep[0].intensity > flat[0].intensity);
+    }
+
+    #[test]
+    fn custom_pattern_follows_user_curve() {
+        let result = arrange(
+            &INCREMENTS,
+            DistributionPattern::Custom,
+            &all_pattern_options(),
+            T,
+        );
+        let total: f64 = INCREMENTS.iter().sum();
+        // 先頭3ステップ（時間比0.25まで）の累加は 0.2 * 0.25 / (1/3) = 0.15
+        let first_quarter: f64 = result[..3].iter().map(|e| e.intensity).sum();
+        assert!((first_quarter - total * 0.15).abs() < 1e-9);
+    }
+
+    #[test]
+    fn multi_peak_places_peaks_at_requested_positions() {
+        let result: Vec<f64> = arrange(
+            &INCREMENTS,
+            DistributionPattern::MultiPeak,
+            &all_pattern_options(),
+            T,
+        )
+        .iter()
+        .map(|e| e.intensity)
+        .collect();
+        let total: f64 = INCREMENTS.iter().sum();
+        assert!((result.iter().sum::<f64>() - total).abs() < 1e-10);
+
+        // 位置 0.25・0.75 は区間 [0, 6)・[6, 12) のステップ 3・9
+        let argmax = |range: std::ops::Range<usize>| {
+            range
+                .max_by(|&a, &b| result[a].total_cmp(&result[b]))
+                .unwrap()
+        };
+        assert_eq!(argmax(0..6), 3, "{:?}", result);
+        assert_eq!(argmax(6..12), 9, "{:?}", result);
+        // 最大の増分は配分の大きい後半のピークに置かれる
+        assert_eq!(result[9], INCREMENTS[0]);
+    }
+
+    #[test]
+    fn ranks_match_arranged_increments() {
+        let options = all_pattern_options();
         for pattern in [
             DistributionPattern::Front,
             DistributionPattern::Center,
             DistributionPattern::Rear,
+            DistributionPattern::MultiPeak,
         ] {
-            let result = arrange(&INCREMENTS, pattern, T);
-            for i in 1..result.len() {
-                assert!(result[i].time_minutes > result[i - 1].time_minutes);
+            let ranks = ranks(&INCREMENTS, pattern, &options).unwrap();
+            let arranged = arrange(&INCREMENTS, pattern, &options, T);
+            for (entry, &rank) in arranged.iter().zip(&ranks) {
+                assert_eq!(entry.intensity, INCREMENTS[rank], "{:?}", pattern);
             }
         }
+        // 中央集中型のピーク（ステップ 6）は最大の増分
+        assert_eq!(
+            ranks(&INCREMENTS, DistributionPattern::Center, &options).unwrap()[6],
+            0
+        );
+        assert!(ranks(&INCREMENTS, DistributionPattern::ScsII, &options).is_none());
+        assert_eq!(ranks_with_peak_ratio(12, 0.25)[3], 0);
+    }
+
+    #[test]
+    fn multi_peak_keeps_close_peaks_apart() {
+        // 0.1, 0.2 はステップ 1, 2（境界は中点 0.15 → ステップ 2）
+        let peaks = [
+            Peak {
+                position: 0.1,
+                share: 0.5,
+            },
+            Peak {
+                position: 0.2,
+                share: 0.5,
+            },
+        ];
+        let arranged = multi_peak(&INCREMENTS, &peaks);
+        assert!(arranged[1] > arranged[0], "{:?}", arranged);
+        let second = (2..12)
+            .max_by(|&a, &b| arranged[a].total_cmp(&arranged[b]))
+            .unwrap();
+        assert_eq!(second, 2, "{:?}", arranged);
+    }
+
+    #[test]
+    fn multi_peak_gives_every_peak_a_step() {
+        let peaks: Vec<Peak> = (0..4)
+            .map(|i| Peak {
+                position: i as f64 / 4.0,
+                share: if i == 0 { 0.97 } else { 0.01 },
+            })
+            .collect();
+        let arranged = multi_peak(&INCREMENTS[..4], &peaks);
+        let mut sorted = arranged.clone();
+        sorted.sort_by(|a, b| b.total_cmp(a));
+        assert_eq!(sorted, INCREMENTS[..4].to_vec());
+        assert_eq!(arranged[0], INCREMENTS[0]);
+    }
+
+    #[test]
+    fn triangular_peak_follows_advancement() {
+        let options = PatternOptions {
+            advancement: 0.25,
+            ..PatternOptions::default()
+        };
+        let result = arrange(&INCREMENTS, DistributionPattern::Triangular, &options, T);
+        let peak = result
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.intensity.total_cmp(&b.1.intensity))
+            .map(|(i, _)| i)
+            .unwrap();
+        // 時間比0.25はステップ3の始まり
+        assert!(peak == 2 || peak == 3, "peak at {}", peak);
     }
 }
@@ -0,0 +1,332 @@
+use anyhow::{bail, Result};
+
+use crate::formula::{Cleveland, IntensityFormula};
+
+/// 係数推定の初期値探索で試すAの範囲と刻み
+const A_SEARCH_MIN: f64 = 0.05;
+const A_SEARCH_MAX: f64 = 1.5;
+const A_SEARCH_STEP: f64 = 0.01;
+
+/// Levenberg-Marquardt法の反復上限と収束判定
+const MAX_ITERATIONS: usize = 500;
+const TOLERANCE: f64 = 1e-12;
+
+/// Bの下限（降雨強度式の係数としてB > 0でなければ再利用できないため）
+pub const B_MIN: f64 = 1e-6;
+
+/// 降雨強度式の当てはめ結果
+#[derive(Debug)]
+pub struct FitResult {
+    /// べき乗指数
+    pub a: f64,
+    /// 加算定数
+    pub b: f64,
+    /// 分子定数
+    pub c: f64,
+    /// 残差（推定値 - 観測値）[mm/h]
+    pub residuals: Vec<f64>,
+    /// 二乗平均平方根誤差[mm/h]
+    pub rmse: f64,
+    /// 決定係数
+    pub r_squared: f64,
+}
+
+impl FitResult {
+    /// Bが下限に張り付いたか（観測値はB ≤ 0の側で最もよく当てはまる）
+    pub fn b_at_bound(&self) -> bool {
+        self.b <= B_MIN * (1.0 + 1e-9)
+    }
+
+    /// 推定した係数による降雨強度式
+    pub fn formula(&self) -> Cleveland {
+        Cleveland {
+            a: self.c,
+            b: self.b,
+            n: self.a,
+        }
+    }
+}
+
+/// 観測された (継続時間[分], 降雨強度[mm/h]) の組に
+/// 降雨強度式 K = C / (t^A + B) を非線形最小二乗法で当てはめる
+///
+/// 初期値はAを格子探索し、各Aについて 1/K = t^A / C + B / C の線形回帰で求める。
+/// その後Levenberg-Marquardt法でA, B, Cを同時に最適化する。
+/// 推定した係数をそのまま降雨強度式に使えるよう、Bは B ≥ B_MIN (> 0) に制約する。
+pub fn fit(durations: &[f64], intensities: &[f64]) -> Result<FitResult> {
+    if durations.len() != intensities.len() {
+        bail!(
+            "Got {} durations but {} intensities",
+            durations.len(),
+            intensities.len()
+        );
+    }
+    if durations.len() < 3 {
+        bail!(
+            "At least 3 observations are required to fit A, B, C, got {}",
+            durations.len()
+        );
+    }
+    for (i, (&d, &k)) in durations.iter().zip(intensities.iter()).enumerate() {
+        if d <= 0.0 || k <= 0.0 {
+            bail!(
+                "Observation {}: duration and intensity must be positive, got ({}, {})",
+                i + 1,
+                d,
+                k
+            );
+        }
+    }
+
+    let Some(mut params) = initial_guess(durations, intensities) else {
+        bail!("Failed to find initial coefficients; intensities must decrease with duration");
+    };
+    let mut sse = sum_of_squares(&params, durations, intensities);
+    let mut lambda = 1e-3;
+
+    for _ in 0..MAX_ITERATIONS {
+        let (jtj, jtr) = normal_equations(&params, durations, intensities);
+
+        let mut damped = jtj;
+        for i in 0..3 {
+            damped[i][i] += lambda * jtj[i][i].max(1e-12);
+        }
+        let Some(delta) = solve3(damped, jtr.map(|v| -v)) else {
+            break;
+        };
+
+        // Bは下限に射影してB > 0を保つ
+        let candidate = [
+            params[0] + delta[0],
+            (params[1] + delta[1]).max(B_MIN),
+            params[2] + delta[2],
+        ];
+        let candidate_sse = sum_of_squares(&candidate, durations, intensities);
+
+        if candidate_sse.is_finite() && candidate_sse < sse {
+            let improvement = (sse - candidate_sse) / sse.max(f64::MIN_POSITIVE);
+            params = candidate;
+            sse = candidate_sse;
+            lambda = (lambda / 10.0).max(1e-15);
+            if improvement < TOLERANCE {
+                break;
+            }
+        } else {
+            lambda *= 10.0;
+            if lambda > 1e15 {
+                break;
+            }
+        }
+    }
+
+    let [a, b, c] = params;
+    let residuals: Vec<f64> = durations
+        .iter()
+        .zip(intensities.iter())
+        .map(|(&t, &k)| model(&params, t) - k)
+        .collect();
+
+    let n = intensities.len() as f64;
+    let mean = intensities.iter().sum::<f64>() / n;
+    let sst: f64 = intensities.iter().map(|k| (k - mean).powi(2)).sum();
+    let r_squared = if sst > 0.0 { 1.0 - sse / sst } else { 1.0 };
+
+    Ok(FitResult {
+        a,
+        b,
+        c,
+        residuals,
+        rmse: (sse / n).sqrt(),
+        r_squared,
+    })
+}
+
+/// K = C / (t^A + B)
+fn model(params: &[f64; 3], t: f64) -> f64 {
+    let [a, b, c] = *params;
+    Cleveland { a: c, b, n: a }.intensity(t)
+}
+
+fn sum_of_squares(params: &[f64; 3], durations: &[f64], intensities: &[f64]) -> f64 {
+    durations
+        .iter()
+        .zip(intensities.iter())
+        .map(|(&t, &k)| (model(params, t) - k).powi(2))
+        .sum()
+}
+
+/// Aの格子探索と線形回帰による初期値
+fn initial_guess(durations: &[f64], intensities: &[f64]) -> Option<[f64; 3]> {
+    let n = durations.len() as f64;
+    let ys: Vec<f64> = intensities.iter().map(|k| 1.0 / k).collect();
+    let mut best: Option<([f64; 3], f64)> = None;
+
+    let steps = ((A_SEARCH_MAX - A_SEARCH_MIN) / A_SEARCH_STEP).round() as usize;
+    for step in 0..=steps {
+        let a = A_SEARCH_MIN + A_SEARCH_STEP * step as f64;
+        let xs: Vec<f64> = durations.iter().map(|t| t.powf(a)).collect();
+
+        let mean_x = xs.iter().sum::<f64>() / n;
+        let mean_y = ys.iter().sum::<f64>() / n;
+        let sxx: f64 = xs.iter().map(|x| (x - mean_x).powi(2)).sum();
+        let sxy: f64 = xs
+            .iter()
+            .zip(ys.iter())
+            .map(|(x, y)| (x - mean_x) * (y - mean_y))
+            .sum();
+        if sxx <= 0.0 {
+            continue;
+        }
+        let slope = sxy / sxx;
+        if slope <= 0.0 {
+            continue;
+        }
+        let intercept = mean_y - slope * mean_x;
+
+        let c = 1.0 / slope;
+        let params = [a, (intercept * c).max(B_MIN), c];
+        let sse = sum_of_squares(&params, durations, intensities);
+        if sse.is_finite() && best.is_none_or(|(_, best_sse)| sse < best_sse) {
+            best = Some((params, sse));
+        }
+    }
+
+    best.map(|(params, _)| params)
+}
+
+/// ヤコビ行列から正規方程式 J^T J, J^T r を組み立てる
+fn normal_equations(
+    params: &[f64; 3],
+    durations: &[f64],
+    intensities: &[f64],
+) -> ([[f64; 3]; 3], [f64; 3]) {
+    let [a, b, c] = *params;
+    let mut jtj = [[0.0; 3]; 3];
+    let mut jtr = [0.0; 3];
+
+    for (&t, &k) in durations.iter().zip(intensities.iter()) {
+        let ta = t.powf(a);
+        let denom = ta + b;
+        let r = c / denom - k;
+        let j = [
+            -c * ta * t.ln() / (denom * denom),
+            -c / (denom * denom),
+            1.0 / denom,
+        ];
+        for row in 0..3 {
+            jtr[row] += j[row] * r;
+            for col in 0..3 {
+                jtj[row][col] += j[row] * j[col];
+            }
+        }
+    }
+
+    (jtj, jtr)
+}
+
+/// 3元連立一次方程式をピボット選択付きガウス消去で解く
+fn solve3(mut m: [[f64; 3]; 3], mut v: [f64; 3]) -> Option<[f64; 3]> {
+    for col in 0..3 {
+        let pivot = (col..3).max_by(|&i, &j| m[i][col].abs().total_cmp(&m[j][col].abs()))?;
+        if m[pivot][col].abs() < 1e-300 {
+            return None;
+        }
+        m.swap(col, pivot);
+        v.swap(col, pivot);
+        let pivot_row = m[col];
+        for row in col + 1..3 {
+            let factor = m[row][col] / pivot_row[col];
+            for (x, p) in m[row].iter_mut().zip(pivot_row.iter()).skip(col) {
+                *x -= factor * p;
+            }
+            v[row] -= factor * v[col];
+        }
+    }
+
+    let mut x = [0.0; 3];
+    for row in (0..3).rev() {
+        let sum: f64 = (row + 1..3).map(|k| m[row][k] * x[k]).sum();
+        x[row] = (v[row] - sum) / m[row][row];
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DURATIONS: [f64; 7] = [10.0, 20.0, 30.0, 60.0, 120.0, 180.0, 360.0];
+
+    fn vba_intensities() -> Vec<f64> {
+        DURATIONS
+            .iter()
+            .map(|t| 1557.825 / (t.powf(0.75) + 5.411))
+            .collect()
+    }
+
+    #[test]
+    fn fit_recovers_exact_coefficients() {
+        let result = fit(&DURATIONS, &vba_intensities()).unwrap();
+        assert!((result.a - 0.75).abs() < 1e-4, "A = {}", result.a);
+        assert!((result.b - 5.411).abs() < 1e-2, "B = {}", result.b);
+        assert!((result.c - 1557.825).abs() < 1e-1, "C = {}", result.c);
+        assert!(result.rmse < 1e-6, "RMSE = {}", result.rmse);
+        assert!((result.r_squared - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn fit_reports_residuals_for_noisy_data() {
+        let mut intensities = vba_intensities();
+        intensities[1] *= 1.03;
+        intensities[4] *= 0.97;
+
+        let result = fit(&DURATIONS, &intensities).unwrap();
+        assert_eq!(result.residuals.len(), DURATIONS.len());
+        assert!(result.rmse > 0.0);
+        assert!(result.r_squared > 0.99 && result.r_squared < 1.0);
+
+        // 残差は推定値 - 観測値
+        let formula = result.formula();
+        for ((&t, &k), r) in DURATIONS.iter().zip(&intensities).zip(&result.residuals) {
+            assert!((formula.intensity(t) - k - r).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn fit_keeps_b_positive() {
+        // B = -2 で生成した観測値でもBは正に制約される
+        let intensities: Vec<f64> = DURATIONS
+            .iter()
+            .map(|t| 1000.0 / (t.powf(0.7) - 2.0))
+            .collect();
+
+        let result = fit(&DURATIONS, &intensities).unwrap();
+        assert!(result.b > 0.0, "B = {}", result.b);
+        assert!(result.b_at_bound());
+        assert!(result.r_squared > 0.99, "R^2 = {}", result.r_squared);
+        assert!(!fit(&DURATIONS, &vba_intensities()).unwrap().b_at_bound());
+    }
+
+    #[test]
+    fn fit_requires_three_points() {
+        let err = fit(&[10.0, 20.0], &[100.0, 80.0]).unwrap_err().to_string();
+        assert!(err.contains("At least 3"), "Error: {}", err);
+    }
+
+    #[test]
+    fn fit_rejects_non_positive_values() {
+        let err = fit(&[10.0, 20.0, 30.0], &[100.0, 0.0, 60.0])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("must be positive"), "Error: {}", err);
+    }
+
+    #[test]
+    fn solve3_solves_linear_system() {
+        let m = [[2.0, 1.0, 0.0], [1.0, 3.0, 1.0], [0.0, 1.0, 4.0]];
+        let x = solve3(m, [3.0, 5.0, 5.0]).unwrap();
+        for (actual, expected) in x.iter().zip([1.0, 1.0, 1.0]) {
+            assert!((actual - expected).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use serde::Serialize;
+
+use crate::idf::IdfTable;
+
+/// 降雨強度式
+/// 継続時間 t[分] に対する平均降雨強度 K[mm/h] を返す。
+pub trait IntensityFormula {
+    /// 継続時間 t[分] の平均降雨強度[mm/h]
+    fn intensity(&self, t: f64) -> f64;
+}
+
+/// Talbot型 K = a / (t + b)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Talbot {
+    #[serde(rename = "C")]
+    pub a: f64,
+    #[serde(rename = "B")]
+    pub b: f64,
+}
+
+impl IntensityFormula for Talbot {
+    fn intensity(&self, t: f64) -> f64 {
+        self.a / (t + self.b)
+    }
+}
+
+/// Sherman型 K = a / t^n
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Sherman {
+    #[serde(rename = "C")]
+    pub a: f64,
+    #[serde(rename = "A")]
+    pub n: f64,
+}
+
+impl IntensityFormula for Sherman {
+    fn intensity(&self, t: f64) -> f64 {
+        self.a / t.powf(self.n)
+    }
+}
+
+/// Cleveland型 K = a / (t^n + b)
+/// 本ツール従来の降雨強度式 K = C / ((T * I)^A + B) はこの形。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Cleveland {
+    #[serde(rename = "C")]
+    pub a: f64,
+    #[serde(rename = "B")]
+    pub b: f64,
+    #[serde(rename = "A")]
+    pub n: f64,
+}
+
+impl IntensityFormula for Cleveland {
+    fn intensity(&self, t: f64) -> f64 {
+        self.a / (t.powf(self.n) + self.b)
+    }
+}
+
+/// 君島型 K = a / (t^n + b)^m
+/// 分母全体に別の指数 m を持つ。m = 1 のときCleveland型に一致する。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Kimijima {
+    #[serde(rename = "C")]
+    pub a: f64,
+    #[serde(rename = "B")]
+    pub b: f64,
+    #[serde(rename = "A")]
+    pub n: f64,
+    #[serde(rename = "M")]
+    pub m: f64,
+}
+
+impl IntensityFormula for Kimijima {
+    fn intensity(&self, t: f64) -> f64 {
+        self.a / (t.powf(self.n) + self.b).powf(self.m)
+    }
+}
+
+/// CLIで選択された降雨強度式
+/// JSON出力では "type" に式の型（talbot, sherman, cleveland, kimijima, table）を持ち、
+/// 係数はCLIで指定した名前（分子 C, 分母の定数 B, 指数 A, 君島型の外側の指数 M）で出力する。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Formula {
+    Talbot(Talbot),
+    Sherman(Sherman),
+    Cleveland(Cleveland),
+    Kimijima(Kimijima),
+    /// IDF表の補間
+    Table(IdfTable),
+}
+
+impl IntensityFormula for Formula {
+    fn intensity(&self, t: f64) -> f64 {
+        match self {
+            Self::Talbot(f) => f.intensity(t),
+            Self::Sherman(f) => f.intensity(t),
+            Self::Cleveland(f) => f.intensity(t),
+            Self::Kimijima(f) => f.intensity(t),
+            Self::Table(f) => f.intensity(t),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn talbot_evaluates_a_over_t_plus_b() {
+        let f = Talbot { a: 5000.0, b: 40.0 };
+        assert!((f.intensity(60.0) - 50.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn sherman_evaluates_a_over_t_pow_n() {
+        let f = Sherman { a: 400.0, n: 0.5 };
+        assert!((f.intensity(16.0) - 100.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn cleveland_matches_legacy_formula() {
+        let f = Cleveland {
+            a: 1557.825,
+            b: 5.411,
+            n: 0.75,
+        };
+        let expected = 1557.825 / (10.0_f64.powf(0.75) + 5.411);
+        assert!((f.intensity(10.0) - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn kimijima_with_unit_m_equals_cleveland() {
+        let cleveland = Cleveland {
+            a: 1557.825,
+            b: 5.411,
+            n: 0.75,
+        };
+        let kimijima = Kimijima {
+            a: 1557.825,
+            b: 5.411,
+            n: 0.75,
+            m: 1.0,
+        };
+        for t in [10.0, 30.0, 60.0, 120.0] {
+            assert!((cleveland.intensity(t) - kimijima.intensity(t)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn kimijima_applies_outer_exponent() {
+        let f = Kimijima {
+            a: 100.0,
+            b: 0.0,
+            n: 1.0,
+            m: 2.0,
+        };
+        assert!((f.intensity(5.0) - 4.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn formula_enum_dispatches_to_inner() {
+        let inner = Talbot { a: 5000.0, b: 40.0 };
+        let formula = Formula::Talbot(inner);
+        assert_eq!(formula.intensity(30.0), inner.intensity(30.0));
+    }
+}
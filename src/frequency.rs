@@ -0,0 +1,611 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use crate::types::ProbabilityDistribution;
+
+/// 1継続時間あたりに必要な最小標本数
+const MIN_SAMPLES: usize = 5;
+
+/// SLSCの正規化に用いる非超過確率
+const SLSC_UPPER: f64 = 0.99;
+const SLSC_LOWER: f64 = 0.01;
+
+/// オイラー定数
+const EULER_GAMMA: f64 = 0.577_215_664_901_532_9;
+
+/// 継続時間ごとの年最大降雨強度系列
+#[derive(Debug)]
+pub struct AnnualMaxima {
+    /// 継続時間[分]
+    pub durations: Vec<f64>,
+    /// 継続時間ごとの年最大降雨強度[mm/h]
+    pub series: Vec<Vec<f64>>,
+}
+
+/// 年最大値系列をCSVファイルから読み込む
+///
+/// 1列目は年（読み飛ばす）、2列目以降のヘッダーは継続時間[分]とする。空欄は欠測として扱う。
+/// depth が true の場合、値を継続時間内の雨量[mm]とみなして降雨強度[mm/h]に換算する。
+pub fn load(path: &Path, depth: bool) -> Result<AnnualMaxima> {
+    let mut rdr = csv::Reader::from_path(path)
+        .with_context(|| format!("Failed to open annual maxima: {}", path.display()))?;
+
+    let headers = rdr
+        .headers()
+        .with_context(|| format!("Failed to read header of {}", path.display()))?
+        .clone();
+    if headers.len() < 2 {
+        bail!(
+            "Annual maxima must have a year column and at least one duration column: {}",
+            path.display()
+        );
+    }
+
+    let mut durations = Vec::with_capacity(headers.len() - 1);
+    for header in headers.iter().skip(1) {
+        let duration: f64 = header.trim().parse().with_context(|| {
+            format!("Duration column header must be minutes, got '{}'", header)
+        })?;
+        if duration <= 0.0 {
+            bail!("Duration must be positive, got {}", duration);
+        }
+        durations.push(duration);
+    }
+
+    let mut series = vec![Vec::new(); durations.len()];
+    for (row, record) in rdr.records().enumerate() {
+        let record = record
+            .with_context(|| format!("Failed to read annual maxima row in {}", path.display()))?;
+        for (col, field) in record.iter().skip(1).enumerate() {
+            let field = field.trim();
+            if field.is_empty() || col >= durations.len() {
+                continue;
+            }
+            let value: f64 = field.parse().with_context(|| {
+                format!("Row {}: invalid value '{}'", row + 1, field)
+            })?;
+            if value <= 0.0 {
+                bail!("Row {}: annual maximum must be positive, got {}", row + 1, value);
+            }
+            let intensity = if depth {
+                value * 60.0 / durations[col]
+            } else {
+                value
+            };
+            series[col].push(intensity);
+        }
+    }
+
+    // IDF表として出力できるよう継続時間の昇順に並べる
+    let mut columns: Vec<(f64, Vec<f64>)> = durations.into_iter().zip(series).collect();
+    columns.sort_by(|a, b| a.0.total_cmp(&b.0));
+    if let Some(pair) = columns.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        bail!("Duplicate duration column {} in {}", pair[0].0, path.display());
+    }
+    let (durations, series) = columns.into_iter().unzip();
+
+    Ok(AnnualMaxima { durations, series })
+}
+
+/// 当てはめた確率分布
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fitted {
+    /// Gumbel分布（L積率法）
+    Gumbel { xi: f64, alpha: f64 },
+    /// 一般化極値分布（L積率法、Hoskingの形状母数 k）
+    Gev { xi: f64, alpha: f64, k: f64 },
+    /// 対数正規分布（対数値の積率法）
+    LogNormal { mu: f64, sigma: f64 },
+    /// 対数ピアソンIII型分布（対数値の積率法）
+    LogPearson3 { mu: f64, sigma: f64, g: f64 },
+}
+
+impl Fitted {
+    /// 非超過確率 p に対する確率水文量
+    pub fn quantile(&self, p: f64) -> f64 {
+        match *self {
+            Self::Gumbel { xi, alpha } => xi - alpha * (-p.ln()).ln(),
+            Self::Gev { xi, alpha, k } => {
+                if k.abs() < 1e-9 {
+                    xi - alpha * (-p.ln()).ln()
+                } else {
+                    xi + alpha * (1.0 - (-p.ln()).powf(k)) / k
+                }
+            }
+            Self::LogNormal { mu, sigma } => (mu + sigma * normal_quantile(p)).exp(),
+            Self::LogPearson3 { mu, sigma, g } => {
+                (mu + sigma * frequency_factor(normal_quantile(p), g)).exp()
+            }
+        }
+    }
+
+    /// 標本値 x を分布の標準変量に変換する
+    fn standardize(&self, x: f64) -> f64 {
+        match *self {
+            Self::Gumbel { xi, alpha } => (x - xi) / alpha,
+            Self::Gev { xi, alpha, k } => {
+                if k.abs() < 1e-9 {
+                    (x - xi) / alpha
+                } else {
+                    -(1.0 - k * (x - xi) / alpha).max(f64::MIN_POSITIVE).ln() / k
+                }
+            }
+            Self::LogNormal { mu, sigma } | Self::LogPearson3 { mu, sigma, .. } => {
+                (x.ln() - mu) / sigma
+            }
+        }
+    }
+
+    /// 非超過確率 p に対する標準変量
+    fn standard_quantile(&self, p: f64) -> f64 {
+        match *self {
+            Self::Gumbel { .. } | Self::Gev { .. } => -(-p.ln()).ln(),
+            Self::LogNormal { .. } => normal_quantile(p),
+            Self::LogPearson3 { g, .. } => frequency_factor(normal_quantile(p), g),
+        }
+    }
+}
+
+impl std::fmt::Display for Fitted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Gumbel { xi, alpha } => write!(f, "xi={:.4} alpha={:.4}", xi, alpha),
+            Self::Gev { xi, alpha, k } => write!(f, "xi={:.4} alpha={:.4} k={:.4}", xi, alpha, k),
+            Self::LogNormal { mu, sigma } => write!(f, "mu={:.4} sigma={:.4}", mu, sigma),
+            Self::LogPearson3 { mu, sigma, g } => {
+                write!(f, "mu={:.4} sigma={:.4} g={:.4}", mu, sigma, g)
+            }
+        }
+    }
+}
+
+/// 標本に確率分布を当てはめる
+pub fn fit(distribution: ProbabilityDistribution, sample: &[f64]) -> Result<Fitted> {
+    if sample.len() < 3 {
+        bail!("At least 3 samples are required, got {}", sample.len());
+    }
+
+    let fitted = match distribution {
+        ProbabilityDistribution::Gumbel => {
+            let (l1, l2, _) = l_moments(sample);
+            let alpha = l2 / std::f64::consts::LN_2;
+            Fitted::Gumbel {
+                xi: l1 - EULER_GAMMA * alpha,
+                alpha,
+            }
+        }
+        ProbabilityDistribution::Gev => {
+            let (l1, l2, l3) = l_moments(sample);
+            let t3 = l3 / l2;
+            let c = 2.0 / (3.0 + t3) - std::f64::consts::LN_2 / 3.0_f64.ln();
+            let k = 7.8590 * c + 2.9554 * c * c;
+            let gamma = ln_gamma(1.0 + k).exp();
+            let alpha = l2 * k / ((1.0 - 2.0_f64.powf(-k)) * gamma);
+            Fitted::Gev {
+                xi: l1 + alpha * (gamma - 1.0) / k,
+                alpha,
+                k,
+            }
+        }
+        ProbabilityDistribution::LogNormal => {
+            let logs: Vec<f64> = sample.iter().map(|x| x.ln()).collect();
+            let (mu, sigma, _) = moments(&logs);
+            Fitted::LogNormal { mu, sigma }
+        }
+        ProbabilityDistribution::LogPearson3 => {
+            let logs: Vec<f64> = sample.iter().map(|x| x.ln()).collect();
+            let (mu, sigma, g) = moments(&logs);
+            Fitted::LogPearson3 { mu, sigma, g }
+        }
+    };
+
+    Ok(fitted)
+}
+
+/// 1分布の評価結果
+#[derive(Debug, Clone, Copy)]
+pub struct Evaluation {
+    pub distribution: ProbabilityDistribution,
+    pub fitted: Fitted,
+    /// 標準最小二乗規準
+    pub slsc: f64,
+    /// 再現期間に対応する確率降雨強度[mm/h]
+    pub quantile: f64,
+    /// ジャックナイフ法による確率降雨強度の標準誤差[mm/h]
+    pub jackknife_se: f64,
+}
+
+/// 分布を当てはめ、SLSC・確率降雨強度・ジャックナイフ推定誤差を求める
+pub fn evaluate(
+    distribution: ProbabilityDistribution,
+    sample: &[f64],
+    return_period: f64,
+) -> Result<Evaluation> {
+    let p = 1.0 - 1.0 / return_period;
+    let fitted = fit(distribution, sample)?;
+    let quantile = fitted.quantile(p);
+
+    let n = sample.len();
+    let mut jackknife = Vec::with_capacity(n);
+    for skip in 0..n {
+        let subset: Vec<f64> = sample
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != skip)
+            .map(|(_, &x)| x)
+            .collect();
+        jackknife.push(fit(distribution, &subset)?.quantile(p));
+    }
+    let mean = jackknife.iter().sum::<f64>() / n as f64;
+    let variance = jackknife.iter().map(|x| (x - mean).powi(2)).sum::<f64>() * (n - 1) as f64
+        / n as f64;
+
+    Ok(Evaluation {
+        distribution,
+        fitted,
+        slsc: slsc(&fitted, sample),
+        quantile,
+        jackknife_se: variance.sqrt(),
+    })
+}
+
+/// 継続時間1つ分の解析結果
+#[derive(Debug)]
+pub struct DurationResult {
+    /// 継続時間[分]
+    pub duration: f64,
+    /// 標本数
+    pub samples: usize,
+    /// 各分布の評価結果
+    pub evaluations: Vec<Evaluation>,
+    /// 採用した分布の添字
+    pub selected: usize,
+}
+
+impl DurationResult {
+    /// 採用した分布の評価結果
+    pub fn selected(&self) -> &Evaluation {
+        &self.evaluations[self.selected]
+    }
+}
+
+/// 全継続時間について確率降雨強度を求める
+/// distribution が None の場合は継続時間ごとにSLSC最小の分布を採用する。
+pub fn analyze(
+    maxima: &AnnualMaxima,
+    return_period: f64,
+    distribution: Option<ProbabilityDistribution>,
+) -> Result<Vec<DurationResult>> {
+    if return_period <= 1.0 {
+        bail!(
+            "Return period must be greater than 1 year, got {}",
+            return_period
+        );
+    }
+
+    let mut results = Vec::with_capacity(maxima.durations.len());
+    for (&duration, sample) in maxima.durations.iter().zip(&maxima.series) {
+        if sample.len() < MIN_SAMPLES {
+            bail!(
+                "Duration {} min has {} annual maxima; at least {} are required",
+                duration,
+                sample.len(),
+                MIN_SAMPLES
+            );
+        }
+
+        let mut evaluations = Vec::new();
+        for &candidate in ProbabilityDistribution::ALL {
+            let evaluation = evaluate(candidate, sample, return_period).with_context(|| {
+                format!("Failed to fit {:?} for duration {} min", candidate, duration)
+            })?;
+            evaluations.push(evaluation);
+        }
+
+        let selected = match distribution {
+            Some(choice) => evaluations
+                .iter()
+                .position(|e| e.distribution == choice)
+                .expect("all distributions are evaluated"),
+            None => evaluations
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| e.slsc.is_finite())
+                .min_by(|(_, a), (_, b)| a.slsc.total_cmp(&b.slsc))
+                .map(|(i, _)| i)
+                .unwrap_or(0),
+        };
+
+        results.push(DurationResult {
+            duration,
+            samples: sample.len(),
+            evaluations,
+            selected,
+        });
+    }
+
+    Ok(results)
+}
+
+/// SLSC（標準最小二乗規準）
+/// Cunnaneプロット位置による標準変量と、当てはめ分布による標本の標準変量との差を
+/// 非超過確率0.99と0.01の標準変量の幅で正規化する。
+fn slsc(fitted: &Fitted, sample: &[f64]) -> f64 {
+    let mut sorted = sample.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let n = sorted.len() as f64;
+
+    let sum_sq: f64 = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| {
+            let p = (i as f64 + 1.0 - 0.4) / (n + 0.2);
+            (fitted.standardize(x) - fitted.standard_quantile(p)).powi(2)
+        })
+        .sum();
+
+    let range = (fitted.standard_quantile(SLSC_UPPER) - fitted.standard_quantile(SLSC_LOWER)).abs();
+    (sum_sq / n).sqrt() / range
+}
+
+/// 標本L積率 (λ1, λ2, λ3)
+fn l_moments(sample: &[f64]) -> (f64, f64, f64) {
+    let mut sorted = sample.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let n = sorted.len() as f64;
+
+    let mut b0 = 0.0;
+    let mut b1 = 0.0;
+    let mut b2 = 0.0;
+    for (i, &x) in sorted.iter().enumerate() {
+        let i = i as f64;
+        b0 += x;
+        b1 += x * i / (n - 1.0);
+        b2 += x * i * (i - 1.0) / ((n - 1.0) * (n - 2.0));
+    }
+    b0 /= n;
+    b1 /= n;
+    b2 /= n;
+
+    (b0, 2.0 * b1 - b0, 6.0 * b2 - 6.0 * b1 + b0)
+}
+
+/// 平均・不偏標準偏差・偏り補正した歪み係数
+fn moments(values: &[f64]) -> (f64, f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let sd = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+    let skew = if sd > 0.0 {
+        n / ((n - 1.0) * (n - 2.0)) * values.iter().map(|v| ((v - mean) / sd).powi(3)).sum::<f64>()
+    } else {
+        0.0
+    };
+    (mean, sd, skew)
+}
+
+/// ピアソンIII型分布の頻度係数（Wilson-Hilferty近似）
+fn frequency_factor(z: f64, g: f64) -> f64 {
+    if g.abs() < 1e-6 {
+        return z;
+    }
+    let k = g / 6.0;
+    ((1.0 + k * z - k * k).powi(3) - 1.0) / (3.0 * k)
+}
+
+/// 標準正規分布の逆関数（Acklamの有理近似）
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const P_LOW: f64 = 0.02425;
+
+    if p < P_LOW {
+        let q = (-2.0 * p.ln()).sqrt();
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    } else if p <= 1.0 - P_LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    } else {
+        -normal_quantile(1.0 - p)
+    }
+}
+
+/// ガンマ関数の自然対数（Lanczos近似）
+fn ln_gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEF: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let mut a = COEF[0];
+    let t = x + G + 0.5;
+    for (i, &c) in COEF.iter().enumerate().skip(1) {
+        a += c / (x + i as f64);
+    }
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + a.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Gumbel(ξ=50, α=10) の非超過確率 (i-0.4)/(N+0.2) に対応する値
+    fn gumbel_sample(n: usize) -> Vec<f64> {
+        (1..=n)
+            .map(|i| {
+                let p = (i as f64 - 0.4) / (n as f64 + 0.2);
+                50.0 - 10.0 * (-p.ln()).ln()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn normal_quantile_matches_known_values() {
+        assert!(normal_quantile(0.5).abs() < 1e-9);
+        assert!((normal_quantile(0.975) - 1.959_964).abs() < 1e-5);
+        assert!((normal_quantile(0.01) + 2.326_348).abs() < 1e-5);
+    }
+
+    #[test]
+    fn ln_gamma_matches_factorials() {
+        assert!(ln_gamma(1.0).abs() < 1e-12);
+        assert!((ln_gamma(5.0) - 24.0_f64.ln()).abs() < 1e-10);
+        assert!((ln_gamma(0.5) - std::f64::consts::PI.sqrt().ln()).abs() < 1e-10);
+    }
+
+    #[test]
+    fn gumbel_fit_recovers_parameters() {
+        let sample = gumbel_sample(200);
+        let Fitted::Gumbel { xi, alpha } = fit(ProbabilityDistribution::Gumbel, &sample).unwrap()
+        else {
+            panic!("expected Gumbel");
+        };
+        assert!((xi - 50.0).abs() < 1.0, "xi = {}", xi);
+        assert!((alpha - 10.0).abs() < 0.5, "alpha = {}", alpha);
+    }
+
+    #[test]
+    fn gev_shape_is_near_zero_for_gumbel_sample() {
+        let sample = gumbel_sample(200);
+        let Fitted::Gev { k, .. } = fit(ProbabilityDistribution::Gev, &sample).unwrap() else {
+            panic!("expected GEV");
+        };
+        assert!(k.abs() < 0.05, "k = {}", k);
+    }
+
+    #[test]
+    fn quantiles_increase_with_return_period() {
+        let sample = gumbel_sample(40);
+        for &distribution in ProbabilityDistribution::ALL {
+            let fitted = fit(distribution, &sample).unwrap();
+            let q10 = fitted.quantile(0.9);
+            let q100 = fitted.quantile(0.99);
+            assert!(q100 > q10, "{:?}: {} <= {}", distribution, q100, q10);
+        }
+    }
+
+    #[test]
+    fn gumbel_slsc_is_small_for_gumbel_sample() {
+        let sample = gumbel_sample(40);
+        let evaluation = evaluate(ProbabilityDistribution::Gumbel, &sample, 50.0).unwrap();
+        assert!(evaluation.slsc < 0.02, "SLSC = {}", evaluation.slsc);
+        assert!(evaluation.jackknife_se > 0.0);
+    }
+
+    #[test]
+    fn analyze_selects_requested_distribution() {
+        let maxima = AnnualMaxima {
+            durations: vec![10.0, 60.0],
+            series: vec![gumbel_sample(30), gumbel_sample(30).iter().map(|x| x / 2.0).collect()],
+        };
+        let results = analyze(&maxima, 50.0, Some(ProbabilityDistribution::Gev)).unwrap();
+        assert_eq!(results.len(), 2);
+        for result in &results {
+            assert_eq!(result.evaluations.len(), ProbabilityDistribution::ALL.len());
+            assert_eq!(result.selected().distribution, ProbabilityDistribution::Gev);
+        }
+    }
+
+    #[test]
+    fn analyze_picks_minimum_slsc_by_default() {
+        let maxima = AnnualMaxima {
+            durations: vec![10.0],
+            series: vec![gumbel_sample(30)],
+        };
+        let results = analyze(&maxima, 50.0, None).unwrap();
+        let min = results[0]
+            .evaluations
+            .iter()
+            .map(|e| e.slsc)
+            .fold(f64::INFINITY, f64::min);
+        assert_eq!(results[0].selected().slsc, min);
+    }
+
+    #[test]
+    fn analyze_rejects_short_series() {
+        let maxima = AnnualMaxima {
+            durations: vec![10.0],
+            series: vec![vec![10.0, 20.0, 30.0]],
+        };
+        let err = analyze(&maxima, 50.0, None).unwrap_err().to_string();
+        assert!(err.contains("at least 5"), "Error: {}", err);
+    }
+
+    #[test]
+    fn load_reads_wide_csv_and_converts_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("maxima.csv");
+        fs::write(&path, "year,10,60\n2001,20,60\n2002,25,\n2003,15,45\n").unwrap();
+
+        let maxima = load(&path, true).unwrap();
+        assert_eq!(maxima.durations, vec![10.0, 60.0]);
+        assert_eq!(maxima.series[0], vec![120.0, 150.0, 90.0]);
+        assert_eq!(maxima.series[1], vec![60.0, 45.0]);
+    }
+
+    #[test]
+    fn load_sorts_duration_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("maxima.csv");
+        fs::write(&path, "year,60,10\n2001,40,90\n2002,30,\n").unwrap();
+
+        let maxima = load(&path, false).unwrap();
+        assert_eq!(maxima.durations, vec![10.0, 60.0]);
+        assert_eq!(maxima.series[0], vec![90.0]);
+        assert_eq!(maxima.series[1], vec![40.0, 30.0]);
+    }
+
+    #[test]
+    fn load_rejects_duplicate_durations() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("maxima.csv");
+        fs::write(&path, "year,10,10\n2001,40,90\n").unwrap();
+
+        let err = load(&path, false).unwrap_err().to_string();
+        assert!(err.contains("Duplicate duration"), "Error: {}", err);
+    }
+}
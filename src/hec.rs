@@ -0,0 +1,366 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::{Duration, NaiveDate, NaiveDateTime, Timelike};
+
+use crate::types::HyetographEntry;
+
+/// HEC-DSS の正規時間間隔[分]とEパート
+const DSS_INTERVALS: &[(u32, &str)] = &[
+    (1, "1MIN"),
+    (2, "2MIN"),
+    (3, "3MIN"),
+    (4, "4MIN"),
+    (5, "5MIN"),
+    (6, "6MIN"),
+    (10, "10MIN"),
+    (12, "12MIN"),
+    (15, "15MIN"),
+    (20, "20MIN"),
+    (30, "30MIN"),
+    (60, "1HOUR"),
+    (120, "2HOUR"),
+    (180, "3HOUR"),
+    (240, "4HOUR"),
+    (360, "6HOUR"),
+    (480, "8HOUR"),
+    (720, "12HOUR"),
+    (1440, "1DAY"),
+];
+
+/// DSS パス名のCパート（増分雨量）
+const PRECIP_PARAMETER: &str = "PRECIP-INC";
+
+/// DSS パス名のFパート
+const DSS_VERSION: &str = "HYETOGRAPH-CLI";
+
+/// HEC-RAS の非定常流ファイルの1行あたりの値の数
+const RAS_VALUES_PER_LINE: usize = 10;
+
+/// HEC-HMS・HEC-RAS 出力の設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct HecExport {
+    /// 降雨開始日時
+    pub start: NaiveDateTime,
+    /// DSS パス名のBパート（地点名）
+    pub location: String,
+    /// DSS パス名のEパート（時間間隔）
+    pub interval: &'static str,
+}
+
+impl HecExport {
+    /// 計算時間刻み t[分] が DSS の正規時間間隔であることを確認して構築する
+    pub fn new(start: NaiveDateTime, location: &str, t: f64) -> Result<Self> {
+        let Some(&(_, interval)) = DSS_INTERVALS
+            .iter()
+            .find(|&&(minutes, _)| (minutes as f64 - t).abs() < 1e-9)
+        else {
+            let valid = DSS_INTERVALS
+                .iter()
+                .map(|(minutes, _)| minutes.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            bail!(
+                "T={} min is not a HEC-DSS regular interval. Valid values: {} min",
+                t,
+                valid
+            );
+        };
+        if location.is_empty() || location.contains('/') {
+            bail!(
+                "HEC location must be non-empty and must not contain '/', got '{}'",
+                location
+            );
+        }
+        Ok(Self {
+            start,
+            location: location.to_ascii_uppercase(),
+            interval,
+        })
+    }
+
+    /// 各ステップの終了日時と増分雨量[mm]
+    fn increments(&self, data: &[HyetographEntry], t: f64) -> Vec<(NaiveDateTime, f64)> {
+        data.iter()
+            .map(|entry| {
+                let end =
+                    self.start + Duration::seconds((entry.time_minutes * 60.0).round() as i64);
+                (end, entry.intensity * t / 60.0)
+            })
+            .collect()
+    }
+
+    /// DSS パス名 /A/B/C/D/E/F/
+    pub fn pathname(&self) -> String {
+        format!(
+            "//{}/{}/{}/{}/{}/",
+            self.location,
+            PRECIP_PARAMETER,
+            self.start.format("%d%b%Y").to_string().to_ascii_uppercase(),
+            self.interval,
+            DSS_VERSION
+        )
+    }
+
+    /// HEC-HMS の時系列ゲージ（指定ハイエトグラフ用の増分雨量）の表
+    /// 時刻は区間終了時刻で、HEC-HMS のタブ区切り表にそのまま貼り付けられる。
+    pub fn hms_table(&self, data: &[HyetographEntry], t: f64) -> String {
+        let mut lines = vec![
+            format!("Gage: {}", self.location),
+            "Gage Type: Precipitation".to_string(),
+            "Precipitation Units: Incremental Millimeters".to_string(),
+            format!("Time Interval: {} Minutes", t),
+            format!("Start Time: {}", self.start.format("%-d %B %Y, %H:%M")),
+            format!(
+                "End Time: {}",
+                hms_datetime(
+                    self.start + Duration::seconds((data.len() as f64 * t * 60.0).round() as i64)
+                )
+            ),
+            String::new(),
+            "Date\tTime\tPrecipitation (MM)".to_string(),
+        ];
+        lines.extend(self.increments(data, t).into_iter().map(|(end, depth)| {
+            let (date, time) = end_of_period(end);
+            format!(
+                "{}\t{}:{}\t{:.4}",
+                date.format("%d%b%Y"),
+                &time[..2],
+                &time[2..],
+                depth
+            )
+        }));
+        lines.push(String::new());
+        lines.join("\n")
+    }
+
+    /// HEC-DSSVue の表形式インポート用テキスト（パス名の各パート・単位・データ型と値）
+    pub fn dss_text(&self, data: &[HyetographEntry], t: f64) -> String {
+        let d_part = self.start.format("%d%b%Y").to_string().to_ascii_uppercase();
+        let mut lines = vec![
+            "A\t".to_string(),
+            format!("B\t{}", self.location),
+            format!("C\t{}", PRECIP_PARAMETER),
+            format!("D\t{}", d_part),
+            format!("E\t{}", self.interval),
+            format!("F\t{}", DSS_VERSION),
+            "Units\tMM".to_string(),
+            "Type\tPER-CUM".to_string(),
+        ];
+        lines.extend(self.increments(data, t).into_iter().map(|(end, depth)| {
+            let (date, time) = end_of_period(end);
+            format!("{} {}\t{:.4}", date.format("%d%b%Y"), time, depth)
+        }));
+        lines.push(String::new());
+        lines.join("\n")
+    }
+
+    /// HEC-RAS 非定常流ファイル（.u##）の境界条件に貼り付ける降雨ハイドログラフ
+    /// 開始時刻の 0 に続けて各区間の増分雨量[mm]を8桁幅・1行10個で並べる。
+    pub fn ras_block(&self, data: &[HyetographEntry], t: f64) -> String {
+        let values: Vec<f64> = std::iter::once(0.0)
+            .chain(self.increments(data, t).into_iter().map(|(_, depth)| depth))
+            .collect();
+        let mut lines = vec![
+            format!("Interval={}", self.interval),
+            "Use Fixed Start Time=True".to_string(),
+            format!(
+                "Fixed Start Date/Time={},{}",
+                self.start.format("%d%b%Y").to_string().to_ascii_uppercase(),
+                self.start.format("%H%M")
+            ),
+            format!("Precipitation Hydrograph= {} ", values.len()),
+        ];
+        lines.extend(
+            values
+                .chunks(RAS_VALUES_PER_LINE)
+                .map(|chunk| chunk.iter().map(|v| ras_value(*v)).collect::<String>()),
+        );
+        lines.push(String::new());
+        lines.join("\n")
+    }
+
+    /// HEC-HMS 用のゲージ表と DSS インポート用テキストを出力する
+    /// 返却値は書き込んだファイルのパス。
+    pub fn write_hms(
+        &self,
+        data: &[HyetographEntry],
+        t: f64,
+        output_path: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        self.write_files(
+            &[
+                ("hms", self.hms_table(data, t)),
+                ("dss", self.dss_text(data, t)),
+            ],
+            output_path,
+        )
+    }
+
+    /// HEC-RAS 用の降雨ハイドログラフと DSS インポート用テキストを出力する
+    /// 返却値は書き込んだファイルのパス。
+    pub fn write_ras(
+        &self,
+        data: &[HyetographEntry],
+        t: f64,
+        output_path: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        self.write_files(
+            &[
+                ("ras", self.ras_block(data, t)),
+                ("dss", self.dss_text(data, t)),
+            ],
+            output_path,
+        )
+    }
+
+    fn write_files(&self, files: &[(&str, String)], output_path: &Path) -> Result<Vec<PathBuf>> {
+        files
+            .iter()
+            .map(|(suffix, content)| {
+                let path = self.timestamped_path(output_path, suffix);
+                fs::write(&path, content)
+                    .with_context(|| format!("Failed to create HEC file: {}", path.display()))?;
+                Ok(path)
+            })
+            .collect()
+    }
+
+    /// 開始日時を付けた出力先（<出力ファイル名>_<YYYYMMDDHHMM>_<suffix>.txt）
+    pub fn timestamped_path(&self, output_path: &Path, suffix: &str) -> PathBuf {
+        let stem = output_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        output_path.with_file_name(format!(
+            "{}_{}_{}.txt",
+            stem,
+            self.start.format("%Y%m%d%H%M"),
+            suffix
+        ))
+    }
+}
+
+/// 区間終了時刻の日付と時刻（HEC の慣例で 0 時は前日の 24:00 とする）
+fn end_of_period(time: NaiveDateTime) -> (NaiveDate, String) {
+    if time.hour() == 0 && time.minute() == 0 {
+        (time.date() - Duration::days(1), "2400".to_string())
+    } else {
+        (time.date(), time.format("%H%M").to_string())
+    }
+}
+
+/// HEC-HMS の終了日時の表記（例: 1 June 2024, 24:00）
+fn hms_datetime(time: NaiveDateTime) -> String {
+    let (date, clock) = end_of_period(time);
+    format!(
+        "{}, {}:{}",
+        date.format("%-d %B %Y"),
+        &clock[..2],
+        &clock[2..]
+    )
+}
+
+/// HEC-RAS の8桁固定幅の値
+fn ras_value(value: f64) -> String {
+    let text = format!("{:.4}", value);
+    let text = if text.len() > 8 {
+        format!("{:.2}", value)
+    } else {
+        text
+    };
+    format!("{:>8}", text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_data() -> Vec<HyetographEntry> {
+        [12.0, 60.0, 24.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &intensity)| HyetographEntry {
+                time_minutes: 10.0 * (i + 1) as f64,
+                intensity,
+                areal_reduction_factor: None,
+                loss: None,
+                effective: None,
+            })
+            .collect()
+    }
+
+    fn export(start: &str) -> HecExport {
+        let start = NaiveDateTime::parse_from_str(start, "%Y-%m-%dT%H:%M").unwrap();
+        HecExport::new(start, "design", 10.0).unwrap()
+    }
+
+    #[test]
+    fn interval_must_be_dss_regular() {
+        let start = export("2024-06-01T09:00").start;
+        assert_eq!(HecExport::new(start, "a", 60.0).unwrap().interval, "1HOUR");
+        let err = HecExport::new(start, "a", 7.0).unwrap_err().to_string();
+        assert!(
+            err.contains("not a HEC-DSS regular interval"),
+            "Error: {}",
+            err
+        );
+        assert!(HecExport::new(start, "a/b", 10.0).is_err());
+    }
+
+    #[test]
+    fn dss_text_uses_end_of_period_and_2400() {
+        let hec = export("2024-06-01T23:40");
+        assert_eq!(
+            hec.pathname(),
+            "//DESIGN/PRECIP-INC/01JUN2024/10MIN/HYETOGRAPH-CLI/"
+        );
+        let text = hec.dss_text(&sample_data(), 10.0);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[1], "B\tDESIGN");
+        assert_eq!(lines[7], "Type\tPER-CUM");
+        assert_eq!(lines[8], "01Jun2024 2350\t2.0000");
+        assert_eq!(lines[9], "01Jun2024 2400\t10.0000");
+        assert_eq!(lines[10], "02Jun2024 0010\t4.0000");
+    }
+
+    #[test]
+    fn hms_table_lists_incremental_depths() {
+        let text = export("2024-06-01T09:00").hms_table(&sample_data(), 10.0);
+        assert!(text.contains("Precipitation Units: Incremental Millimeters"));
+        assert!(text.contains("Start Time: 1 June 2024, 09:00"));
+        assert!(text.contains("End Time: 1 June 2024, 09:30"));
+        assert!(text.contains("01Jun2024\t09:20\t10.0000"));
+
+        let midnight = export("2024-06-02T00:00").hms_table(&sample_data(), 10.0);
+        assert!(midnight.contains("Start Time: 2 June 2024, 00:00"), "{}", midnight);
+    }
+
+    #[test]
+    fn ras_block_wraps_ten_values_per_line() {
+        let data: Vec<HyetographEntry> = (1..=12)
+            .map(|i| HyetographEntry {
+                time_minutes: 10.0 * i as f64,
+                intensity: 6.0,
+                areal_reduction_factor: None,
+                loss: None,
+                effective: None,
+            })
+            .collect();
+        let text = export("2024-06-01T09:00").ras_block(&data, 10.0);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Interval=10MIN");
+        assert_eq!(lines[2], "Fixed Start Date/Time=01JUN2024,0900");
+        assert_eq!(lines[3], "Precipitation Hydrograph= 13 ");
+        assert_eq!(lines[4].len(), 80);
+        assert_eq!(&lines[4][..16], "  0.0000  1.0000");
+        assert_eq!(lines[5].len(), 24);
+    }
+
+    #[test]
+    fn timestamped_path_includes_start() {
+        let path = export("2024-06-01T09:00").timestamped_path(Path::new("out/storm.png"), "dss");
+        assert_eq!(path, PathBuf::from("out/storm_202406010900_dss.txt"));
+    }
+}
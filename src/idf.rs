@@ -0,0 +1,254 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::formula::IntensityFormula;
+use crate::types::Interpolation;
+
+/// IDF表（継続時間-降雨強度表）の1行
+#[derive(Debug, Deserialize, Serialize)]
+struct IdfRow {
+    /// 継続時間[分]
+    duration_minutes: f64,
+    /// 降雨強度[mm/h]
+    intensity_mm_per_h: f64,
+}
+
+/// IDF表による降雨強度
+/// 表の継続時間の間を補間して K(t) を求める。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IdfTable {
+    #[serde(rename = "durations_minutes")]
+    durations: Vec<f64>,
+    #[serde(rename = "intensities_mm_per_h")]
+    intensities: Vec<f64>,
+    interpolation: Interpolation,
+}
+
+impl IdfTable {
+    /// 継続時間[分]と降雨強度[mm/h]の組からIDF表を構築する
+    /// 継続時間は狭義単調増加、降雨強度は正でなければならない。
+    pub fn new(
+        durations: Vec<f64>,
+        intensities: Vec<f64>,
+        interpolation: Interpolation,
+    ) -> Result<Self> {
+        if durations.len() != intensities.len() {
+            bail!(
+                "IDF table has {} durations but {} intensities",
+                durations.len(),
+                intensities.len()
+            );
+        }
+        if durations.len() < 2 {
+            bail!(
+                "IDF table must have at least 2 rows, got {}",
+                durations.len()
+            );
+        }
+        for (i, (&d, &k)) in durations.iter().zip(intensities.iter()).enumerate() {
+            if d <= 0.0 {
+                bail!("IDF table row {}: duration must be positive, got {}", i + 1, d);
+            }
+            if k <= 0.0 {
+                bail!("IDF table row {}: intensity must be positive, got {}", i + 1, k);
+            }
+        }
+        for i in 1..durations.len() {
+            if durations[i] <= durations[i - 1] {
+                bail!(
+                    "IDF table durations must be strictly increasing: row {} ({}) <= row {} ({})",
+                    i + 1,
+                    durations[i],
+                    i,
+                    durations[i - 1]
+                );
+            }
+        }
+
+        Ok(Self {
+            durations,
+            intensities,
+            interpolation,
+        })
+    }
+
+    /// 表の最小継続時間[分]
+    pub fn min_duration(&self) -> f64 {
+        self.durations[0]
+    }
+
+    /// 表の最大継続時間[分]
+    pub fn max_duration(&self) -> f64 {
+        self.durations[self.durations.len() - 1]
+    }
+}
+
+impl IntensityFormula for IdfTable {
+    /// 表の範囲外では端の区間を延長して評価する。範囲の検査は呼び出し側で行う。
+    fn intensity(&self, t: f64) -> f64 {
+        let last = self.durations.len() - 1;
+        let seg = self.durations[1..last]
+            .iter()
+            .take_while(|&&d| d < t)
+            .count();
+        let (d0, d1) = (self.durations[seg], self.durations[seg + 1]);
+        let (k0, k1) = (self.intensities[seg], self.intensities[seg + 1]);
+
+        match self.interpolation {
+            Interpolation::LogLog => {
+                let w = (t.ln() - d0.ln()) / (d1.ln() - d0.ln());
+                (k0.ln() + w * (k1.ln() - k0.ln())).exp()
+            }
+            Interpolation::Linear => {
+                let w = (t - d0) / (d1 - d0);
+                k0 + w * (k1 - k0)
+            }
+        }
+    }
+}
+
+/// 継続時間と降雨強度の組をCSVファイルから読み込む
+/// 列: duration_minutes, intensity_mm_per_h
+/// 返却: (継続時間[分], 降雨強度[mm/h])
+pub fn read_points(path: &Path) -> Result<(Vec<f64>, Vec<f64>)> {
+    let mut rdr = csv::Reader::from_path(path)
+        .with_context(|| format!("Failed to open IDF table: {}", path.display()))?;
+
+    let mut durations = Vec::new();
+    let mut intensities = Vec::new();
+    for record in rdr.deserialize() {
+        let row: IdfRow = record
+            .with_context(|| format!("Failed to read IDF table row in {}", path.display()))?;
+        durations.push(row.duration_minutes);
+        intensities.push(row.intensity_mm_per_h);
+    }
+
+    Ok((durations, intensities))
+}
+
+/// 継続時間と降雨強度の組をIDF表CSVとして書き出す
+pub fn write_points(path: &Path, durations: &[f64], intensities: &[f64]) -> Result<()> {
+    let mut wtr = csv::Writer::from_path(path)
+        .with_context(|| format!("Failed to create CSV file: {}", path.display()))?;
+
+    for (&duration_minutes, &intensity_mm_per_h) in durations.iter().zip(intensities) {
+        wtr.serialize(IdfRow {
+            duration_minutes,
+            intensity_mm_per_h,
+        })
+        .with_context(|| format!("Failed to write CSV record to {}", path.display()))?;
+    }
+
+    wtr.flush()
+        .with_context(|| format!("Failed to flush CSV file: {}", path.display()))?;
+
+    Ok(())
+}
+
+/// IDF表をCSVファイルから読み込む
+/// 列: duration_minutes, intensity_mm_per_h
+pub fn load(path: &Path, interpolation: Interpolation) -> Result<IdfTable> {
+    let (durations, intensities) = read_points(path)?;
+
+    IdfTable::new(durations, intensities, interpolation)
+        .with_context(|| format!("Invalid IDF table: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn sample_table(interpolation: Interpolation) -> IdfTable {
+        IdfTable::new(
+            vec![10.0, 20.0, 30.0, 60.0, 120.0],
+            vec![141.179, 104.774, 85.456, 59.124, 38.103],
+            interpolation,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn exact_durations_return_table_values() {
+        for interpolation in [Interpolation::LogLog, Interpolation::Linear] {
+            let table = sample_table(interpolation);
+            assert!((table.intensity(10.0) - 141.179).abs() < 1e-9);
+            assert!((table.intensity(60.0) - 59.124).abs() < 1e-9);
+            assert!((table.intensity(120.0) - 38.103).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn log_log_interpolation_is_linear_in_log_space() {
+        let table = sample_table(Interpolation::LogLog);
+        // 30分と60分の対数中点
+        let t = (30.0_f64 * 60.0).sqrt();
+        let expected = (85.456_f64 * 59.124).sqrt();
+        assert!((table.intensity(t) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn linear_interpolation_is_linear() {
+        let table = sample_table(Interpolation::Linear);
+        let expected = (85.456 + 59.124) / 2.0;
+        assert!((table.intensity(45.0) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn new_rejects_non_increasing_durations() {
+        let err = IdfTable::new(
+            vec![10.0, 30.0, 20.0],
+            vec![100.0, 80.0, 90.0],
+            Interpolation::LogLog,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("strictly increasing"), "Error: {}", err);
+    }
+
+    #[test]
+    fn new_rejects_single_row() {
+        let err = IdfTable::new(vec![10.0], vec![100.0], Interpolation::LogLog)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("at least 2 rows"), "Error: {}", err);
+    }
+
+    #[test]
+    fn load_reads_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("idf.csv");
+        fs::write(
+            &path,
+            "duration_minutes,intensity_mm_per_h\n10,141.179\n20,104.774\n60,59.124\n",
+        )
+        .unwrap();
+
+        let table = load(&path, Interpolation::LogLog).unwrap();
+        assert_eq!(table.min_duration(), 10.0);
+        assert_eq!(table.max_duration(), 60.0);
+        assert!((table.intensity(20.0) - 104.774).abs() < 1e-9);
+    }
+
+    #[test]
+    fn write_points_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("idf.csv");
+
+        write_points(&path, &[10.0, 60.0], &[141.179, 59.124]).unwrap();
+
+        let (durations, intensities) = read_points(&path).unwrap();
+        assert_eq!(durations, vec![10.0, 60.0]);
+        assert_eq!(intensities, vec![141.179, 59.124]);
+    }
+
+    #[test]
+    fn load_missing_file_fails() {
+        let err = load(Path::new("/nonexistent/idf.csv"), Interpolation::LogLog)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Failed to open IDF table"), "Error: {}", err);
+    }
+}
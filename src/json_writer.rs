@@ -0,0 +1,171 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::presets::Preset;
+use crate::types::{HyetographEntry, RainfallParams};
+
+/// ツール名
+const TOOL_NAME: &str = "hyetograph-cli";
+
+/// 1回の実行結果とメタデータ（JSON・NDJSON出力）
+#[derive(Debug, Serialize)]
+pub struct RunDocument<'a> {
+    /// ツール名
+    pub tool: &'static str,
+    /// ツールのバージョン
+    pub version: &'static str,
+    /// 降雨強度式・計算時間刻み・降雨継続時間
+    #[serde(flatten)]
+    pub rainfall_params: &'a RainfallParams,
+    /// 係数の出典となったプリセット
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preset: Option<&'a Preset>,
+    /// 雨量分布パターン
+    pub pattern: String,
+    /// シカゴ型降雨のピーク位置係数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_ratio: Option<f64>,
+    /// 計算ステップ数 NT = TT × 60 / T
+    pub nt: usize,
+    /// 総雨量[mm]
+    pub total_depth_mm: f64,
+    /// ピーク降雨強度[mm/h]
+    pub peak_intensity_mm_per_h: f64,
+    /// 有効雨量[mm]（損失モデルの指定時のみ）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_depth_mm: Option<f64>,
+    /// ハイエトグラフ
+    pub series: &'a [HyetographEntry],
+}
+
+impl<'a> RunDocument<'a> {
+    /// ハイエトグラフから総雨量・ピーク降雨強度などの集計値を求めて構築する
+    pub fn new(
+        rainfall_params: &'a RainfallParams,
+        preset: Option<&'a Preset>,
+        pattern: String,
+        peak_ratio: Option<f64>,
+        series: &'a [HyetographEntry],
+    ) -> Self {
+        let depth = |intensity: f64| intensity * rainfall_params.t / 60.0;
+        let has_loss = series.iter().any(|e| e.effective.is_some());
+        Self {
+            tool: TOOL_NAME,
+            version: env!("CARGO_PKG_VERSION"),
+            rainfall_params,
+            preset,
+            pattern,
+            peak_ratio,
+            nt: series.len(),
+            total_depth_mm: series.iter().map(|e| depth(e.intensity)).sum(),
+            peak_intensity_mm_per_h: series.iter().map(|e| e.intensity).fold(0.0, f64::max),
+            effective_depth_mm: has_loss.then(|| {
+                series
+                    .iter()
+                    .map(|e| depth(e.effective.unwrap_or(0.0)))
+                    .sum()
+            }),
+            series,
+        }
+    }
+}
+
+/// 実行結果を整形したJSONファイルとして出力する
+pub fn write(document: &RunDocument, output_path: &Path) -> Result<()> {
+    let json = serde_json::to_string_pretty(document)
+        .with_context(|| "Failed to serialize run document")?;
+    fs::write(output_path, json + "\n")
+        .with_context(|| format!("Failed to create JSON file: {}", output_path.display()))?;
+    Ok(())
+}
+
+/// 実行結果を1行のJSONとしてNDJSONファイルに追記する
+/// ファイルがなければ作成する。バッチ実行の結果を1つのファイルにまとめられる。
+pub fn append_ndjson(document: &RunDocument, output_path: &Path) -> Result<()> {
+    let json =
+        serde_json::to_string(document).with_context(|| "Failed to serialize run document")?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(output_path)
+        .with_context(|| format!("Failed to open NDJSON file: {}", output_path.display()))?;
+    writeln!(file, "{}", json)
+        .with_context(|| format!("Failed to write NDJSON record to {}", output_path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formula::{Formula, Talbot};
+
+    fn params() -> RainfallParams {
+        RainfallParams {
+            formula: Formula::Talbot(Talbot { a: 5000.0, b: 40.0 }),
+            t: 10.0,
+            tt: 0.5,
+        }
+    }
+
+    fn series() -> Vec<HyetographEntry> {
+        [30.0, 90.0, 60.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &intensity)| HyetographEntry {
+                time_minutes: 10.0 * (i + 1) as f64,
+                intensity,
+                areal_reduction_factor: None,
+                loss: None,
+                effective: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn document_contains_metadata_and_series() {
+        let params = params();
+        let series = series();
+        let document = RunDocument::new(&params, None, "center".to_string(), None, &series);
+        let json = serde_json::to_value(&document).unwrap();
+
+        assert_eq!(json["tool"], "hyetograph-cli");
+        assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(json["formula"]["type"], "talbot");
+        assert_eq!(json["formula"]["B"], 40.0);
+        assert_eq!(json["formula"]["C"], 5000.0);
+        assert_eq!(json["t_minutes"], 10.0);
+        assert_eq!(json["tt_hours"], 0.5);
+        assert_eq!(json["pattern"], "center");
+        assert_eq!(json["nt"], 3);
+        assert_eq!(json["total_depth_mm"], 30.0);
+        assert_eq!(json["peak_intensity_mm_per_h"], 90.0);
+        assert_eq!(json["series"][1]["intensity_mm_per_h"], 90.0);
+        assert!(json.get("preset").is_none());
+        assert!(json.get("peak_ratio").is_none());
+        assert!(json.get("effective_depth_mm").is_none());
+    }
+
+    #[test]
+    fn append_ndjson_adds_one_line_per_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("runs.ndjson");
+        let params = params();
+        let series = series();
+        let document = RunDocument::new(&params, None, "front".to_string(), None, &series);
+
+        append_ndjson(&document, &path).unwrap();
+        append_ndjson(&document, &path).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        for line in lines {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(value["pattern"], "front");
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use anyhow::{bail, Result};
+
+use crate::formula::IntensityFormula;
+use crate::runoff::RunoffModel;
+
+/// 斜面の流下方向の分割数
+const CELLS: usize = 50;
+
+/// 時間積分のクーラン数
+const COURANT: f64 = 0.5;
+
+/// 1計算時間刻みあたりの最小分割数
+const MIN_SUBSTEPS: usize = 20;
+
+/// マニング則の水深指数 m（q = α h^m）
+const DEPTH_EXPONENT: f64 = 5.0 / 3.0;
+
+/// 到達時間の反復計算の最大回数と収束判定[分]
+const TC_ITERATIONS: usize = 100;
+const TC_TOLERANCE: f64 = 1e-6;
+
+/// 斜面長方向1次元の表面流（キネマティックウェーブ法）
+/// 連続式 ∂h/∂t + ∂q/∂x = r とマニング則 q = (√S / n) h^(5/3) を、
+/// 上流端 q = 0 の風上差分による陽解法でクーラン条件を満たす時間刻みに分割して解く。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OverlandPlane {
+    /// 斜面勾配 S [m/m]
+    pub slope: f64,
+    /// 斜面長 L [m]
+    pub length: f64,
+    /// マニングの粗度係数 n [s/m^(1/3)]
+    pub roughness: f64,
+    /// 斜面幅 W [m]
+    pub width: f64,
+}
+
+impl OverlandPlane {
+    pub fn new(slope: f64, length: f64, roughness: f64, width: f64) -> Result<Self> {
+        if slope <= 0.0 {
+            bail!("Plane slope must be positive (> 0), got {}", slope);
+        }
+        if length <= 0.0 || width <= 0.0 {
+            bail!(
+                "Plane length and width must be positive (> 0), got L={}, W={}",
+                length,
+                width
+            );
+        }
+        if roughness <= 0.0 {
+            bail!("Manning's n must be positive (> 0), got {}", roughness);
+        }
+        Ok(Self {
+            slope,
+            length,
+            roughness,
+            width,
+        })
+    }
+
+    /// 斜面の面積[km²]
+    pub fn area(&self) -> f64 {
+        self.length * self.width / 1e6
+    }
+
+    /// マニング則の係数 α = √S / n
+    fn alpha(&self) -> f64 {
+        self.slope.sqrt() / self.roughness
+    }
+
+    /// 一定の降雨強度 intensity[mm/h] で平衡流出に達するまでの到達時間[分]
+    /// tc = (L / (α i^(m-1)))^(1/m)。SI単位で tc ≈ 6.99 (nL)^0.6 / (i^0.4 S^0.3) [分] となる。
+    pub fn time_of_concentration(&self, intensity: f64) -> f64 {
+        let rate = intensity / 3.6e6;
+        (self.length / (self.alpha() * rate.powf(DEPTH_EXPONENT - 1.0))).powf(1.0 / DEPTH_EXPONENT)
+            / 60.0
+    }
+
+    /// 降雨強度式と到達時間式を連立して tc = tc(K(tc)) を反復法で解く
+    /// 返却値は (到達時間[分], 降雨強度 K(tc)[mm/h])。収束しない場合は None。
+    pub fn rational_tc<F: IntensityFormula + ?Sized>(&self, formula: &F) -> Option<(f64, f64)> {
+        let mut tc = 10.0;
+        for _ in 0..TC_ITERATIONS {
+            let intensity = formula.intensity(tc);
+            if !intensity.is_finite() || intensity <= 0.0 {
+                return None;
+            }
+            let next = self.time_of_concentration(intensity);
+            if (next - tc).abs() < TC_TOLERANCE {
+                return Some((next, formula.intensity(next)));
+            }
+            tc = next;
+        }
+        None
+    }
+}
+
+impl RunoffModel for OverlandPlane {
+    fn discharge(&self, rainfall: &[f64], t: f64, steps: usize) -> Vec<f64> {
+        let alpha = self.alpha();
+        let dx = self.length / CELLS as f64;
+        let step_seconds = t * 60.0;
+        let max_dt = step_seconds / MIN_SUBSTEPS as f64;
+        // 各セルの水深[m]
+        let mut depth = vec![0.0_f64; CELLS];
+
+        (0..steps)
+            .map(|i| {
+                let rate = rainfall.get(i).copied().unwrap_or(0.0) / 3.6e6;
+                let mut elapsed = 0.0;
+                while elapsed < step_seconds {
+                    // 伝播速度 c = m α h^(m-1) に対するクーラン条件
+                    let max_depth = depth.iter().copied().fold(0.0_f64, f64::max) + rate * max_dt;
+                    let celerity = DEPTH_EXPONENT * alpha * max_depth.powf(DEPTH_EXPONENT - 1.0);
+                    let dt = if celerity > 0.0 {
+                        (COURANT * dx / celerity).min(max_dt)
+                    } else {
+                        max_dt
+                    }
+                    .min(step_seconds - elapsed);
+
+                    let mut upstream = 0.0;
+                    for h in depth.iter_mut() {
+                        let q = alpha * h.powf(DEPTH_EXPONENT);
+                        *h = (*h + dt * (rate - (q - upstream) / dx)).max(0.0);
+                        upstream = q;
+                    }
+                    elapsed += dt;
+                }
+                alpha * depth[CELLS - 1].powf(DEPTH_EXPONENT) * self.width
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formula::Talbot;
+
+    fn plane() -> OverlandPlane {
+        OverlandPlane::new(0.01, 100.0, 0.015, 50.0).unwrap()
+    }
+
+    #[test]
+    fn constant_rain_reaches_equilibrium_at_tc() {
+        let plane = plane();
+        let intensity = 60.0;
+        let tc = plane.time_of_concentration(intensity);
+        // 6.99 (nL)^0.6 / (i^0.4 S^0.3)
+        let textbook =
+            6.99 * (0.015_f64 * 100.0).powf(0.6) / (60.0_f64.powf(0.4) * 0.01_f64.powf(0.3));
+        assert!(
+            (tc - textbook).abs() / textbook < 0.01,
+            "tc {} vs {}",
+            tc,
+            textbook
+        );
+
+        let t = 1.0;
+        let steps = 60;
+        let q = plane.discharge(&vec![intensity; steps], t, steps);
+        let equilibrium = intensity * plane.area() / 3.6;
+        let before = q[(tc * 0.5 / t) as usize];
+        assert!(before < 0.6 * equilibrium, "Q(tc/2) = {}", before);
+        let after = q[(tc * 1.5 / t).ceil() as usize];
+        assert!(
+            (after - equilibrium).abs() / equilibrium < 0.01,
+            "Q(1.5 tc) = {}",
+            after
+        );
+    }
+
+    #[test]
+    fn outflow_conserves_volume() {
+        let plane = plane();
+        // 5分ごとに 30, 90, 20 mm/h の降雨を1分刻みで与える
+        let rainfall: Vec<f64> = [30.0, 90.0, 20.0]
+            .iter()
+            .flat_map(|&r| [r; 5])
+            .collect();
+        let t = 1.0;
+        let q = plane.discharge(&rainfall, t, 120);
+        let volume_out: f64 = q.iter().sum::<f64>() * t * 60.0;
+        let volume_in = rainfall.iter().sum::<f64>() * t / 60.0 / 1000.0 * 100.0 * 50.0;
+        assert!(
+            (volume_out - volume_in).abs() / volume_in < 0.01,
+            "{} vs {}",
+            volume_out,
+            volume_in
+        );
+        assert!(q.iter().all(|v| v.is_finite() && *v >= 0.0));
+    }
+
+    #[test]
+    fn rational_tc_is_consistent_with_formula() {
+        let plane = plane();
+        let formula = Talbot { a: 5000.0, b: 40.0 };
+        let (tc, intensity) = plane.rational_tc(&formula).unwrap();
+        assert!((intensity - formula.intensity(tc)).abs() < 1e-9);
+        assert!((plane.time_of_concentration(intensity) - tc).abs() < 1e-5);
+    }
+
+    #[test]
+    fn invalid_parameters_fail() {
+        assert!(OverlandPlane::new(0.0, 100.0, 0.015, 50.0).is_err());
+        assert!(OverlandPlane::new(0.01, -1.0, 0.015, 50.0).is_err());
+        assert!(OverlandPlane::new(0.01, 100.0, 0.0, 50.0).is_err());
+    }
+}
@@ -0,0 +1,475 @@
+use std::fmt;
+
+use anyhow::{bail, Result};
+
+use crate::types::{AntecedentMoisture, HyetographEntry};
+
+/// Green-Ampt 式の反復計算の上限と収束判定
+const GREEN_AMPT_MAX_ITERATIONS: usize = 100;
+const GREEN_AMPT_TOLERANCE: f64 = 1e-9;
+
+/// 損失モデルの共通インターフェース
+pub trait LossModel {
+    /// 時系列順の降雨強度 rainfall[mm/h] に対する各ステップの損失[mm/h]
+    /// t: 計算時間刻み[分]
+    fn losses(&self, rainfall: &[f64], t: f64) -> Vec<f64>;
+}
+
+/// 時系列順のハイエトグラフに対する各ステップの損失と有効降雨
+/// 損失は各ステップの降雨強度を超えないよう 0〜降雨強度 に制限する。
+pub fn compute<M: LossModel + ?Sized>(
+    model: &M,
+    entries: &[HyetographEntry],
+    t: f64,
+) -> Vec<LossStep> {
+    let rainfall: Vec<f64> = entries.iter().map(|e| e.intensity).collect();
+    model
+        .losses(&rainfall, t)
+        .into_iter()
+        .zip(&rainfall)
+        .map(|(loss, &rain)| {
+            let loss = loss.clamp(0.0, rain.max(0.0));
+            LossStep {
+                loss,
+                effective: rain - loss,
+            }
+        })
+        .collect()
+}
+
+/// 1ステップの損失と有効降雨
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LossStep {
+    /// 損失[mm/h]
+    pub loss: f64,
+    /// 有効降雨強度[mm/h]
+    pub effective: f64,
+}
+
+/// SCS（NRCS）カーブナンバー法
+/// 累加雨量 P に対する累加有効雨量 Pe = (P - Ia)^2 / (P - Ia + S)（P > Ia）、
+/// S = 25400 / CN - 254 [mm]、Ia = λS。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScsCurveNumber {
+    /// AMC 補正後のカーブナンバー
+    pub cn: f64,
+    /// 初期損失比 λ = Ia / S
+    pub ia_ratio: f64,
+}
+
+impl ScsCurveNumber {
+    /// AMC II のカーブナンバーを先行降雨条件で補正して構築する
+    /// AMC I: 4.2 CN / (10 - 0.058 CN)、AMC III: 23 CN / (10 + 0.13 CN)（Chow et al., 1988）
+    pub fn new(cn: f64, ia_ratio: f64, amc: AntecedentMoisture) -> Result<Self> {
+        if !(cn > 0.0 && cn <= 100.0) {
+            bail!(
+                "Curve number must be in (0, 100], got {}. Valid range: 0 < --cn <= 100",
+                cn
+            );
+        }
+        if ia_ratio < 0.0 {
+            bail!(
+                "Initial abstraction ratio must be non-negative, got {}. Valid range: --ia-ratio >= 0",
+                ia_ratio
+            );
+        }
+        let cn = match amc {
+            AntecedentMoisture::Dry => 4.2 * cn / (10.0 - 0.058 * cn),
+            AntecedentMoisture::Normal => cn,
+            AntecedentMoisture::Wet => 23.0 * cn / (10.0 + 0.13 * cn),
+        };
+        Ok(Self { cn, ia_ratio })
+    }
+
+    /// 最大保留量 S[mm]
+    pub fn retention(&self) -> f64 {
+        25400.0 / self.cn - 254.0
+    }
+
+    /// 累加雨量 p[mm] に対する累加有効雨量[mm]
+    pub fn cumulative_excess(&self, p: f64) -> f64 {
+        let s = self.retention();
+        let ia = self.ia_ratio * s;
+        if p <= ia {
+            0.0
+        } else {
+            (p - ia).powi(2) / (p - ia + s)
+        }
+    }
+}
+
+impl LossModel for ScsCurveNumber {
+    fn losses(&self, rainfall: &[f64], t: f64) -> Vec<f64> {
+        let hours = t / 60.0;
+        let mut cumulative = 0.0;
+        let mut excess = 0.0;
+        rainfall
+            .iter()
+            .map(|&rain| {
+                cumulative += rain * hours;
+                let next = self.cumulative_excess(cumulative);
+                let effective = (next - excess) / hours;
+                excess = next;
+                rain - effective
+            })
+            .collect()
+    }
+}
+
+/// Horton 式 f(t) = fc + (f0 - fc) exp(-k t)
+/// 各ステップの損失は浸透能のステップ平均と降雨強度の小さい方とする。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Horton {
+    /// 初期浸透能[mm/h]
+    pub f0: f64,
+    /// 終期浸透能[mm/h]
+    pub fc: f64,
+    /// 減衰係数[1/h]
+    pub k: f64,
+}
+
+impl Horton {
+    pub fn new(f0: f64, fc: f64, k: f64) -> Result<Self> {
+        if !(fc >= 0.0 && f0 >= fc) {
+            bail!(
+                "Horton rates must satisfy f0 >= fc >= 0, got f0={}, fc={}",
+                f0,
+                fc
+            );
+        }
+        if k <= 0.0 {
+            bail!("Horton decay constant k must be positive (> 0), got {}", k);
+        }
+        Ok(Self { f0, fc, k })
+    }
+
+    /// 時刻 0〜hours[h] の累加浸透能[mm]
+    fn cumulative_capacity(&self, hours: f64) -> f64 {
+        self.fc * hours + (self.f0 - self.fc) / self.k * (1.0 - (-self.k * hours).exp())
+    }
+}
+
+impl LossModel for Horton {
+    fn losses(&self, rainfall: &[f64], t: f64) -> Vec<f64> {
+        let hours = t / 60.0;
+        rainfall
+            .iter()
+            .enumerate()
+            .map(|(i, &rain)| {
+                let start = hours * i as f64;
+                let capacity = (self.cumulative_capacity(start + hours)
+                    - self.cumulative_capacity(start))
+                    / hours;
+                rain.min(capacity)
+            })
+            .collect()
+    }
+}
+
+/// Green-Ampt 式 f = Ks (1 + ψΔθ / F)
+/// 各ステップで湛水状態を仮定した累加浸透量の増分を陰的に解き、降雨量を上限とする。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GreenAmpt {
+    /// 飽和透水係数[mm/h]
+    pub ksat: f64,
+    /// 湿潤前線の毛管水頭[mm]
+    pub suction: f64,
+    /// 含水率の不足分 Δθ（有効間隙率 - 初期含水率）
+    pub moisture_deficit: f64,
+}
+
+impl GreenAmpt {
+    pub fn new(ksat: f64, suction: f64, moisture_deficit: f64) -> Result<Self> {
+        if ksat <= 0.0 {
+            bail!("Saturated conductivity must be positive (> 0), got {}", ksat);
+        }
+        if suction < 0.0 {
+            bail!("Wetting front suction must be non-negative, got {}", suction);
+        }
+        if !(0.0..=1.0).contains(&moisture_deficit) {
+            bail!("Moisture deficit must be between 0 and 1, got {}", moisture_deficit);
+        }
+        Ok(Self {
+            ksat,
+            suction,
+            moisture_deficit,
+        })
+    }
+
+    /// 累加浸透量 f0[mm] から湛水状態で hours[h] 経過後の累加浸透量[mm]
+    /// F1 - F0 - ψΔθ ln((F1 + ψΔθ) / (F0 + ψΔθ)) = Ks Δt をニュートン法で解く。
+    fn ponded_cumulative(&self, f0: f64, hours: f64) -> f64 {
+        let sd = self.suction * self.moisture_deficit;
+        let kt = self.ksat * hours;
+        if sd <= 0.0 {
+            return f0 + kt;
+        }
+        let mut f1 = f0 + kt.max((2.0 * sd * kt).sqrt());
+        for _ in 0..GREEN_AMPT_MAX_ITERATIONS {
+            let g = f1 - f0 - sd * ((f1 + sd) / (f0 + sd)).ln() - kt;
+            let dg = 1.0 - sd / (f1 + sd);
+            let next = (f1 - g / dg).max(f0);
+            if (next - f1).abs() < GREEN_AMPT_TOLERANCE {
+                return next;
+            }
+            f1 = next;
+        }
+        f1
+    }
+}
+
+impl LossModel for GreenAmpt {
+    fn losses(&self, rainfall: &[f64], t: f64) -> Vec<f64> {
+        let hours = t / 60.0;
+        let mut infiltrated = 0.0;
+        rainfall
+            .iter()
+            .map(|&rain| {
+                let capacity = self.ponded_cumulative(infiltrated, hours) - infiltrated;
+                let depth = (rain * hours).min(capacity);
+                infiltrated += depth;
+                depth / hours
+            })
+            .collect()
+    }
+}
+
+/// φインデックス法（一定の損失強度）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhiIndex {
+    /// 損失強度[mm/h]
+    pub phi: f64,
+}
+
+impl LossModel for PhiIndex {
+    fn losses(&self, rainfall: &[f64], _t: f64) -> Vec<f64> {
+        rainfall.iter().map(|&rain| rain.min(self.phi)).collect()
+    }
+}
+
+/// 初期損失・継続損失法
+/// 降雨の初めに初期損失を満たし、以降は一定の継続損失強度で損失する。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InitialContinuing {
+    /// 初期損失[mm]
+    pub initial: f64,
+    /// 継続損失強度[mm/h]
+    pub continuing: f64,
+}
+
+impl LossModel for InitialContinuing {
+    fn losses(&self, rainfall: &[f64], t: f64) -> Vec<f64> {
+        let hours = t / 60.0;
+        let mut remaining = self.initial;
+        rainfall
+            .iter()
+            .map(|&rain| {
+                let depth = rain * hours;
+                let initial = depth.min(remaining);
+                remaining -= initial;
+                let continuing = (depth - initial).min(self.continuing * hours);
+                (initial + continuing) / hours
+            })
+            .collect()
+    }
+}
+
+/// 選択された損失モデル
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Loss {
+    ScsCurveNumber(ScsCurveNumber),
+    Horton(Horton),
+    GreenAmpt(GreenAmpt),
+    PhiIndex(PhiIndex),
+    InitialContinuing(InitialContinuing),
+}
+
+impl LossModel for Loss {
+    fn losses(&self, rainfall: &[f64], t: f64) -> Vec<f64> {
+        match self {
+            Loss::ScsCurveNumber(m) => m.losses(rainfall, t),
+            Loss::Horton(m) => m.losses(rainfall, t),
+            Loss::GreenAmpt(m) => m.losses(rainfall, t),
+            Loss::PhiIndex(m) => m.losses(rainfall, t),
+            Loss::InitialContinuing(m) => m.losses(rainfall, t),
+        }
+    }
+}
+
+impl fmt::Display for Loss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Loss::ScsCurveNumber(m) => {
+                write!(f, "SCS-CN: CN = {:.1}, S = {:.1} mm", m.cn, m.retention())
+            }
+            Loss::Horton(m) => write!(
+                f,
+                "Horton: f0 = {} mm/h, fc = {} mm/h, k = {} 1/h",
+                m.f0, m.fc, m.k
+            ),
+            Loss::GreenAmpt(m) => write!(
+                f,
+                "Green-Ampt: Ks = {} mm/h, psi = {} mm, dtheta = {}",
+                m.ksat, m.suction, m.moisture_deficit
+            ),
+            Loss::PhiIndex(m) => write!(f, "phi-index: {} mm/h", m.phi),
+            Loss::InitialContinuing(m) => write!(
+                f,
+                "initial/continuing: IL = {} mm, CL = {} mm/h",
+                m.initial, m.continuing
+            ),
+        }
+    }
+}
+
+/// 損失と有効降雨をハイエトグラフに書き込む
+pub fn apply(entries: &mut [HyetographEntry], steps: &[LossStep]) {
+    for (entry, step) in entries.iter_mut().zip(steps) {
+        entry.loss = Some(step.loss);
+        entry.effective = Some(step.effective);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(intensities: &[f64]) -> Vec<HyetographEntry> {
+        intensities
+            .iter()
+            .enumerate()
+            .map(|(i, &intensity)| HyetographEntry {
+                time_minutes: 60.0 * (i + 1) as f64,
+                intensity,
+                areal_reduction_factor: None,
+                loss: None,
+                effective: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cumulative_excess_matches_textbook_example() {
+        // CN = 80: S = 63.5 mm, Ia = 12.7 mm。P = 100 mm で Pe = 87.3^2 / 150.8 = 50.54 mm
+        let scs = ScsCurveNumber::new(80.0, 0.2, AntecedentMoisture::Normal).unwrap();
+        assert!((scs.retention() - 63.5).abs() < 1e-9);
+        assert!((scs.cumulative_excess(100.0) - 87.3 * 87.3 / 150.8).abs() < 1e-9);
+        assert_eq!(scs.cumulative_excess(10.0), 0.0);
+    }
+
+    #[test]
+    fn compute_splits_rainfall_into_loss_and_excess() {
+        let scs = ScsCurveNumber::new(80.0, 0.2, AntecedentMoisture::Normal).unwrap();
+        let data = entries(&[10.0, 40.0, 30.0, 20.0]);
+        let steps = compute(&scs, &data, 60.0);
+
+        // 初期損失 12.7 mm に達するまで全量が損失
+        assert_eq!(steps[0].effective, 0.0);
+        assert_eq!(steps[0].loss, 10.0);
+        for (entry, step) in data.iter().zip(&steps) {
+            assert!((step.loss + step.effective - entry.intensity).abs() < 1e-9);
+            assert!(step.loss >= 0.0 && step.effective >= 0.0);
+        }
+        let total_effective: f64 = steps.iter().map(|s| s.effective).sum();
+        assert!((total_effective - 87.3 * 87.3 / 150.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn antecedent_moisture_adjusts_curve_number() {
+        let dry = ScsCurveNumber::new(80.0, 0.2, AntecedentMoisture::Dry).unwrap();
+        let wet = ScsCurveNumber::new(80.0, 0.2, AntecedentMoisture::Wet).unwrap();
+        assert!((dry.cn - 336.0 / 5.36).abs() < 1e-9);
+        assert!((wet.cn - 1840.0 / 20.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn invalid_curve_number_fails() {
+        let err = ScsCurveNumber::new(0.0, 0.2, AntecedentMoisture::Normal)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Curve number"), "Error: {}", err);
+    }
+
+    #[test]
+    fn apply_writes_columns() {
+        let mut data = entries(&[10.0]);
+        apply(
+            &mut data,
+            &[LossStep {
+                loss: 4.0,
+                effective: 6.0,
+            }],
+        );
+        assert_eq!(data[0].loss, Some(4.0));
+        assert_eq!(data[0].effective, Some(6.0));
+    }
+
+    #[test]
+    fn horton_loss_follows_decaying_capacity() {
+        let horton = Horton::new(60.0, 10.0, 2.0).unwrap();
+        let losses = horton.losses(&[100.0, 100.0, 5.0], 60.0);
+        // 1時間目の平均浸透能 10 + 50 / 2 * (1 - e^-2)
+        assert!((losses[0] - (10.0 + 25.0 * (1.0 - (-2.0_f64).exp()))).abs() < 1e-9);
+        assert!(losses[1] < losses[0]);
+        assert_eq!(losses[2], 5.0);
+    }
+
+    #[test]
+    fn green_ampt_infiltration_decreases_toward_ksat() {
+        let green_ampt = GreenAmpt::new(10.0, 110.0, 0.3).unwrap();
+        let losses = green_ampt.losses(&[200.0; 6], 60.0);
+        for pair in losses.windows(2) {
+            assert!(pair[1] < pair[0], "{:?}", losses);
+        }
+        assert!(losses[5] > 10.0);
+
+        // 累加浸透量は Green-Ampt の陰的方程式を満たす
+        let f: f64 = losses[0];
+        let sd = 110.0 * 0.3;
+        assert!((f - sd * (1.0 + f / sd).ln() - 10.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn green_ampt_is_limited_by_rainfall() {
+        let green_ampt = GreenAmpt::new(10.0, 110.0, 0.3).unwrap();
+        assert_eq!(green_ampt.losses(&[2.0, 3.0], 10.0), vec![2.0, 3.0]);
+    }
+
+    #[test]
+    fn phi_index_caps_loss() {
+        let phi = PhiIndex { phi: 15.0 };
+        assert_eq!(phi.losses(&[10.0, 40.0], 10.0), vec![10.0, 15.0]);
+    }
+
+    #[test]
+    fn initial_loss_is_satisfied_before_continuing_loss() {
+        let model = InitialContinuing {
+            initial: 15.0,
+            continuing: 2.0,
+        };
+        // 1時間ステップ: 10mm は全量初期損失、20mm は初期損失5mm + 継続損失2mm
+        let losses = model.losses(&[10.0, 20.0, 1.0], 60.0);
+        assert_eq!(losses, vec![10.0, 7.0, 1.0]);
+    }
+
+    #[test]
+    fn compute_never_exceeds_rainfall() {
+        let data = entries(&[5.0, 50.0, 0.0, 20.0]);
+        let models = [
+            Loss::ScsCurveNumber(ScsCurveNumber::new(60.0, 0.05, AntecedentMoisture::Wet).unwrap()),
+            Loss::Horton(Horton::new(80.0, 20.0, 1.0).unwrap()),
+            Loss::GreenAmpt(GreenAmpt::new(5.0, 200.0, 0.4).unwrap()),
+            Loss::PhiIndex(PhiIndex { phi: 30.0 }),
+            Loss::InitialContinuing(InitialContinuing {
+                initial: 10.0,
+                continuing: 5.0,
+            }),
+        ];
+        for model in models {
+            for (entry, step) in data.iter().zip(compute(&model, &data, 60.0)) {
+                assert!(step.loss >= 0.0 && step.loss <= entry.intensity, "{}: {:?}", model, step);
+                assert!((step.loss + step.effective - entry.intensity).abs() < 1e-9);
+            }
+        }
+    }
+}
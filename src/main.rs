@@ -1,14 +1,36 @@
+mod areal_reduction;
 mod chart;
 mod cli;
+mod coefficient_table;
 mod csv_writer;
 mod distribution;
+mod fit;
+mod formula;
+mod frequency;
+mod hec;
+mod idf;
+mod json_writer;
+mod kinematic_wave;
+mod loss;
+mod mass_curve;
+mod pond;
+mod presets;
 mod rainfall;
+mod rational;
+mod runoff;
+mod storage_function;
+mod swmm;
+mod tank_model;
 mod types;
+mod unit_hydrograph;
 mod validator;
 
 use std::process;
 
-use clap::Parser;
+use clap::ValueEnum;
+
+use formula::IntensityFormula;
+use runoff::RunoffModel;
 
 fn main() {
     if let Err(err) = run() {
@@ -18,31 +40,541 @@ fn main() {
 }
 
 fn run() -> anyhow::Result<()> {
-    let cli = cli::Cli::parse();
+    let cli = cli::Cli::try_parse_with_table_help(std::env::args_os()).unwrap_or_else(|e| e.exit());
+
+    if let Some(command) = &cli.command {
+        return match command {
+            cli::Command::Fit(args) => run_fit(args),
+            cli::Command::Frequency(args) => run_frequency(args),
+            cli::Command::Rational(args) => run_rational(args),
+            cli::Command::Pond(args) => run_pond(args),
+            cli::Command::Presets { action } => run_presets(action),
+        };
+    }
+
     let validated = validator::validate(&cli)?;
 
-    let increments = rainfall::calculate(&validated.rainfall_params);
-    let data = distribution::arrange(&increments, validated.pattern, validated.rainfall_params.t);
+    if let Some(preset) = &validated.preset {
+        println!("Preset: {} ({}; source: {})", preset.name, preset.region, preset.source);
+    }
+
+    let params = &validated.rainfall_params;
+    let mut increments = match &validated.areal_reduction {
+        Some((reduction, area)) => {
+            let reduced = areal_reduction::Reduced {
+                formula: &params.formula,
+                reduction,
+                area: *area,
+            };
+            rainfall::calculate(&reduced, params.t, params.tt)
+        }
+        None => rainfall::calculate(&params.formula, params.t, params.tt),
+    };
+    if let Some(depth) = validated.total_depth {
+        rainfall::scale_to_depth(&mut increments, depth, params.t);
+    }
+    let mut data = match validated.peak_ratio {
+        Some(r) => distribution::arrange_with_peak_ratio(&increments, r, params.t),
+        None => distribution::arrange(
+            &increments,
+            validated.pattern,
+            &validated.pattern_options,
+            params.t,
+        ),
+    };
+
+    let mut annotation = None;
+    if let Some((reduction, area)) = &validated.areal_reduction {
+        // 継続時間 T × (順位 + 1) の係数で低減した増分がそのステップに置かれている。
+        // 累加雨量曲線のパターンは低減後の総雨量を配分するため降雨継続時間の係数となる。
+        let ranks = match validated.peak_ratio {
+            Some(r) => Some(distribution::ranks_with_peak_ratio(increments.len(), r)),
+            None => distribution::ranks(&increments, validated.pattern, &validated.pattern_options),
+        };
+        let duration = params.tt * 60.0;
+        for (i, entry) in data.iter_mut().enumerate() {
+            let applied = match &ranks {
+                Some(ranks) => params.t * (ranks[i] + 1) as f64,
+                None => duration,
+            };
+            entry.areal_reduction_factor = Some(reduction.factor(*area, applied));
+        }
+        let label = format!(
+            "ARF (A = {} km², {} min) = {:.3}",
+            area,
+            duration,
+            reduction.factor(*area, duration)
+        );
+        println!("{}", label);
+        annotation = Some(label);
+    }
+
+    if let Some(model) = &validated.loss {
+        let steps = loss::compute(model, &data, params.t);
+        loss::apply(&mut data, &steps);
+        let effective: f64 = steps.iter().map(|s| s.effective).sum::<f64>() * params.t / 60.0;
+        println!("Effective rainfall: {:.2} mm ({})", effective, model);
+    }
+
+    let runoff_rainfall: Vec<f64> = data
+        .iter()
+        .map(|e| e.effective.unwrap_or(e.intensity))
+        .collect();
+    let hydrograph = validated.runoff.as_ref().map(|(model, steps)| {
+        let hydrograph = runoff::simulate(model, &runoff_rainfall, params.t, *steps);
+        println!("Runoff model: {}", model);
+        if let Some(peak) = runoff::peak(&hydrograph) {
+            println!(
+                "Peak discharge: {:.3} m³/s at {} min",
+                peak.discharge, peak.time_minutes
+            );
+        }
+        println!(
+            "Runoff volume: {:.0} m³",
+            runoff::volume(&hydrograph, params.t)
+        );
+        hydrograph
+    });
+    if let Some((runoff::Runoff::KinematicWave(plane), _)) = &validated.runoff {
+        // 同じ降雨強度式による合理式のピーク流量と並べて表示する。
+        // 流出係数は損失モデルによる有効降雨の比（損失モデルなしでは1）とする
+        let rainfall_total: f64 = data.iter().map(|e| e.intensity).sum();
+        let coefficient = if rainfall_total > 0.0 {
+            runoff_rainfall.iter().sum::<f64>() / rainfall_total
+        } else {
+            1.0
+        };
+        match plane.rational_tc(&params.formula) {
+            Some((tc, intensity)) => println!(
+                "Rational method: tc = {:.1} min (kinematic wave), K(tc) = {:.3} mm/h, C = {:.3}, \
+                 Q = C·i·A/3.6 = {:.3} m³/s",
+                tc,
+                intensity,
+                coefficient,
+                rational::peak_discharge(coefficient, intensity, plane.area())
+            ),
+            None => println!("Rational method: time of concentration did not converge"),
+        }
+    }
+    let routed = match (&validated.pond, &validated.runoff, &hydrograph) {
+        (Some((table, target)), Some((model, _)), Some(hydrograph)) => {
+            // 初期時刻は降雨前の流量（無降雨で1ステップ計算した流量）を放流する定常状態とする
+            let initial = model.discharge(&[], params.t, 1)[0];
+            let times: Vec<f64> = std::iter::once(0.0)
+                .chain(hydrograph.iter().map(|e| e.time_minutes))
+                .collect();
+            let inflow: Vec<f64> = std::iter::once(initial)
+                .chain(hydrograph.iter().map(|e| e.discharge))
+                .collect();
+            Some(route_pond(table, &times, &inflow, *target)?)
+        }
+        _ => None,
+    };
+
+    let comparison = validated.compare.map(|pattern| {
+        let label = match validated.peak_ratio {
+            Some(r) => format!("chicago r={}", r),
+            None => pattern_name(validated.pattern),
+        };
+        let other = distribution::arrange(
+            &increments,
+            pattern,
+            &validated.pattern_options,
+            params.t,
+        );
+        (other, label, pattern_name(pattern))
+    });
+    let extras = chart::ChartExtras {
+        comparison: comparison.as_ref().map(|(other, label, other_label)| {
+            (other.as_slice(), (label.as_str(), other_label.as_str()))
+        }),
+        annotation,
+        hydrograph: hydrograph.as_deref(),
+        routed: routed.as_deref(),
+    };
+    let chart_options = &validated.output_config.chart;
+    let render_chart = |path: &std::path::Path| -> anyhow::Result<()> {
+        let path = chart_options.output_path(path);
+        chart::render(&data, &extras, &path, params.t, chart_options)?;
+        println!(
+            "{} output: {}",
+            chart_options.format_for(&path).extension().to_uppercase(),
+            path.display()
+        );
+        Ok(())
+    };
 
     let output_path = &validated.output_config.output_path;
+    let write_runoff = || -> anyhow::Result<()> {
+        if let Some(hydrograph) = &hydrograph {
+            let path = runoff::csv_path(output_path, "runoff");
+            runoff::write(hydrograph, &path)?;
+            println!("Runoff CSV output: {}", path.display());
+        }
+        if let Some((runoff::Runoff::Tank(model), steps)) = &validated.runoff {
+            let path = runoff::csv_path(output_path, "tanks");
+            let states = model.simulate(&runoff_rainfall, params.t, *steps);
+            tank_model::write_states(&states, params.t, &path)?;
+            println!("Tank CSV output: {}", path.display());
+        }
+        if let Some(routed) = &routed {
+            let path = runoff::csv_path(output_path, "pond");
+            pond::write(routed, &path)?;
+            println!("Pond CSV output: {}", path.display());
+        }
+        Ok(())
+    };
 
     match validated.output_config.format {
-        types::OutputFormat::Png => {
-            chart::render(&data, output_path, validated.rainfall_params.t)?;
-            println!("PNG output: {}", output_path.display());
-        }
+        types::OutputFormat::Png => render_chart(output_path)?,
         types::OutputFormat::Csv => {
             let csv_path = output_path.with_extension("csv");
             csv_writer::write(&data, &csv_path)?;
             println!("CSV output: {}", csv_path.display());
+            write_runoff()?;
         }
         types::OutputFormat::Both => {
-            chart::render(&data, output_path, validated.rainfall_params.t)?;
-            println!("PNG output: {}", output_path.display());
+            render_chart(output_path)?;
 
             let csv_path = output_path.with_extension("csv");
             csv_writer::write(&data, &csv_path)?;
             println!("CSV output: {}", csv_path.display());
+            write_runoff()?;
+        }
+        types::OutputFormat::Swmm => {
+            let Some(swmm) = &validated.output_config.swmm else {
+                anyhow::bail!("--format swmm requires SWMM export settings, but none were built");
+            };
+            let path = swmm.write(&data, params.t, output_path)?;
+            match &swmm.inp {
+                Some(_) => println!("SWMM input patched: {}", path.display()),
+                None => println!("SWMM output: {}", path.display()),
+            }
+        }
+        types::OutputFormat::Json | types::OutputFormat::Ndjson => {
+            let pattern = match validated.peak_ratio {
+                Some(_) => "chicago".to_string(),
+                None => pattern_name(validated.pattern),
+            };
+            let document = json_writer::RunDocument::new(
+                params,
+                validated.preset.as_ref(),
+                pattern,
+                validated.peak_ratio,
+                &data,
+            );
+            if validated.output_config.format == types::OutputFormat::Json {
+                let path = output_path.with_extension("json");
+                json_writer::write(&document, &path)?;
+                println!("JSON output: {}", path.display());
+            } else {
+                let path = output_path.with_extension("ndjson");
+                json_writer::append_ndjson(&document, &path)?;
+                println!("NDJSON output (appended): {}", path.display());
+            }
+        }
+        types::OutputFormat::HecHms | types::OutputFormat::HecRas => {
+            let Some(hec) = &validated.output_config.hec else {
+                anyhow::bail!(
+                    "--format hec-hms and hec-ras require HEC export settings, but none were built"
+                );
+            };
+            let paths = if validated.output_config.format == types::OutputFormat::HecHms {
+                hec.write_hms(&data, params.t, output_path)?
+            } else {
+                hec.write_ras(&data, params.t, output_path)?
+            };
+            println!("HEC-DSS pathname: {}", hec.pathname());
+            for path in paths {
+                println!("HEC output: {}", path.display());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// コマンドラインで指定する名前（例: scs-ii）
+fn pattern_name(pattern: types::DistributionPattern) -> String {
+    pattern
+        .to_possible_value()
+        .map(|v| v.get_name().to_string())
+        .unwrap_or_default()
+}
+
+fn run_fit(args: &cli::FitArgs) -> anyhow::Result<()> {
+    let options = match &args.plot {
+        Some(plot_path) => Some(validator::build_chart(&args.chart, plot_path)?),
+        None => None,
+    };
+    let (durations, intensities) = idf::read_points(&args.input)?;
+    let result = fit::fit(&durations, &intensities)?;
+
+    println!("K = C / (t^A + B)");
+    println!("A = {:.6}", result.a);
+    println!("B = {:.6}", result.b);
+    println!("C = {:.6}", result.c);
+    println!();
+    println!("duration_min  observed_mm_h  fitted_mm_h  residual_mm_h");
+    for ((t, k), r) in durations.iter().zip(&intensities).zip(&result.residuals) {
+        println!("{:>12.1}  {:>13.3}  {:>11.3}  {:>13.3}", t, k, k + r, r);
+    }
+    println!();
+    println!("RMSE = {:.4} mm/h", result.rmse);
+    println!("R^2  = {:.6}", result.r_squared);
+    if result.b_at_bound() {
+        eprintln!(
+            "Warning: B reached its lower bound ({}); the observations are better fitted with B <= 0, \
+             consider --formula sherman",
+            fit::B_MIN
+        );
+    }
+
+    if let (Some(plot_path), Some(options)) = (&args.plot, &options) {
+        let plot_path = &options.output_path(plot_path);
+        chart::render_fit(
+            &durations,
+            &intensities,
+            &result.formula(),
+            plot_path,
+            options,
+        )?;
+        println!(
+            "{} output: {}",
+            options.format_for(plot_path).extension().to_uppercase(),
+            plot_path.display()
+        );
+    }
+
+    Ok(())
+}
+
+fn run_frequency(args: &cli::FrequencyArgs) -> anyhow::Result<()> {
+    let maxima = frequency::load(&args.input, args.depth)?;
+    let results = frequency::analyze(&maxima, args.return_period, args.distribution)?;
+
+    let name = |distribution: types::ProbabilityDistribution| {
+        distribution
+            .to_possible_value()
+            .map(|v| v.get_name().to_string())
+            .unwrap_or_default()
+    };
+
+    for result in &results {
+        println!("Duration {} min (N = {})", result.duration, result.samples);
+        println!("  distribution    SLSC    intensity_mm_h  jackknife_se  parameters");
+        for (i, evaluation) in result.evaluations.iter().enumerate() {
+            let marker = if i == result.selected { "*" } else { " " };
+            println!(
+                "{} {:<14} {:>6.4}  {:>14.3}  {:>12.3}  {}",
+                marker,
+                name(evaluation.distribution),
+                evaluation.slsc,
+                evaluation.quantile,
+                evaluation.jackknife_se,
+                evaluation.fitted
+            );
+        }
+        println!();
+    }
+
+    let durations: Vec<f64> = results.iter().map(|r| r.duration).collect();
+    let intensities: Vec<f64> = results.iter().map(|r| r.selected().quantile).collect();
+
+    println!("{}-year intensities (* = selected)", args.return_period);
+    println!("duration_min  intensity_mm_h  distribution");
+    for result in &results {
+        println!(
+            "{:>12.1}  {:>14.3}  {}",
+            result.duration,
+            result.selected().quantile,
+            name(result.selected().distribution)
+        );
+    }
+    // 係数の要約は参考値のため、当てはめに失敗してもIDF表の出力は続ける
+    if durations.len() >= 3 {
+        match fit::fit(&durations, &intensities) {
+            Ok(fitted) => println!(
+                "Fitted K = C / (t^A + B): A = {:.6}, B = {:.6}, C = {:.6} (R^2 = {:.6})",
+                fitted.a, fitted.b, fitted.c, fitted.r_squared
+            ),
+            Err(e) => eprintln!("Warning: skipped fitting K = C / (t^A + B): {:#}", e),
+        }
+    }
+
+    if let Some(output_path) = &args.output {
+        idf::write_points(output_path, &durations, &intensities)?;
+        println!("IDF table output: {}", output_path.display());
+    }
+
+    Ok(())
+}
+
+fn run_rational(args: &cli::RationalArgs) -> anyhow::Result<()> {
+    let params = validator::validate_rational(args)?;
+
+    if let Some(preset) = &params.preset {
+        println!("Preset: {} ({}; source: {})", preset.name, preset.region, preset.source);
+    }
+    println!("Runoff coefficient C = {:.3}", params.runoff_coefficient);
+    println!("Catchment area A = {} km²", params.area);
+    match params.tc_estimate {
+        Some((method, length, slope)) => {
+            let name = method
+                .to_possible_value()
+                .map(|v| v.get_name().to_string())
+                .unwrap_or_default();
+            println!(
+                "Time of concentration tc = {:.1} min ({}: L = {} m, S = {})",
+                params.tc, name, length, slope
+            );
+        }
+        None => println!("Time of concentration tc = {:.1} min", params.tc),
+    }
+    let intensity = params.formula.intensity(params.tc);
+    println!("Rainfall intensity K(tc) = {:.3} mm/h", intensity);
+    println!(
+        "Peak discharge Q = C·i·A/3.6 = {:.3} m³/s",
+        rational::peak_discharge(params.runoff_coefficient, intensity, params.area)
+    );
+
+    if !params.durations.is_empty() {
+        let rows = rational::table(
+            &params.formula,
+            params.runoff_coefficient,
+            params.area,
+            &params.durations,
+        );
+        println!();
+        println!("duration_min  intensity_mm_h  discharge_m3_s");
+        for row in &rows {
+            println!(
+                "{:>12.1}  {:>14.3}  {:>14.3}",
+                row.duration_minutes, row.intensity_mm_per_h, row.discharge_m3_per_s
+            );
+        }
+        if let Some(path) = &params.output {
+            rational::write_table(path, &rows)?;
+            println!("CSV output: {}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+fn run_pond(args: &cli::PondArgs) -> anyhow::Result<()> {
+    let params = validator::validate_pond(args)?;
+
+    let routed = route_pond(
+        &params.table,
+        &params.times,
+        &params.inflow,
+        params.target_release,
+    )?;
+    if let Some(path) = &params.output {
+        pond::write(&routed, path)?;
+        println!("CSV output: {}", path.display());
+    }
+
+    Ok(())
+}
+
+/// 修正プルス法で調整池を追跡し（許容放流量の指定時は自動設計し）、要約を表示する
+fn route_pond(
+    table: &pond::StageTable,
+    times: &[f64],
+    inflow: &[f64],
+    target_release: Option<f64>,
+) -> anyhow::Result<Vec<types::RoutedEntry>> {
+    let dt = (times[1] - times[0]) * 60.0;
+    let steps = match target_release {
+        Some(target) => {
+            let (factor, steps) = pond::size(table, inflow, dt, target)?;
+            println!(
+                "Pond sizing: allowable release {} m³/s -> storage × {:.3} (table capacity {:.0} m³)",
+                target,
+                factor,
+                table.capacity() * factor
+            );
+            steps
+        }
+        None => table.route(inflow, dt)?,
+    };
+    let summary = pond::summarize(&steps);
+    println!("Pond routing: modified Puls, Δt = {} s", dt);
+    println!("Peak inflow: {:.3} m³/s", summary.peak_inflow);
+    println!(
+        "Peak outflow: {:.3} m³/s at {} min",
+        summary.peak_outflow, times[summary.peak_index]
+    );
+    println!("Maximum stage: {:.3} m", summary.max_stage);
+    println!("Required volume: {:.0} m³", summary.max_storage);
+    Ok(pond::to_entries(&steps, times))
+}
+
+fn run_presets(action: &cli::PresetsAction) -> anyhow::Result<()> {
+    let catalog = presets::load()?;
+
+    match action {
+        cli::PresetsAction::List => {
+            println!("Preset library v{}", catalog.bundled_version);
+            if let Some(path) = presets::user_file() {
+                println!("User presets: {}", path.display());
+            }
+            println!();
+            println!("{:<20} {:<10} {:<16} {:>6}  source", "name", "formula", "region", "T[yr]");
+            for entry in &catalog.entries {
+                let preset = &entry.preset;
+                let formula = preset
+                    .formula
+                    .to_possible_value()
+                    .map(|v| v.get_name().to_string())
+                    .unwrap_or_default();
+                let return_period = preset
+                    .return_period
+                    .map(|t| t.to_string())
+                    .unwrap_or_else(|| "-".to_string());
+                let origin = match entry.origin {
+                    presets::Origin::Bundled => "",
+                    presets::Origin::User(_) => " [user]",
+                };
+                println!(
+                    "{:<20} {:<10} {:<16} {:>6}  {}{}",
+                    preset.name, formula, preset.region, return_period, preset.source, origin
+                );
+            }
+        }
+        cli::PresetsAction::Show { name } => {
+            let entry = catalog.find(name)?;
+            let preset = &entry.preset;
+            println!("name:          {}", preset.name);
+            if let Some(description) = &preset.description {
+                println!("description:   {}", description);
+            }
+            println!("source:        {}", preset.source);
+            println!("region:        {}", preset.region);
+            match preset.return_period {
+                Some(t) => println!("return period: {} years", t),
+                None => println!("return period: -"),
+            }
+            let formula = preset
+                .formula
+                .to_possible_value()
+                .map(|v| v.get_name().to_string())
+                .unwrap_or_default();
+            println!("formula:       {}", formula);
+            println!("A = {}", preset.a);
+            println!("B = {}", preset.b);
+            println!("C = {}", preset.c);
+            if let Some(m) = preset.m {
+                println!("M = {}", m);
+            }
+            match &entry.origin {
+                presets::Origin::Bundled => {
+                    println!("origin:        bundled library v{}", catalog.bundled_version)
+                }
+                presets::Origin::User(path) => println!("origin:        {}", path.display()),
+            }
         }
     }
 
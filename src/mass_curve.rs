@@ -0,0 +1,695 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+/// ユーザー定義曲線の合計・端点の許容誤差（比率）
+const SUM_TOLERANCE: f64 = 1e-3;
+
+/// 無次元累加雨量曲線の共通インターフェース
+pub trait CumulativeCurve {
+    /// 時間比 x (0〜1) における累加雨量比
+    fn cumulative(&self, x: f64) -> f64;
+
+    /// 総量 total を nt ステップに配分する
+    /// 各ステップの値は曲線の増分に比例し、合計は total に一致する。
+    fn distribute(&self, total: f64, nt: usize) -> Vec<f64> {
+        (0..nt)
+            .map(|j| {
+                let f0 = self.cumulative(j as f64 / nt as f64);
+                let f1 = self.cumulative((j + 1) as f64 / nt as f64);
+                total * (f1 - f0)
+            })
+            .collect()
+    }
+}
+
+/// 無次元累加雨量曲線
+/// (時間比, 累加雨量比) の組を時間比の昇順に持ち、両端は (0, 0) と (1, 1) とする。
+#[derive(Debug, Clone, PartialEq)]
+pub struct MassCurve {
+    points: Vec<(f64, f64)>,
+}
+
+impl MassCurve {
+    /// 検証済みの点列から曲線を構築する
+    pub fn new(points: Vec<(f64, f64)>) -> Self {
+        Self { points }
+    }
+
+    /// (時間比, 累加雨量比) の点列を検証して曲線を構築する
+    /// 端点は (0, 0) と (1, 1) でなければならず、累加雨量比は終点が1となるよう正規化する。
+    pub fn from_cumulative(points: Vec<(f64, f64)>) -> Result<Self> {
+        let (Some(&first), Some(&last)) = (points.first(), points.last()) else {
+            bail!("Mass curve has no points");
+        };
+        if points.len() < 2 {
+            bail!("Mass curve needs at least 2 points, got {}", points.len());
+        }
+        if first != (0.0, 0.0) {
+            bail!(
+                "Mass curve must start at (0, 0), got ({}, {})",
+                first.0,
+                first.1
+            );
+        }
+        if last.0 != 1.0 || (last.1 - 1.0).abs() > SUM_TOLERANCE {
+            bail!("Mass curve must end at (1, 1), got ({}, {})", last.0, last.1);
+        }
+        for (i, pair) in points.windows(2).enumerate() {
+            if pair[1].0 <= pair[0].0 {
+                bail!(
+                    "Mass curve time fractions must increase: point {} ({}) is not after point {} ({})",
+                    i + 2,
+                    pair[1].0,
+                    i + 1,
+                    pair[0].0
+                );
+            }
+            if pair[1].1 < pair[0].1 {
+                bail!(
+                    "Mass curve must be monotonic: point {} ({}) is below point {} ({})",
+                    i + 2,
+                    pair[1].1,
+                    i + 1,
+                    pair[0].1
+                );
+            }
+        }
+
+        let scale = last.1;
+        Ok(Self {
+            points: points.into_iter().map(|(x, y)| (x, y / scale)).collect(),
+        })
+    }
+
+    /// 等間隔ステップごとの雨量百分率から曲線を構築する
+    /// 百分率の合計は100でなければならない。
+    pub fn from_step_percent(percent: &[f64]) -> Result<Self> {
+        if percent.is_empty() {
+            bail!("Step percentages are empty");
+        }
+        if let Some((i, p)) = percent.iter().enumerate().find(|(_, p)| **p < 0.0) {
+            bail!("Step percentage {} must not be negative, got {}", i + 1, p);
+        }
+        let total: f64 = percent.iter().sum();
+        if (total / 100.0 - 1.0).abs() > SUM_TOLERANCE {
+            bail!("Step percentages must sum to 100, got {}", total);
+        }
+
+        let n = percent.len();
+        let mut cumulative = 0.0;
+        let mut points = vec![(0.0, 0.0)];
+        for (i, p) in percent.iter().enumerate() {
+            cumulative += p / total;
+            points.push(((i + 1) as f64 / n as f64, cumulative));
+        }
+        points[n] = (1.0, 1.0);
+        Ok(Self { points })
+    }
+}
+
+impl CumulativeCurve for MassCurve {
+    /// 線形補間
+    fn cumulative(&self, x: f64) -> f64 {
+        let x = x.clamp(0.0, 1.0);
+        let upper = self
+            .points
+            .iter()
+            .position(|&(px, _)| px >= x)
+            .unwrap_or(self.points.len() - 1);
+        if upper == 0 {
+            return self.points[0].1;
+        }
+        let (x0, y0) = self.points[upper - 1];
+        let (x1, y1) = self.points[upper];
+        if x1 <= x0 {
+            return y1;
+        }
+        y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+    }
+}
+
+/// 台形型降雨の強度波形（立ち上がり・一定・減衰の時間比、合計1）
+/// 立ち上がりと減衰は線形で、一定区間の強度は総量が1となるように決まる。
+/// 一定区間を0とすると Yen & Chow の三角形型降雨となる。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trapezoid {
+    pub rise: f64,
+    pub plateau: f64,
+    pub fall: f64,
+}
+
+impl Trapezoid {
+    /// Yen & Chow の三角形型降雨（r: ピーク前の時間比）
+    pub fn triangle(r: f64) -> Self {
+        Self {
+            rise: r,
+            plateau: 0.0,
+            fall: 1.0 - r,
+        }
+    }
+
+    /// 最大強度（平均強度に対する比）
+    pub fn peak(&self) -> f64 {
+        1.0 / (self.plateau + (self.rise + self.fall) / 2.0)
+    }
+}
+
+impl CumulativeCurve for Trapezoid {
+    fn cumulative(&self, x: f64) -> f64 {
+        let x = x.clamp(0.0, 1.0);
+        let h = self.peak();
+        if x < self.rise {
+            h * x * x / (2.0 * self.rise)
+        } else if x <= self.rise + self.plateau || self.fall <= 0.0 {
+            (h * (self.rise / 2.0 + (x - self.rise))).min(1.0)
+        } else {
+            1.0 - h * (1.0 - x).powi(2) / (2.0 * self.fall)
+        }
+    }
+}
+
+/// ユーザー定義曲線の列
+/// 累加曲線 (time_fraction, cumulative_fraction) またはステップ百分率 step_percent のいずれか。
+#[derive(Debug, Default, Deserialize)]
+struct CurveColumns {
+    #[serde(default)]
+    time_fraction: Vec<f64>,
+    #[serde(default)]
+    cumulative_fraction: Vec<f64>,
+    #[serde(default)]
+    step_percent: Vec<f64>,
+}
+
+/// ユーザー定義曲線CSVの1行
+#[derive(Debug, Deserialize)]
+struct CurveRow {
+    #[serde(default)]
+    time_fraction: Option<f64>,
+    #[serde(default)]
+    cumulative_fraction: Option<f64>,
+    #[serde(default)]
+    step_percent: Option<f64>,
+}
+
+impl CurveColumns {
+    fn into_curve(self) -> Result<MassCurve> {
+        let cumulative = !self.time_fraction.is_empty() || !self.cumulative_fraction.is_empty();
+        match (cumulative, self.step_percent.is_empty()) {
+            (true, true) => {
+                if self.time_fraction.len() != self.cumulative_fraction.len() {
+                    bail!(
+                        "Got {} time fractions but {} cumulative fractions",
+                        self.time_fraction.len(),
+                        self.cumulative_fraction.len()
+                    );
+                }
+                MassCurve::from_cumulative(
+                    self.time_fraction
+                        .into_iter()
+                        .zip(self.cumulative_fraction)
+                        .collect(),
+                )
+            }
+            (false, false) => MassCurve::from_step_percent(&self.step_percent),
+            _ => bail!(
+                "Mass curve must contain either time_fraction and cumulative_fraction, or step_percent"
+            ),
+        }
+    }
+}
+
+/// ユーザー定義の無次元累加雨量曲線を読み込む
+///
+/// 拡張子が .json の場合はJSON、それ以外はCSVとして読み込む。
+/// 累加曲線は列 time_fraction, cumulative_fraction（0〜1）、
+/// ステップごとの配分は列 step_percent（合計100）で与える。
+///
+/// ```json
+/// { "step_percent": [10, 25, 40, 15, 10] }
+/// ```
+pub fn load(path: &Path) -> Result<MassCurve> {
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+
+    let columns = if is_json {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to open mass curve: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse mass curve: {}", path.display()))?
+    } else {
+        let mut rdr = csv::Reader::from_path(path)
+            .with_context(|| format!("Failed to open mass curve: {}", path.display()))?;
+        let mut columns = CurveColumns::default();
+        for (i, record) in rdr.deserialize().enumerate() {
+            let row: CurveRow = record
+                .with_context(|| format!("Failed to read mass curve row in {}", path.display()))?;
+            let missing = |name: &str| {
+                anyhow::anyhow!("Mass curve row {} is missing {}", i + 1, name)
+            };
+            if row.step_percent.is_some() || row.time_fraction.is_none() {
+                columns
+                    .step_percent
+                    .push(row.step_percent.ok_or_else(|| missing("step_percent"))?);
+            } else {
+                columns.time_fraction.extend(row.time_fraction);
+                columns.cumulative_fraction.push(
+                    row.cumulative_fraction
+                        .ok_or_else(|| missing("cumulative_fraction"))?,
+                );
+            }
+        }
+        columns
+    };
+
+    columns
+        .into_curve()
+        .with_context(|| format!("Invalid mass curve: {}", path.display()))
+}
+
+/// NRCS（旧SCS）24時間降雨の累加雨量比
+/// TR-55 (USDA-SCS, 1986) の Type I, IA, II, III 曲線（時刻[時], 累加雨量比）。
+const SCS_TYPE_I: [(f64, f64); 22] = [
+    (0.0, 0.000),
+    (2.0, 0.035),
+    (4.0, 0.076),
+    (6.0, 0.125),
+    (7.0, 0.156),
+    (8.0, 0.194),
+    (8.5, 0.219),
+    (9.0, 0.254),
+    (9.5, 0.303),
+    (9.75, 0.362),
+    (10.0, 0.515),
+    (10.5, 0.583),
+    (11.0, 0.624),
+    (11.5, 0.654),
+    (12.0, 0.682),
+    (12.5, 0.706),
+    (13.0, 0.727),
+    (13.5, 0.748),
+    (14.0, 0.767),
+    (16.0, 0.830),
+    (20.0, 0.926),
+    (24.0, 1.000),
+];
+
+const SCS_TYPE_IA: [(f64, f64); 21] = [
+    (0.0, 0.000),
+    (2.0, 0.050),
+    (4.0, 0.116),
+    (6.0, 0.206),
+    (7.0, 0.268),
+    (7.5, 0.310),
+    (7.75, 0.425),
+    (8.0, 0.480),
+    (8.5, 0.520),
+    (9.0, 0.550),
+    (9.5, 0.577),
+    (10.0, 0.601),
+    (10.5, 0.624),
+    (11.0, 0.645),
+    (11.5, 0.664),
+    (12.0, 0.683),
+    (13.0, 0.715),
+    (14.0, 0.743),
+    (16.0, 0.794),
+    (20.0, 0.880),
+    (24.0, 1.000),
+];
+
+const SCS_TYPE_II: [(f64, f64); 22] = [
+    (0.0, 0.000),
+    (2.0, 0.022),
+    (4.0, 0.048),
+    (6.0, 0.080),
+    (7.0, 0.098),
+    (8.0, 0.120),
+    (8.5, 0.133),
+    (9.0, 0.147),
+    (9.5, 0.163),
+    (10.0, 0.181),
+    (10.5, 0.204),
+    (11.0, 0.235),
+    (11.5, 0.283),
+    (11.75, 0.357),
+    (12.0, 0.663),
+    (12.5, 0.735),
+    (13.0, 0.772),
+    (13.5, 0.799),
+    (14.0, 0.820),
+    (16.0, 0.880),
+    (20.0, 0.952),
+    (24.0, 1.000),
+];
+
+const SCS_TYPE_III: [(f64, f64); 21] = [
+    (0.0, 0.000),
+    (2.0, 0.020),
+    (4.0, 0.043),
+    (6.0, 0.072),
+    (7.0, 0.089),
+    (8.0, 0.115),
+    (8.5, 0.130),
+    (9.0, 0.148),
+    (9.5, 0.167),
+    (10.0, 0.189),
+    (10.5, 0.216),
+    (11.0, 0.250),
+    (11.5, 0.298),
+    (12.0, 0.500),
+    (12.5, 0.702),
+    (13.0, 0.751),
+    (13.5, 0.785),
+    (14.0, 0.811),
+    (16.0, 0.886),
+    (20.0, 0.957),
+    (24.0, 1.000),
+];
+
+/// NRCS 24時間降雨の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScsType {
+    Type1,
+    Type1a,
+    Type2,
+    Type3,
+}
+
+/// NRCS 24時間降雨の累加雨量曲線（時間を24時間で無次元化）
+pub fn scs(storm: ScsType) -> MassCurve {
+    let table: &[(f64, f64)] = match storm {
+        ScsType::Type1 => &SCS_TYPE_I,
+        ScsType::Type1a => &SCS_TYPE_IA,
+        ScsType::Type2 => &SCS_TYPE_II,
+        ScsType::Type3 => &SCS_TYPE_III,
+    };
+    MassCurve::new(table.iter().map(|&(h, f)| (h / 24.0, f)).collect())
+}
+
+/// Huff 曲線の確率水準[%]
+pub const HUFF_PROBABILITIES: [u8; 9] = [10, 20, 30, 40, 50, 60, 70, 80, 90];
+
+/// Huff 曲線の時間比の刻み（0, 0.1, ..., 1.0）
+const HUFF_STEPS: usize = 11;
+
+/// Huff (1967, 1990) の四分位別累加雨量比（イリノイ州の豪雨に基づく）
+/// 行は確率水準 10%〜90%、列は時間比 0, 0.1, ..., 1.0 における累加雨量比。
+const HUFF_FIRST_QUARTILE: [[f64; HUFF_STEPS]; 9] = [
+    [0.00, 0.55, 0.80, 0.88, 0.92, 0.95, 0.97, 0.98, 0.99, 1.00, 1.00],
+    [0.00, 0.43, 0.70, 0.81, 0.87, 0.91, 0.94, 0.96, 0.98, 0.99, 1.00],
+    [0.00, 0.36, 0.63, 0.76, 0.83, 0.88, 0.91, 0.94, 0.96, 0.98, 1.00],
+    [0.00, 0.30, 0.56, 0.71, 0.79, 0.85, 0.89, 0.92, 0.95, 0.98, 1.00],
+    [0.00, 0.25, 0.50, 0.66, 0.75, 0.82, 0.87, 0.90, 0.94, 0.97, 1.00],
+    [0.00, 0.21, 0.44, 0.60, 0.71, 0.79, 0.84, 0.88, 0.92, 0.96, 1.00],
+    [0.00, 0.17, 0.37, 0.53, 0.66, 0.75, 0.81, 0.86, 0.91, 0.95, 1.00],
+    [0.00, 0.12, 0.30, 0.46, 0.59, 0.70, 0.77, 0.83, 0.89, 0.94, 1.00],
+    [0.00, 0.07, 0.21, 0.36, 0.50, 0.62, 0.71, 0.79, 0.86, 0.93, 1.00],
+];
+
+const HUFF_SECOND_QUARTILE: [[f64; HUFF_STEPS]; 9] = [
+    [0.00, 0.10, 0.30, 0.63, 0.82, 0.90, 0.94, 0.96, 0.98, 0.99, 1.00],
+    [0.00, 0.08, 0.24, 0.53, 0.75, 0.86, 0.91, 0.94, 0.97, 0.99, 1.00],
+    [0.00, 0.07, 0.20, 0.46, 0.69, 0.82, 0.88, 0.92, 0.96, 0.98, 1.00],
+    [0.00, 0.06, 0.17, 0.40, 0.63, 0.78, 0.85, 0.90, 0.94, 0.97, 1.00],
+    [0.00, 0.05, 0.14, 0.35, 0.58, 0.73, 0.82, 0.88, 0.93, 0.97, 1.00],
+    [0.00, 0.04, 0.12, 0.30, 0.52, 0.68, 0.78, 0.85, 0.91, 0.96, 1.00],
+    [0.00, 0.03, 0.10, 0.25, 0.45, 0.62, 0.74, 0.82, 0.89, 0.95, 1.00],
+    [0.00, 0.02, 0.07, 0.19, 0.37, 0.55, 0.68, 0.78, 0.87, 0.94, 1.00],
+    [0.00, 0.01, 0.04, 0.12, 0.27, 0.45, 0.60, 0.72, 0.83, 0.92, 1.00],
+];
+
+const HUFF_THIRD_QUARTILE: [[f64; HUFF_STEPS]; 9] = [
+    [0.00, 0.06, 0.14, 0.24, 0.37, 0.58, 0.84, 0.94, 0.97, 0.99, 1.00],
+    [0.00, 0.05, 0.11, 0.19, 0.30, 0.49, 0.76, 0.90, 0.95, 0.98, 1.00],
+    [0.00, 0.04, 0.09, 0.16, 0.26, 0.43, 0.69, 0.86, 0.93, 0.97, 1.00],
+    [0.00, 0.03, 0.08, 0.14, 0.22, 0.37, 0.62, 0.82, 0.91, 0.96, 1.00],
+    [0.00, 0.03, 0.07, 0.12, 0.19, 0.32, 0.56, 0.77, 0.89, 0.95, 1.00],
+    [0.00, 0.02, 0.06, 0.10, 0.16, 0.27, 0.49, 0.72, 0.86, 0.94, 1.00],
+    [0.00, 0.02, 0.05, 0.08, 0.13, 0.22, 0.42, 0.66, 0.82, 0.93, 1.00],
+    [0.00, 0.01, 0.03, 0.06, 0.10, 0.17, 0.34, 0.58, 0.77, 0.91, 1.00],
+    [0.00, 0.01, 0.02, 0.04, 0.07, 0.12, 0.25, 0.47, 0.69, 0.87, 1.00],
+];
+
+const HUFF_FOURTH_QUARTILE: [[f64; HUFF_STEPS]; 9] = [
+    [0.00, 0.05, 0.12, 0.20, 0.28, 0.37, 0.47, 0.60, 0.80, 0.96, 1.00],
+    [0.00, 0.04, 0.10, 0.16, 0.23, 0.31, 0.40, 0.52, 0.71, 0.92, 1.00],
+    [0.00, 0.04, 0.08, 0.13, 0.19, 0.26, 0.34, 0.45, 0.63, 0.88, 1.00],
+    [0.00, 0.03, 0.07, 0.11, 0.16, 0.22, 0.29, 0.39, 0.56, 0.84, 1.00],
+    [0.00, 0.03, 0.06, 0.09, 0.13, 0.18, 0.24, 0.33, 0.49, 0.79, 1.00],
+    [0.00, 0.02, 0.05, 0.08, 0.11, 0.15, 0.20, 0.28, 0.42, 0.73, 1.00],
+    [0.00, 0.02, 0.04, 0.06, 0.09, 0.12, 0.16, 0.23, 0.35, 0.66, 1.00],
+    [0.00, 0.01, 0.03, 0.05, 0.07, 0.09, 0.12, 0.17, 0.27, 0.57, 1.00],
+    [0.00, 0.01, 0.02, 0.03, 0.05, 0.06, 0.08, 0.12, 0.19, 0.45, 1.00],
+];
+
+/// Huff 曲線の四分位（降雨の大部分が生じる区間）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HuffQuartile {
+    First,
+    Second,
+    Third,
+    Fourth,
+}
+
+/// Huff 曲線の無次元累加雨量曲線
+/// probability: 確率水準[%]（HUFF_PROBABILITIES のいずれか）
+pub fn huff(quartile: HuffQuartile, probability: u8) -> Option<MassCurve> {
+    let level = HUFF_PROBABILITIES.iter().position(|&p| p == probability)?;
+    let table = match quartile {
+        HuffQuartile::First => &HUFF_FIRST_QUARTILE,
+        HuffQuartile::Second => &HUFF_SECOND_QUARTILE,
+        HuffQuartile::Third => &HUFF_THIRD_QUARTILE,
+        HuffQuartile::Fourth => &HUFF_FOURTH_QUARTILE,
+    };
+    let points = table[level]
+        .iter()
+        .enumerate()
+        .map(|(i, &f)| (i as f64 / (HUFF_STEPS - 1) as f64, f))
+        .collect();
+    Some(MassCurve::new(points))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_SCS: [ScsType; 4] = [ScsType::Type1, ScsType::Type1a, ScsType::Type2, ScsType::Type3];
+
+    const ALL_HUFF: [HuffQuartile; 4] = [
+        HuffQuartile::First,
+        HuffQuartile::Second,
+        HuffQuartile::Third,
+        HuffQuartile::Fourth,
+    ];
+
+    #[test]
+    fn scs_curves_are_monotonic_from_zero_to_one() {
+        for storm in ALL_SCS {
+            let curve = scs(storm);
+            assert_eq!(curve.points.first(), Some(&(0.0, 0.0)));
+            assert_eq!(curve.points.last(), Some(&(1.0, 1.0)));
+            for pair in curve.points.windows(2) {
+                assert!(pair[1].0 > pair[0].0, "{:?}: time not increasing", storm);
+                assert!(pair[1].1 >= pair[0].1, "{:?}: mass not monotonic", storm);
+            }
+        }
+    }
+
+    #[test]
+    fn cumulative_interpolates_linearly() {
+        let curve = MassCurve::new(vec![(0.0, 0.0), (0.5, 0.8), (1.0, 1.0)]);
+        assert!((curve.cumulative(0.25) - 0.4).abs() < 1e-12);
+        assert!((curve.cumulative(0.75) - 0.9).abs() < 1e-12);
+        assert_eq!(curve.cumulative(0.0), 0.0);
+        assert_eq!(curve.cumulative(1.0), 1.0);
+    }
+
+    #[test]
+    fn distribute_preserves_total() {
+        for storm in ALL_SCS {
+            let values = scs(storm).distribute(250.0, 144);
+            let total: f64 = values.iter().sum();
+            assert!((total - 250.0).abs() < 1e-9, "{:?}: total {}", storm, total);
+            assert!(values.iter().all(|v| *v >= 0.0));
+        }
+    }
+
+    #[test]
+    fn type_ii_peaks_just_before_noon() {
+        let values = scs(ScsType::Type2).distribute(100.0, 24);
+        let peak = values
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, _)| i)
+            .unwrap();
+        // 11〜12時のステップ
+        assert_eq!(peak, 11);
+    }
+
+    #[test]
+    fn huff_curves_are_monotonic_from_zero_to_one() {
+        for quartile in ALL_HUFF {
+            for probability in HUFF_PROBABILITIES {
+                let curve = huff(quartile, probability).unwrap();
+                assert_eq!(curve.points.first(), Some(&(0.0, 0.0)));
+                assert_eq!(curve.points.last(), Some(&(1.0, 1.0)));
+                for pair in curve.points.windows(2) {
+                    assert!(
+                        pair[1].1 >= pair[0].1,
+                        "{:?} {}%: mass not monotonic",
+                        quartile,
+                        probability
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lower_huff_probability_is_more_advanced() {
+        // 確率水準が低いほど早い時刻に多くの雨量が集中する
+        for quartile in ALL_HUFF {
+            for pair in HUFF_PROBABILITIES.windows(2) {
+                let steeper = huff(quartile, pair[0]).unwrap();
+                let flatter = huff(quartile, pair[1]).unwrap();
+                for (a, b) in steeper.points.iter().zip(&flatter.points) {
+                    assert!(a.1 >= b.1, "{:?}: {}% below {}%", quartile, pair[0], pair[1]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn huff_quartile_concentrates_rainfall() {
+        for (q, quartile) in ALL_HUFF.into_iter().enumerate() {
+            let values = huff(quartile, 50).unwrap().distribute(100.0, 4);
+            let peak = values
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .map(|(i, _)| i)
+                .unwrap();
+            assert_eq!(peak, q, "{:?}: {:?}", quartile, values);
+        }
+    }
+
+    #[test]
+    fn huff_unknown_probability_is_none() {
+        assert!(huff(HuffQuartile::First, 55).is_none());
+    }
+
+    #[test]
+    fn from_cumulative_normalizes_end_point() {
+        let curve = MassCurve::from_cumulative(vec![(0.0, 0.0), (0.5, 0.6), (1.0, 0.9995)]).unwrap();
+        assert_eq!(curve.points.last(), Some(&(1.0, 1.0)));
+    }
+
+    #[test]
+    fn from_cumulative_rejects_decreasing_values() {
+        let err = MassCurve::from_cumulative(vec![(0.0, 0.0), (0.5, 0.6), (0.7, 0.5), (1.0, 1.0)])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("monotonic"), "Error: {}", err);
+    }
+
+    #[test]
+    fn from_step_percent_requires_sum_of_100() {
+        let err = MassCurve::from_step_percent(&[20.0, 30.0, 40.0])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("sum to 100"), "Error: {}", err);
+    }
+
+    #[test]
+    fn step_percent_is_reinterpolated_onto_grid() {
+        let curve = MassCurve::from_step_percent(&[50.0, 30.0, 20.0]).unwrap();
+        // 3ステップの配分を6ステップに補間すると各ステップを半分ずつに分ける
+        let values = curve.distribute(100.0, 6);
+        let expected = [25.0, 25.0, 15.0, 15.0, 10.0, 10.0];
+        for (v, e) in values.iter().zip(expected) {
+            assert!((v - e).abs() < 1e-9, "{:?}", values);
+        }
+    }
+
+    #[test]
+    fn load_csv_and_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("curve.csv");
+        fs::write(
+            &csv_path,
+            "time_fraction,cumulative_fraction\n0,0\n0.25,0.1\n0.5,0.7\n1,1\n",
+        )
+        .unwrap();
+        let curve = load(&csv_path).unwrap();
+        assert!((curve.cumulative(0.5) - 0.7).abs() < 1e-12);
+
+        let step_path = dir.path().join("steps.csv");
+        fs::write(&step_path, "step_percent\n40\n60\n").unwrap();
+        let curve = load(&step_path).unwrap();
+        assert!((curve.cumulative(0.5) - 0.4).abs() < 1e-12);
+
+        let json_path = dir.path().join("curve.json");
+        fs::write(&json_path, r#"{ "step_percent": [10, 25, 40, 15, 10] }"#).unwrap();
+        let curve = load(&json_path).unwrap();
+        assert!((curve.cumulative(0.4) - 0.35).abs() < 1e-12);
+    }
+
+    #[test]
+    fn load_rejects_mixed_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("curve.json");
+        fs::write(
+            &path,
+            r#"{ "time_fraction": [0, 1], "cumulative_fraction": [0, 1], "step_percent": [100] }"#,
+        )
+        .unwrap();
+        let err = format!("{:#}", load(&path).unwrap_err());
+        assert!(err.contains("either"), "Error: {}", err);
+    }
+
+    #[test]
+    fn triangle_peaks_at_advancement_and_preserves_total() {
+        let values = Trapezoid::triangle(0.25).distribute(100.0, 8);
+        let total: f64 = values.iter().sum();
+        assert!((total - 100.0).abs() < 1e-9);
+        // 時間比0.25を挟む2ステップが最大
+        let smallest_of_peak = values[1].min(values[2]);
+        for (i, v) in values.iter().enumerate().filter(|(i, _)| *i != 1 && *i != 2) {
+            assert!(*v <= smallest_of_peak + 1e-12, "step {}: {:?}", i, values);
+        }
+        // ピーク強度は平均の2倍
+        assert_eq!(Trapezoid::triangle(0.25).peak(), 2.0);
+    }
+
+    #[test]
+    fn trapezoid_is_flat_on_plateau() {
+        let shape = Trapezoid {
+            rise: 0.25,
+            plateau: 0.5,
+            fall: 0.25,
+        };
+        let values = shape.distribute(75.0, 4);
+        // 最大強度は平均の4/3倍、一定区間の2ステップは 75 / 4 * 4/3 = 25
+        assert!((values[1] - 25.0).abs() < 1e-9);
+        assert!((values[2] - 25.0).abs() < 1e-9);
+        assert!((values[0] - 12.5).abs() < 1e-9);
+        assert!((shape.cumulative(1.0) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn trapezoid_without_tails_is_uniform() {
+        let shape = Trapezoid {
+            rise: 0.0,
+            plateau: 1.0,
+            fall: 0.0,
+        };
+        for v in shape.distribute(60.0, 6) {
+            assert!((v - 10.0).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,464 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::types::RoutedEntry;
+
+/// 自動設計で容量倍率を探索する上限
+const MAX_SCALE: f64 = 1e6;
+
+/// 自動設計の二分法の反復回数
+const SIZING_ITERATIONS: usize = 60;
+
+/// 水位-貯留量-放流量表の1行
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct StageRow {
+    /// 水位[m]
+    #[serde(rename = "stage_m")]
+    pub stage: f64,
+    /// 貯留量[m³]
+    #[serde(rename = "storage_m3")]
+    pub storage: f64,
+    /// 放流量[m³/s]
+    #[serde(rename = "discharge_m3_per_s")]
+    pub discharge: f64,
+}
+
+/// 調整池の水位-貯留量-放流量表
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageTable {
+    rows: Vec<StageRow>,
+}
+
+/// 1ステップの追跡結果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoutingStep {
+    /// 流入量[m³/s]
+    pub inflow: f64,
+    /// 放流量[m³/s]
+    pub outflow: f64,
+    /// 貯留量[m³]
+    pub storage: f64,
+    /// 水位[m]
+    pub stage: f64,
+}
+
+/// 追跡結果の要約
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub peak_inflow: f64,
+    pub peak_outflow: f64,
+    /// ピーク放流量の発生ステップ
+    pub peak_index: usize,
+    pub max_stage: f64,
+    /// 最大貯留量（必要容量）[m³]
+    pub max_storage: f64,
+}
+
+impl StageTable {
+    /// 水位の昇順に並んだ表を検証して構築する
+    /// 水位・貯留量は狭義単調増加、放流量は単調非減少とする。
+    pub fn new(rows: Vec<StageRow>) -> Result<Self> {
+        if rows.len() < 2 {
+            bail!(
+                "Stage-storage-discharge table needs at least 2 rows, got {}",
+                rows.len()
+            );
+        }
+        if let Some((i, row)) = rows
+            .iter()
+            .enumerate()
+            .find(|(_, r)| r.storage < 0.0 || r.discharge < 0.0)
+        {
+            bail!(
+                "Row {}: storage and discharge must be non-negative, got ({}, {})",
+                i + 1,
+                row.storage,
+                row.discharge
+            );
+        }
+        for (i, pair) in rows.windows(2).enumerate() {
+            if pair[1].stage <= pair[0].stage || pair[1].storage <= pair[0].storage {
+                bail!(
+                    "Row {}: stage and storage must be strictly increasing",
+                    i + 2
+                );
+            }
+            if pair[1].discharge < pair[0].discharge {
+                bail!("Row {}: discharge must not decrease with stage", i + 2);
+            }
+        }
+        Ok(Self { rows })
+    }
+
+    /// 貯留量を factor 倍した表（放流施設はそのままで池の平面積を拡大縮小する）
+    pub fn scaled(&self, factor: f64) -> Self {
+        Self {
+            rows: self
+                .rows
+                .iter()
+                .map(|r| StageRow {
+                    storage: r.storage * factor,
+                    ..*r
+                })
+                .collect(),
+        }
+    }
+
+    /// 表の最大貯留量[m³]
+    pub fn capacity(&self) -> f64 {
+        self.rows[self.rows.len() - 1].storage
+    }
+
+    /// 修正プルス法による追跡
+    /// (2S/Δt + O)_{n+1} = I_n + I_{n+1} + (2S/Δt - O)_n
+    /// inflow[0] は初期時刻の流入量で、その流量を放流する定常状態から開始する。
+    /// dt: 時間間隔[秒]
+    pub fn route(&self, inflow: &[f64], dt: f64) -> Result<Vec<RoutingStep>> {
+        let indicator: Vec<(f64, f64)> = self
+            .rows
+            .iter()
+            .map(|r| (2.0 * r.storage / dt + r.discharge, r.discharge))
+            .collect();
+        let storage_to_stage: Vec<(f64, f64)> =
+            self.rows.iter().map(|r| (r.storage, r.stage)).collect();
+        let discharge_to_storage: Vec<(f64, f64)> =
+            self.rows.iter().map(|r| (r.discharge, r.storage)).collect();
+
+        let Some(&first) = inflow.first() else {
+            return Ok(Vec::new());
+        };
+        let max_discharge = self.rows[self.rows.len() - 1].discharge;
+        if first > max_discharge {
+            bail!(
+                "Initial inflow {:.3} m³/s exceeds the largest discharge in the pond table ({} m³/s)",
+                first,
+                max_discharge
+            );
+        }
+        let storage = lookup(&discharge_to_storage, first);
+        let mut steps = vec![RoutingStep {
+            inflow: first,
+            outflow: first.max(self.rows[0].discharge),
+            storage,
+            stage: lookup(&storage_to_stage, storage),
+        }];
+
+        let max_indicator = indicator[indicator.len() - 1].0;
+        for pair in inflow.windows(2) {
+            let previous = steps[steps.len() - 1];
+            let n = pair[0] + pair[1] + 2.0 * previous.storage / dt - previous.outflow;
+            if n > max_indicator {
+                bail!(
+                    "Pond overtops at {:.0} s: storage exceeds the table capacity ({} m³). \
+                     Extend the stage-storage-discharge table",
+                    steps.len() as f64 * dt,
+                    self.capacity()
+                );
+            }
+            let outflow = lookup(&indicator, n);
+            let storage = ((n - outflow) * dt / 2.0).max(0.0);
+            steps.push(RoutingStep {
+                inflow: pair[1],
+                outflow,
+                storage,
+                stage: lookup(&storage_to_stage, storage),
+            });
+        }
+        Ok(steps)
+    }
+}
+
+/// 追跡結果を要約する
+pub fn summarize(steps: &[RoutingStep]) -> Summary {
+    let (peak_index, peak) = steps
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.outflow.total_cmp(&b.1.outflow))
+        .map(|(i, s)| (i, s.outflow))
+        .unwrap_or((0, 0.0));
+    Summary {
+        peak_inflow: steps.iter().map(|s| s.inflow).fold(0.0, f64::max),
+        peak_outflow: peak,
+        peak_index,
+        max_stage: steps.iter().map(|s| s.stage).fold(f64::MIN, f64::max),
+        max_storage: steps.iter().map(|s| s.storage).fold(0.0, f64::max),
+    }
+}
+
+/// 許容放流量 target[m³/s] 以下に抑える最小の容量倍率を二分法で探索する
+/// 返却値は (容量倍率, 拡大縮小した表での追跡結果)。
+pub fn size(
+    table: &StageTable,
+    inflow: &[f64],
+    dt: f64,
+    target: f64,
+) -> Result<(f64, Vec<RoutingStep>)> {
+    let meets = |factor: f64| {
+        table
+            .scaled(factor)
+            .route(inflow, dt)
+            .ok()
+            .filter(|steps| summarize(steps).peak_outflow <= target)
+    };
+
+    let mut high = 1.0;
+    let mut best = loop {
+        if let Some(steps) = meets(high) {
+            break steps;
+        }
+        high *= 2.0;
+        if high > MAX_SCALE {
+            bail!(
+                "Allowable release {} m³/s cannot be met by enlarging the pond storage; \
+                 check the outlet rating in the stage-storage-discharge table",
+                target
+            );
+        }
+    };
+    let mut low = 0.0;
+    for _ in 0..SIZING_ITERATIONS {
+        let mid = (low + high) / 2.0;
+        match meets(mid) {
+            Some(steps) => {
+                high = mid;
+                best = steps;
+            }
+            None => low = mid,
+        }
+    }
+    Ok((high, best))
+}
+
+/// 追跡結果をハイドログラフ形式のエントリに変換する
+pub fn to_entries(steps: &[RoutingStep], times: &[f64]) -> Vec<RoutedEntry> {
+    steps
+        .iter()
+        .zip(times)
+        .map(|(step, &time_minutes)| RoutedEntry {
+            time_minutes,
+            inflow: step.inflow,
+            outflow: step.outflow,
+            stage: step.stage,
+            storage: step.storage,
+        })
+        .collect()
+}
+
+/// 昇順の折れ線 points 上で x の値を線形補間する（範囲外は端の値）
+fn lookup(points: &[(f64, f64)], x: f64) -> f64 {
+    match points.iter().position(|&(px, _)| px >= x) {
+        Some(0) => points[0].1,
+        Some(i) => {
+            let (x0, y0) = points[i - 1];
+            let (x1, y1) = points[i];
+            y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+        }
+        None => points[points.len() - 1].1,
+    }
+}
+
+/// 水位-貯留量-放流量表をCSVファイルから読み込む
+/// 列: stage_m, storage_m3, discharge_m3_per_s
+pub fn load_table(path: &Path) -> Result<StageTable> {
+    let mut rdr = csv::Reader::from_path(path)
+        .with_context(|| format!("Failed to open pond table: {}", path.display()))?;
+    let rows = rdr
+        .deserialize()
+        .collect::<Result<Vec<StageRow>, _>>()
+        .with_context(|| format!("Failed to read pond table: {}", path.display()))?;
+    StageTable::new(rows).with_context(|| format!("Invalid pond table: {}", path.display()))
+}
+
+/// 流入ハイドログラフCSVの1行
+#[derive(Debug, Deserialize)]
+struct InflowRow {
+    time_minutes: f64,
+    discharge_m3_per_s: f64,
+}
+
+/// 流入ハイドログラフをCSVファイルから読み込む
+/// 列: time_minutes, discharge_m3_per_s（等間隔。ハイドログラフCSVをそのまま使用できる）
+/// 返却値は (時刻[分], 流入量[m³/s])。
+pub fn load_inflow(path: &Path) -> Result<(Vec<f64>, Vec<f64>)> {
+    let mut rdr = csv::Reader::from_path(path)
+        .with_context(|| format!("Failed to open inflow hydrograph: {}", path.display()))?;
+    let mut times = Vec::new();
+    let mut inflow = Vec::new();
+    for record in rdr.deserialize() {
+        let row: InflowRow = record.with_context(|| {
+            format!("Failed to read inflow hydrograph row in {}", path.display())
+        })?;
+        if row.discharge_m3_per_s < 0.0 {
+            bail!(
+                "Inflow must be non-negative, got {} at {} min in {}",
+                row.discharge_m3_per_s,
+                row.time_minutes,
+                path.display()
+            );
+        }
+        times.push(row.time_minutes);
+        inflow.push(row.discharge_m3_per_s);
+    }
+    if times.len() < 2 {
+        bail!(
+            "Inflow hydrograph needs at least 2 rows: {}",
+            path.display()
+        );
+    }
+    let dt = times[1] - times[0];
+    if dt <= 0.0
+        || times
+            .windows(2)
+            .any(|w| ((w[1] - w[0]) - dt).abs() > 1e-6 * dt.max(1.0))
+    {
+        bail!(
+            "Inflow hydrograph times must be evenly spaced and increasing: {}",
+            path.display()
+        );
+    }
+    Ok((times, inflow))
+}
+
+/// 追跡結果をCSVファイルに出力する
+pub fn write(entries: &[RoutedEntry], path: &Path) -> Result<()> {
+    let mut wtr = csv::Writer::from_path(path)
+        .with_context(|| format!("Failed to create CSV file: {}", path.display()))?;
+
+    for entry in entries {
+        wtr.serialize(entry)
+            .with_context(|| format!("Failed to write CSV record to {}", path.display()))?;
+    }
+
+    wtr.flush()
+        .with_context(|| format!("Failed to flush CSV file: {}", path.display()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// 平面積 1000 m²、放流量 Q = 2 h の直方体の池
+    fn linear_pond() -> StageTable {
+        StageTable::new(
+            (0..=10)
+                .map(|i| {
+                    let h = i as f64 * 0.5;
+                    StageRow {
+                        stage: h,
+                        storage: 1000.0 * h,
+                        discharge: 2.0 * h,
+                    }
+                })
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    fn triangle_inflow() -> Vec<f64> {
+        let mut inflow: Vec<f64> = (0..=6).map(|i| i as f64).collect();
+        inflow.extend((0..6).rev().map(|i| i as f64));
+        inflow.extend([0.0; 20]);
+        inflow
+    }
+
+    #[test]
+    fn route_attenuates_and_delays_peak() {
+        let steps = linear_pond().route(&triangle_inflow(), 60.0).unwrap();
+        let summary = summarize(&steps);
+        assert_eq!(summary.peak_inflow, 6.0);
+        assert!(summary.peak_outflow < 6.0);
+        assert!(summary.peak_index > 6);
+        // 線形貯水池では放流量のピークは流入量の下降部と交わる
+        let at_peak = steps[summary.peak_index];
+        assert!((at_peak.outflow - at_peak.inflow).abs() < 1.0);
+    }
+
+    #[test]
+    fn route_conserves_volume() {
+        let inflow = triangle_inflow();
+        let steps = linear_pond().route(&inflow, 60.0).unwrap();
+        // 台形則による流入・放流量と最終貯留量の収支
+        let trapezoid = |v: Vec<f64>| {
+            v.windows(2)
+                .map(|w| (w[0] + w[1]) / 2.0 * 60.0)
+                .sum::<f64>()
+        };
+        let volume_in = trapezoid(inflow);
+        let volume_out = trapezoid(steps.iter().map(|s| s.outflow).collect());
+        let stored = steps.last().unwrap().storage;
+        assert!((volume_in - volume_out - stored).abs() < 1e-6);
+    }
+
+    #[test]
+    fn route_reports_overtopping() {
+        let inflow = vec![0.0, 50.0, 50.0, 50.0];
+        let err = linear_pond().route(&inflow, 600.0).unwrap_err().to_string();
+        assert!(err.contains("overtops"), "Error: {}", err);
+    }
+
+    #[test]
+    fn size_finds_storage_for_allowable_release() {
+        let inflow = triangle_inflow();
+        let table = linear_pond();
+        let (factor, steps) = size(&table, &inflow, 60.0, 3.0).unwrap();
+        let summary = summarize(&steps);
+        assert!(summary.peak_outflow <= 3.0);
+        assert!(summary.peak_outflow > 2.99, "peak {}", summary.peak_outflow);
+        // 小さい倍率では許容放流量を超える
+        let smaller = table.scaled(factor * 0.9).route(&inflow, 60.0).unwrap();
+        assert!(summarize(&smaller).peak_outflow > 3.0);
+    }
+
+    #[test]
+    fn table_requires_increasing_stage() {
+        let rows = vec![
+            StageRow {
+                stage: 1.0,
+                storage: 0.0,
+                discharge: 0.0,
+            },
+            StageRow {
+                stage: 0.5,
+                storage: 100.0,
+                discharge: 1.0,
+            },
+        ];
+        let err = StageTable::new(rows).unwrap_err().to_string();
+        assert!(err.contains("strictly increasing"), "Error: {}", err);
+    }
+
+    #[test]
+    fn load_table_and_inflow_from_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let table_path = dir.path().join("pond.csv");
+        fs::write(
+            &table_path,
+            "stage_m,storage_m3,discharge_m3_per_s\n0,0,0\n1,1000,2\n2,2500,5\n",
+        )
+        .unwrap();
+        assert_eq!(load_table(&table_path).unwrap().capacity(), 2500.0);
+
+        let inflow_path = dir.path().join("inflow.csv");
+        fs::write(
+            &inflow_path,
+            "time_minutes,rainfall_mm_per_h,discharge_m3_per_s\n10,5,0.5\n20,0,1.5\n30,0,0.2\n",
+        )
+        .unwrap();
+        let (times, inflow) = load_inflow(&inflow_path).unwrap();
+        assert_eq!(times, vec![10.0, 20.0, 30.0]);
+        assert_eq!(inflow, vec![0.5, 1.5, 0.2]);
+
+        fs::write(
+            &inflow_path,
+            "time_minutes,discharge_m3_per_s\n0,0\n10,1\n30,0\n",
+        )
+        .unwrap();
+        let err = load_inflow(&inflow_path).unwrap_err().to_string();
+        assert!(err.contains("evenly spaced"), "Error: {}", err);
+    }
+}
@@ -0,0 +1,238 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::types::FormulaType;
+
+/// 同梱のプリセットライブラリ
+const BUNDLED_LIBRARY: &str = include_str!("../presets/library.toml");
+
+/// 読み込み可能なライブラリ形式の最新バージョン
+pub const LIBRARY_VERSION: u32 = 1;
+
+/// ユーザー定義プリセットファイル名（設定ディレクトリ/hyetograph-cli/ 以下）
+const USER_FILE_NAME: &str = "presets.toml";
+
+/// 名前付きの降雨強度係数セット
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Preset {
+    /// プリセット名
+    pub name: String,
+    /// 説明
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 出典
+    pub source: String,
+    /// 降雨強度式の型
+    pub formula: FormulaType,
+    /// 地域
+    pub region: String,
+    /// 再現期間[年]
+    #[serde(default)]
+    pub return_period: Option<u32>,
+    /// べき乗指数
+    pub a: f64,
+    /// 加算定数
+    pub b: f64,
+    /// 分子定数
+    pub c: f64,
+    /// 君島型の分母指数
+    #[serde(default)]
+    pub m: Option<f64>,
+}
+
+/// プリセットライブラリファイル
+#[derive(Debug, Deserialize)]
+struct Library {
+    version: u32,
+    #[serde(default)]
+    presets: Vec<Preset>,
+}
+
+/// プリセットの出所
+#[derive(Debug, Clone, PartialEq)]
+pub enum Origin {
+    /// バイナリに同梱
+    Bundled,
+    /// ユーザー定義ファイル
+    User(PathBuf),
+}
+
+/// 出所付きのプリセット
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub preset: Preset,
+    pub origin: Origin,
+}
+
+/// 同梱ライブラリとユーザー定義ファイルを合わせたプリセット一覧
+#[derive(Debug)]
+pub struct Catalog {
+    /// 同梱ライブラリのバージョン
+    pub bundled_version: u32,
+    pub entries: Vec<Entry>,
+}
+
+impl Catalog {
+    /// 名前でプリセットを検索する
+    pub fn find(&self, name: &str) -> Result<&Entry> {
+        match self.entries.iter().find(|e| e.preset.name == name) {
+            Some(entry) => Ok(entry),
+            None => {
+                let names: Vec<&str> = self.entries.iter().map(|e| e.preset.name.as_str()).collect();
+                bail!(
+                    "Unknown preset '{}'. Available presets: {}",
+                    name,
+                    names.join(", ")
+                )
+            }
+        }
+    }
+}
+
+/// ユーザー定義プリセットファイルのパス
+pub fn user_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("hyetograph-cli").join(USER_FILE_NAME))
+}
+
+/// 同梱ライブラリと（存在すれば）ユーザー定義ファイルを読み込む
+/// 同名のプリセットはユーザー定義が優先される。
+pub fn load() -> Result<Catalog> {
+    load_with(user_file().as_deref())
+}
+
+/// 同梱ライブラリと指定したユーザー定義ファイルを読み込む（None なら同梱ライブラリのみ）
+pub fn load_with(user_path: Option<&Path>) -> Result<Catalog> {
+    let bundled = parse(BUNDLED_LIBRARY).context("Failed to parse bundled preset library")?;
+    let mut entries: Vec<Entry> = bundled
+        .presets
+        .into_iter()
+        .map(|preset| Entry {
+            preset,
+            origin: Origin::Bundled,
+        })
+        .collect();
+
+    if let Some(path) = user_path.filter(|p| p.exists()) {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read preset file: {}", path.display()))?;
+        let user = parse(&content)
+            .with_context(|| format!("Failed to parse preset file: {}", path.display()))?;
+
+        for preset in user.presets {
+            entries.retain(|e| e.preset.name != preset.name);
+            entries.push(Entry {
+                preset,
+                origin: Origin::User(path.to_path_buf()),
+            });
+        }
+    }
+
+    Ok(Catalog {
+        bundled_version: bundled.version,
+        entries,
+    })
+}
+
+fn parse(content: &str) -> Result<Library> {
+    let library: Library = toml::from_str(content)?;
+    if library.version > LIBRARY_VERSION {
+        bail!(
+            "Preset library version {} is newer than supported version {}",
+            library.version,
+            LIBRARY_VERSION
+        );
+    }
+    for (i, preset) in library.presets.iter().enumerate() {
+        if preset.name.trim().is_empty() {
+            bail!("Preset {} has an empty name", i + 1);
+        }
+        if library.presets[..i].iter().any(|p| p.name == preset.name) {
+            bail!("Duplicate preset name '{}'", preset.name);
+        }
+    }
+    Ok(library)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_library_parses() {
+        let catalog = load_with(None).unwrap();
+        assert_eq!(catalog.bundled_version, LIBRARY_VERSION);
+        assert!(!catalog.entries.is_empty());
+        assert!(catalog.entries.iter().all(|e| e.origin == Origin::Bundled));
+        assert!(catalog.entries.iter().all(|e| !e.preset.source.is_empty()));
+    }
+
+    #[test]
+    fn find_returns_vba_sample() {
+        let catalog = load_with(None).unwrap();
+        let entry = catalog.find("vba-sample").unwrap();
+        assert_eq!(entry.preset.formula, FormulaType::Cleveland);
+        assert_eq!(entry.preset.a, 0.75);
+        assert_eq!(entry.preset.b, 5.411);
+        assert_eq!(entry.preset.c, 1557.825);
+    }
+
+    #[test]
+    fn find_unknown_lists_available() {
+        let catalog = load_with(None).unwrap();
+        let err = catalog.find("nowhere").unwrap_err().to_string();
+        assert!(err.contains("Unknown preset 'nowhere'"), "Error: {}", err);
+        assert!(err.contains("vba-sample"), "Error: {}", err);
+    }
+
+    #[test]
+    fn user_file_extends_and_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("presets.toml");
+        fs::write(
+            &path,
+            r#"
+version = 1
+
+[[presets]]
+name = "vba-sample"
+source = "user override"
+formula = "cleveland"
+region = "-"
+a = 0.7
+b = 5.0
+c = 1500.0
+
+[[presets]]
+name = "my-city-10y"
+source = "市 雨水計画 2020"
+formula = "kimijima"
+region = "My City"
+return_period = 10
+a = 0.7
+b = 4.0
+c = 900.0
+m = 1.1
+"#,
+        )
+        .unwrap();
+
+        let catalog = load_with(Some(&path)).unwrap();
+        let overridden = catalog.find("vba-sample").unwrap();
+        assert_eq!(overridden.preset.source, "user override");
+        assert_eq!(overridden.origin, Origin::User(path.clone()));
+
+        let added = catalog.find("my-city-10y").unwrap();
+        assert_eq!(added.preset.formula, FormulaType::Kimijima);
+        assert_eq!(added.preset.return_period, Some(10));
+        assert_eq!(added.preset.m, Some(1.1));
+    }
+
+    #[test]
+    fn newer_library_version_fails() {
+        let err = parse("version = 99\n").unwrap_err().to_string();
+        assert!(err.contains("newer than supported"), "Error: {}", err);
+    }
+}
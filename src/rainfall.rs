@@ -1,16 +1,18 @@
-use crate::types::RainfallParams;
+use crate::formula::IntensityFormula;
 
 /// RKEISAN相当の降雨強度計算
-/// 降雨強度式 K = C / ((T * I)^A + B) に基づき、各時間ステップの増分雨量を算出する。
+/// 降雨強度式 K(T * I) に基づき、各時間ステップの増分雨量を算出する。
+/// t: 計算時間刻み[分]
+/// tt: 降雨継続時間[時間]
 /// 返却値は降雨強度の大きい順（R[0]が最大、降順）。
-pub fn calculate(params: &RainfallParams) -> Vec<f64> {
-    let nt = (params.tt * 60.0 / params.t) as usize;
+pub fn calculate<F: IntensityFormula + ?Sized>(formula: &F, t: f64, tt: f64) -> Vec<f64> {
+    let nt = (tt * 60.0 / t) as usize;
     let mut increments = Vec::with_capacity(nt);
     let mut zk: f64 = 0.0;
 
     for i in 1..=nt {
-        let ti = params.t * i as f64;
-        let k = params.c / (ti.powf(params.a) + params.b);
+        let ti = t * i as f64;
+        let k = formula.intensity(ti);
         let cumulative = k * i as f64;
         let r = cumulative - zk;
         increments.push(r);
@@ -20,20 +22,44 @@ pub fn calculate(params: &RainfallParams) -> Vec<f64> {
     increments
 }
 
+/// 増分雨量（各ステップの降雨強度[mm/h]）の総雨量[mm]
+pub fn total_depth(increments: &[f64], t: f64) -> f64 {
+    increments.iter().sum::<f64>() * t / 60.0
+}
+
+/// 総雨量が depth[mm] になるよう増分雨量を一律に拡大縮小する
+pub fn scale_to_depth(increments: &mut [f64], depth: f64, t: f64) {
+    let current = total_depth(increments, t);
+    if current > 0.0 {
+        let factor = depth / current;
+        for value in increments.iter_mut() {
+            *value *= factor;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::formula::{Cleveland, Formula, Talbot};
+    use crate::types::RainfallParams;
 
     fn vba_test_params() -> RainfallParams {
         RainfallParams {
-            a: 0.75,
-            b: 5.411,
-            c: 1557.825,
+            formula: Formula::Cleveland(Cleveland {
+                a: 1557.825,
+                b: 5.411,
+                n: 0.75,
+            }),
             t: 10.0,
             tt: 2.0,
         }
     }
 
+    fn run(params: &RainfallParams) -> Vec<f64> {
+        calculate(&params.formula, params.t, params.tt)
+    }
+
     /// VBA期待値（小数3桁に丸めた値）
     const VBA_EXPECTED: [f64; 12] = [
         141.179, 68.369, 46.819, 35.957, 29.354, 24.900, 21.684, 19.249, 17.339, 15.799, 14.530,
@@ -43,14 +69,14 @@ mod tests {
     #[test]
     fn calculate_returns_correct_number_of_steps() {
         let params = vba_test_params();
-        let result = calculate(&params);
+        let result = run(&params);
         assert_eq!(result.len(), 12);
     }
 
     #[test]
     fn calculate_matches_vba_output() {
         let params = vba_test_params();
-        let result = calculate(&params);
+        let result = run(&params);
 
         for (i, (actual, expected)) in result.iter().zip(VBA_EXPECTED.iter()).enumerate() {
             let rounded = (actual * 1000.0).round() / 1000.0;
@@ -68,7 +94,7 @@ mod tests {
     #[test]
     fn calculate_values_are_in_descending_order() {
         let params = vba_test_params();
-        let result = calculate(&params);
+        let result = run(&params);
 
         for i in 1..result.len() {
             assert!(
@@ -85,7 +111,7 @@ mod tests {
     #[test]
     fn calculate_all_values_are_positive() {
         let params = vba_test_params();
-        let result = calculate(&params);
+        let result = run(&params);
 
         for (i, val) in result.iter().enumerate() {
             assert!(*val > 0.0, "R({}) = {} should be positive", i + 1, val);
@@ -95,12 +121,12 @@ mod tests {
     #[test]
     fn calculate_sum_equals_total_cumulative() {
         let params = vba_test_params();
-        let result = calculate(&params);
+        let result = run(&params);
 
         // 増分雨量の合計 == 最終ステップの累計値 K(NT) * NT
         let nt = 12;
         let t_nt = params.t * nt as f64;
-        let k_nt = params.c / (t_nt.powf(params.a) + params.b);
+        let k_nt = params.formula.intensity(t_nt);
         let expected_total = k_nt * nt as f64;
 
         let actual_total: f64 = result.iter().sum();
@@ -111,4 +137,33 @@ mod tests {
             expected_total
         );
     }
+
+    #[test]
+    fn calculate_accepts_any_formula() {
+        let talbot = Talbot { a: 5000.0, b: 40.0 };
+        let result = calculate(&talbot, 10.0, 1.0);
+        assert_eq!(result.len(), 6);
+
+        // 合計 == K(60) * 6
+        let actual_total: f64 = result.iter().sum();
+        assert!((actual_total - 50.0 * 6.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn total_depth_converts_intensity_to_depth() {
+        // 10分刻みで 60 mm/h が6ステップ -> 60 mm
+        assert!((total_depth(&[60.0; 6], 10.0) - 60.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn scale_to_depth_keeps_ratios() {
+        let params = vba_test_params();
+        let mut result = run(&params);
+        let ratio = result[1] / result[0];
+
+        scale_to_depth(&mut result, 200.0, params.t);
+
+        assert!((total_depth(&result, params.t) - 200.0).abs() < 1e-9);
+        assert!((result[1] / result[0] - ratio).abs() < 1e-12);
+    }
 }
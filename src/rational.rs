@@ -0,0 +1,147 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::formula::IntensityFormula;
+use crate::types::LandUse;
+
+/// 合理式の単位換算係数（降雨強度[mm/h] × 面積[km²] → 流量[m³/s]）
+const UNIT_CONVERSION: f64 = 3.6;
+
+/// Kirpich式による洪水到達時間[分]
+/// tc = 0.0195 L^0.77 S^-0.385（L: 流路長[m], S: 流路勾配）
+pub fn kirpich(length: f64, slope: f64) -> f64 {
+    0.0195 * length.powf(0.77) * slope.powf(-0.385)
+}
+
+/// Kraven式による洪水到達時間[分]
+/// 流路勾配 1/100 以上で 3.5 m/s、1/100〜1/200 で 3.0 m/s、1/200 未満で 2.1 m/s の流速とする。
+pub fn kraven(length: f64, slope: f64) -> f64 {
+    let velocity = if slope >= 1.0 / 100.0 {
+        3.5
+    } else if slope >= 1.0 / 200.0 {
+        3.0
+    } else {
+        2.1
+    };
+    length / velocity / 60.0
+}
+
+/// 土地利用別の流出係数の面積加重平均
+pub fn weighted_coefficient(land_uses: &[LandUse]) -> f64 {
+    let area: f64 = land_uses.iter().map(|l| l.area).sum();
+    land_uses.iter().map(|l| l.coefficient * l.area).sum::<f64>() / area
+}
+
+/// 合理式によるピーク流量[m³/s]
+/// Q = C i A / 3.6（i: 降雨強度[mm/h], A: 流域面積[km²]）
+pub fn peak_discharge(coefficient: f64, intensity: f64, area: f64) -> f64 {
+    coefficient * intensity * area / UNIT_CONVERSION
+}
+
+/// 継続時間別のピーク流量表の1行
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RationalRow {
+    pub duration_minutes: f64,
+    pub intensity_mm_per_h: f64,
+    pub discharge_m3_per_s: f64,
+}
+
+/// 継続時間 durations[分] ごとの降雨強度とピーク流量
+pub fn table<F: IntensityFormula + ?Sized>(
+    formula: &F,
+    coefficient: f64,
+    area: f64,
+    durations: &[f64],
+) -> Vec<RationalRow> {
+    durations
+        .iter()
+        .map(|&duration| {
+            let intensity = formula.intensity(duration);
+            RationalRow {
+                duration_minutes: duration,
+                intensity_mm_per_h: intensity,
+                discharge_m3_per_s: peak_discharge(coefficient, intensity, area),
+            }
+        })
+        .collect()
+}
+
+/// ピーク流量表をCSVファイルに出力する
+pub fn write_table(path: &Path, rows: &[RationalRow]) -> Result<()> {
+    let mut wtr = csv::Writer::from_path(path)
+        .with_context(|| format!("Failed to create CSV file: {}", path.display()))?;
+
+    for row in rows {
+        wtr.serialize(row)
+            .with_context(|| format!("Failed to write CSV record to {}", path.display()))?;
+    }
+
+    wtr.flush()
+        .with_context(|| format!("Failed to flush CSV file: {}", path.display()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formula::Sherman;
+
+    #[test]
+    fn kirpich_matches_reference_value() {
+        // L = 1000 m, S = 0.01: 0.0195 * 1000^0.77 * 0.01^-0.385 ≈ 23.4 分
+        let tc = kirpich(1000.0, 0.01);
+        assert!((tc - 23.44).abs() < 0.01, "tc = {}", tc);
+    }
+
+    #[test]
+    fn kraven_velocity_depends_on_slope() {
+        assert!((kraven(2100.0, 0.02) - 10.0).abs() < 1e-12);
+        assert!((kraven(1800.0, 0.008) - 10.0).abs() < 1e-12);
+        assert!((kraven(1260.0, 0.001) - 10.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn weighted_coefficient_uses_area_weights() {
+        let land_uses = [
+            LandUse {
+                coefficient: 0.9,
+                area: 1.0,
+            },
+            LandUse {
+                coefficient: 0.3,
+                area: 3.0,
+            },
+        ];
+        assert!((weighted_coefficient(&land_uses) - 0.45).abs() < 1e-12);
+    }
+
+    #[test]
+    fn peak_discharge_uses_unit_conversion() {
+        // C = 0.8, i = 90 mm/h, A = 2 km² → 40 m³/s
+        assert!((peak_discharge(0.8, 90.0, 2.0) - 40.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn table_decreases_with_duration() {
+        let formula = Sherman { a: 400.0, n: 0.5 };
+        let rows = table(&formula, 0.5, 3.6, &[16.0, 64.0]);
+        assert_eq!(rows[0].intensity_mm_per_h, 100.0);
+        assert!((rows[0].discharge_m3_per_s - 50.0).abs() < 1e-12);
+        assert!((rows[1].discharge_m3_per_s - 25.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn write_table_creates_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rational.csv");
+        let formula = Sherman { a: 400.0, n: 0.5 };
+
+        write_table(&path, &table(&formula, 0.5, 3.6, &[16.0])).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("duration_minutes,intensity_mm_per_h,discharge_m3_per_s"));
+    }
+}
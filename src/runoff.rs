@@ -0,0 +1,196 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::kinematic_wave::OverlandPlane;
+use crate::storage_function::StorageFunction;
+use crate::tank_model::TankModel;
+use crate::types::HydrographEntry;
+use crate::unit_hydrograph::{Shape, UnitHydrograph};
+
+/// 単位換算係数（流出高[mm/h] × 面積[km²] → 流量[m³/s]）
+pub const UNIT_CONVERSION: f64 = 3.6;
+
+/// 流出計算期間の既定値（降雨継続時間に対する倍率）
+pub const DEFAULT_DURATION_FACTOR: f64 = 3.0;
+
+/// 流出モデルの共通インターフェース
+pub trait RunoffModel {
+    /// 時系列順の降雨強度 rainfall[mm/h] に対する各ステップ終了時の流量[m³/s]
+    /// t: 計算時間刻み[分]、steps: 計算ステップ数（降雨終了後の減衰部を含む）
+    fn discharge(&self, rainfall: &[f64], t: f64, steps: usize) -> Vec<f64>;
+}
+
+/// 選択された流出モデル
+#[derive(Debug, Clone, PartialEq)]
+pub enum Runoff {
+    StorageFunction(StorageFunction),
+    UnitHydrograph(UnitHydrograph),
+    Tank(TankModel),
+    KinematicWave(OverlandPlane),
+}
+
+impl RunoffModel for Runoff {
+    fn discharge(&self, rainfall: &[f64], t: f64, steps: usize) -> Vec<f64> {
+        match self {
+            Runoff::StorageFunction(m) => m.discharge(rainfall, t, steps),
+            Runoff::UnitHydrograph(m) => m.discharge(rainfall, t, steps),
+            Runoff::Tank(m) => m.discharge(rainfall, t, steps),
+            Runoff::KinematicWave(m) => m.discharge(rainfall, t, steps),
+        }
+    }
+}
+
+impl fmt::Display for Runoff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Runoff::StorageFunction(m) => {
+                write!(
+                    f,
+                    "storage function: K = {}, P = {}, Tl = {} h, f1 = {}",
+                    m.k, m.p, m.lag, m.f1
+                )?;
+                if let Some(rsa) = m.saturation {
+                    write!(f, ", Rsa = {} mm", rsa)?;
+                }
+                write!(f, ", A = {} km²", m.area)
+            }
+            Runoff::UnitHydrograph(m) => {
+                match &m.shape {
+                    Shape::Scs { lag } => write!(f, "SCS unit hydrograph: lag = {} h", lag)?,
+                    Shape::Snyder {
+                        ct,
+                        cp,
+                        length,
+                        centroid_length,
+                    } => write!(
+                        f,
+                        "Snyder unit hydrograph: Ct = {}, Cp = {}, L = {} km, Lc = {} km",
+                        ct, cp, length, centroid_length
+                    )?,
+                    Shape::Clark { tc, storage } => {
+                        write!(f, "Clark unit hydrograph: tc = {} h, R = {} h", tc, storage)?
+                    }
+                    Shape::Table(points) => {
+                        write!(f, "user unit hydrograph: {} ordinates", points.len())?
+                    }
+                }
+                write!(f, ", A = {} km²", m.area)
+            }
+            Runoff::Tank(m) => write!(
+                f,
+                "tank model: {} tanks, A = {} km²",
+                m.tanks.len(),
+                m.area
+            ),
+            Runoff::KinematicWave(m) => write!(
+                f,
+                "kinematic wave plane: S = {}, L = {} m, n = {}, W = {} m",
+                m.slope, m.length, m.roughness, m.width
+            ),
+        }
+    }
+}
+
+/// 降雨強度 rainfall[mm/h] の時系列から steps ステップ分のハイドログラフを計算する
+pub fn simulate<M: RunoffModel + ?Sized>(
+    model: &M,
+    rainfall: &[f64],
+    t: f64,
+    steps: usize,
+) -> Vec<HydrographEntry> {
+    model
+        .discharge(rainfall, t, steps)
+        .into_iter()
+        .enumerate()
+        .map(|(i, discharge)| HydrographEntry {
+            time_minutes: t * (i + 1) as f64,
+            rainfall: rainfall.get(i).copied().unwrap_or(0.0),
+            discharge,
+        })
+        .collect()
+}
+
+/// ピーク流量のエントリ
+pub fn peak(hydrograph: &[HydrographEntry]) -> Option<&HydrographEntry> {
+    hydrograph
+        .iter()
+        .max_by(|a, b| a.discharge.total_cmp(&b.discharge))
+}
+
+/// ハイドログラフの総流出量[m³]
+pub fn volume(hydrograph: &[HydrographEntry], t: f64) -> f64 {
+    hydrograph.iter().map(|e| e.discharge).sum::<f64>() * t * 60.0
+}
+
+/// 流出計算結果のCSVの出力先（出力ファイル名に _suffix を付けた .csv）
+pub fn csv_path(output_path: &Path, suffix: &str) -> PathBuf {
+    let stem = output_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    output_path.with_file_name(format!("{}_{}.csv", stem, suffix))
+}
+
+/// ハイドログラフをCSVファイルに出力する
+pub fn write(hydrograph: &[HydrographEntry], path: &Path) -> Result<()> {
+    let mut wtr = csv::Writer::from_path(path)
+        .with_context(|| format!("Failed to create CSV file: {}", path.display()))?;
+
+    for entry in hydrograph {
+        wtr.serialize(entry)
+            .with_context(|| format!("Failed to write CSV record to {}", path.display()))?;
+    }
+
+    wtr.flush()
+        .with_context(|| format!("Failed to flush CSV file: {}", path.display()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 降雨をそのまま1ステップ遅れで流出させるモデル
+    struct Delay;
+
+    impl RunoffModel for Delay {
+        fn discharge(&self, rainfall: &[f64], _t: f64, steps: usize) -> Vec<f64> {
+            (0..steps)
+                .map(|i| if i == 0 { 0.0 } else { rainfall.get(i - 1).copied().unwrap_or(0.0) })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn simulate_extends_beyond_rainfall() {
+        let hydrograph = simulate(&Delay, &[10.0, 20.0], 10.0, 4);
+        assert_eq!(hydrograph.len(), 4);
+        assert_eq!(hydrograph[3].time_minutes, 40.0);
+        assert_eq!(hydrograph[3].rainfall, 0.0);
+        assert_eq!(hydrograph[2].discharge, 20.0);
+        assert_eq!(peak(&hydrograph).unwrap().time_minutes, 30.0);
+        assert_eq!(volume(&hydrograph, 10.0), 30.0 * 600.0);
+    }
+
+    #[test]
+    fn csv_path_appends_runoff_suffix() {
+        assert_eq!(
+            csv_path(Path::new("out/storm.png"), "runoff"),
+            PathBuf::from("out/storm_runoff.csv")
+        );
+    }
+
+    #[test]
+    fn write_creates_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("runoff.csv");
+
+        write(&simulate(&Delay, &[10.0], 10.0, 2), &path).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("time_minutes,rainfall_mm_per_h,discharge_m3_per_s"));
+    }
+}
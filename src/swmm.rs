@@ -0,0 +1,359 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDateTime};
+
+use crate::types::{HyetographEntry, SwmmRainFormat};
+
+/// SWMM 入力ファイルの時系列セクション名
+const TIMESERIES_SECTION: &str = "[TIMESERIES]";
+
+/// SWMM 入力ファイルの雨量計セクション名
+const RAINGAGES_SECTION: &str = "[RAINGAGES]";
+
+/// SWMM 出力の設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwmmExport {
+    /// 時系列名
+    pub series: String,
+    /// 雨量計名
+    pub gage: String,
+    /// 雨量の形式
+    pub format: SwmmRainFormat,
+    /// 降雨開始日時（None のときは開始からの相対時刻で出力する）
+    pub start: Option<NaiveDateTime>,
+    /// 書き換える既存の .inp ファイル（None のときはセクションを新しいファイルに出力する）
+    pub inp: Option<PathBuf>,
+}
+
+impl SwmmExport {
+    /// [RAINGAGES] の行（雨量単位は mm、SCF = 1.0）
+    pub fn raingage_line(&self, t: f64) -> String {
+        format!(
+            "{:<16} {:<10} {:<8} {:<8} TIMESERIES {}",
+            self.gage,
+            keyword(self.format),
+            clock(t),
+            "1.0",
+            self.series
+        )
+    }
+
+    /// [TIMESERIES] の行
+    /// 各値の時刻は計算時間刻みの開始時刻とし、INTENSITY・VOLUME では降雨終了時刻に 0 を加える。
+    pub fn timeseries_lines(&self, data: &[HyetographEntry], t: f64) -> Vec<String> {
+        let mut cumulative = 0.0;
+        let mut points: Vec<(f64, f64)> = data
+            .iter()
+            .map(|entry| {
+                let depth = entry.intensity * t / 60.0;
+                cumulative += depth;
+                let value = match self.format {
+                    SwmmRainFormat::Intensity => entry.intensity,
+                    SwmmRainFormat::Volume => depth,
+                    SwmmRainFormat::Cumulative => cumulative,
+                };
+                (entry.time_minutes - t, value)
+            })
+            .collect();
+        if self.format != SwmmRainFormat::Cumulative
+            && let Some(last) = data.last()
+        {
+            points.push((last.time_minutes, 0.0));
+        }
+
+        points
+            .into_iter()
+            .map(|(minutes, value)| match self.start {
+                Some(start) => {
+                    let time = start + Duration::milliseconds((minutes * 60_000.0).round() as i64);
+                    format!(
+                        "{:<16} {} {:<10} {:.4}",
+                        self.series,
+                        time.format("%m/%d/%Y"),
+                        time.format(time_format(t)).to_string(),
+                        value
+                    )
+                }
+                None => format!("{:<16} {:<10} {:.4}", self.series, clock(minutes), value),
+            })
+            .collect()
+    }
+
+    /// [RAINGAGES] と [TIMESERIES] のセクション
+    pub fn render(&self, data: &[HyetographEntry], t: f64) -> String {
+        let mut lines = vec![
+            RAINGAGES_SECTION.to_string(),
+            ";;Name           Format     Interval SCF      Source".to_string(),
+            self.raingage_line(t),
+            String::new(),
+            TIMESERIES_SECTION.to_string(),
+            if self.start.is_some() {
+                ";;Name           Date       Time       Value"
+            } else {
+                ";;Name           Time       Value"
+            }
+            .to_string(),
+        ];
+        lines.extend(self.timeseries_lines(data, t));
+        lines.push(String::new());
+        lines.join("\n")
+    }
+
+    /// 既存の .inp の内容から同名の時系列と雨量計を置き換える
+    /// 該当するセクションがない場合はファイル末尾に追加する。
+    pub fn patch(&self, content: &str, data: &[HyetographEntry], t: f64) -> String {
+        let newline = if content.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let series = self.timeseries_lines(data, t);
+        let gage = self.raingage_line(t);
+
+        let mut out: Vec<String> = Vec::new();
+        let mut section = String::new();
+        let mut seen_timeseries = false;
+        let mut seen_raingages = false;
+        let mut gage_replaced = false;
+
+        let finish = |out: &mut Vec<String>, section: &str, gage_replaced: bool| {
+            let at = insertion_point(out);
+            if section == TIMESERIES_SECTION {
+                out.splice(at..at, series.iter().cloned());
+            } else if section == RAINGAGES_SECTION && !gage_replaced {
+                out.insert(at, gage.clone());
+            }
+        };
+
+        for line in content.lines() {
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') {
+                finish(&mut out, &section, gage_replaced);
+                section = trimmed.to_ascii_uppercase();
+                seen_timeseries |= section == TIMESERIES_SECTION;
+                seen_raingages |= section == RAINGAGES_SECTION;
+                out.push(line.to_string());
+                continue;
+            }
+            let name = (!trimmed.starts_with(';'))
+                .then(|| trimmed.split_whitespace().next())
+                .flatten();
+            if section == TIMESERIES_SECTION && name == Some(self.series.as_str()) {
+                continue;
+            }
+            if section == RAINGAGES_SECTION && name == Some(self.gage.as_str()) {
+                if !gage_replaced {
+                    out.push(gage.clone());
+                    gage_replaced = true;
+                }
+                continue;
+            }
+            out.push(line.to_string());
+        }
+        finish(&mut out, &section, gage_replaced);
+
+        if !seen_raingages || !seen_timeseries {
+            if out.last().is_some_and(|l| !l.trim().is_empty()) {
+                out.push(String::new());
+            }
+            if !seen_raingages {
+                out.extend([RAINGAGES_SECTION.to_string(), gage.clone(), String::new()]);
+            }
+            if !seen_timeseries {
+                out.push(TIMESERIES_SECTION.to_string());
+                out.extend(series.iter().cloned());
+                out.push(String::new());
+            }
+        }
+
+        let mut patched = out.join(newline);
+        if content.ends_with('\n') && !patched.ends_with('\n') {
+            patched.push_str(newline);
+        }
+        patched
+    }
+
+    /// セクションを出力するか、--swmm-inp の既存ファイルを書き換える
+    /// 返却値は書き込んだファイルのパス。
+    pub fn write(&self, data: &[HyetographEntry], t: f64, output_path: &Path) -> Result<PathBuf> {
+        match &self.inp {
+            Some(path) => {
+                let content = fs::read_to_string(path).with_context(|| {
+                    format!("Failed to read SWMM input file: {}", path.display())
+                })?;
+                fs::write(path, self.patch(&content, data, t)).with_context(|| {
+                    format!("Failed to write SWMM input file: {}", path.display())
+                })?;
+                Ok(path.clone())
+            }
+            None => {
+                let path = output_path.with_extension("inp");
+                fs::write(&path, self.render(data, t))
+                    .with_context(|| format!("Failed to create SWMM file: {}", path.display()))?;
+                Ok(path)
+            }
+        }
+    }
+}
+
+/// SWMM の雨量形式のキーワード
+fn keyword(format: SwmmRainFormat) -> &'static str {
+    match format {
+        SwmmRainFormat::Intensity => "INTENSITY",
+        SwmmRainFormat::Volume => "VOLUME",
+        SwmmRainFormat::Cumulative => "CUMULATIVE",
+    }
+}
+
+/// 分を H:MM 形式（整数分でない場合は H:MM:SS 形式）にする
+fn clock(minutes: f64) -> String {
+    let seconds = (minutes * 60.0).round() as i64;
+    if seconds % 60 == 0 {
+        format!("{}:{:02}", seconds / 3600, seconds % 3600 / 60)
+    } else {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds % 3600 / 60,
+            seconds % 60
+        )
+    }
+}
+
+/// 日時付きの時刻の書式（計算時間刻みが整数分でない場合は秒まで）
+fn time_format(t: f64) -> &'static str {
+    if (t * 60.0).round() as i64 % 60 == 0 {
+        "%H:%M"
+    } else {
+        "%H:%M:%S"
+    }
+}
+
+/// セクション末尾の空行の手前の位置
+fn insertion_point(lines: &[String]) -> usize {
+    lines
+        .iter()
+        .rposition(|l| !l.trim().is_empty())
+        .map_or(lines.len(), |i| i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn sample_data() -> Vec<HyetographEntry> {
+        [12.0, 60.0, 24.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &intensity)| HyetographEntry {
+                time_minutes: 10.0 * (i + 1) as f64,
+                intensity,
+                areal_reduction_factor: None,
+                loss: None,
+                effective: None,
+            })
+            .collect()
+    }
+
+    fn export(format: SwmmRainFormat, start: Option<NaiveDateTime>) -> SwmmExport {
+        SwmmExport {
+            series: "design".to_string(),
+            gage: "RG1".to_string(),
+            format,
+            start,
+            inp: None,
+        }
+    }
+
+    fn values(lines: &[String]) -> Vec<f64> {
+        lines
+            .iter()
+            .map(|l| l.split_whitespace().last().unwrap().parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn intensity_series_uses_relative_start_times() {
+        let lines = export(SwmmRainFormat::Intensity, None).timeseries_lines(&sample_data(), 10.0);
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("design"));
+        assert_eq!(lines[0].split_whitespace().nth(1), Some("0:00"));
+        assert_eq!(lines[3].split_whitespace().nth(1), Some("0:30"));
+        assert_eq!(values(&lines), vec![12.0, 60.0, 24.0, 0.0]);
+    }
+
+    #[test]
+    fn volume_and_cumulative_series() {
+        let data = sample_data();
+        let volume = export(SwmmRainFormat::Volume, None).timeseries_lines(&data, 10.0);
+        assert_eq!(values(&volume), vec![2.0, 10.0, 4.0, 0.0]);
+        let cumulative = export(SwmmRainFormat::Cumulative, None).timeseries_lines(&data, 10.0);
+        assert_eq!(values(&cumulative), vec![2.0, 12.0, 16.0]);
+    }
+
+    #[test]
+    fn dated_series_and_raingage() {
+        let start = NaiveDate::from_ymd_opt(2024, 12, 31)
+            .unwrap()
+            .and_hms_opt(23, 40, 0)
+            .unwrap();
+        let swmm = export(SwmmRainFormat::Intensity, Some(start));
+        let lines = swmm.timeseries_lines(&sample_data(), 10.0);
+        let fields: Vec<&str> = lines[2].split_whitespace().collect();
+        assert_eq!(fields[..3], ["design", "01/01/2025", "00:00"]);
+
+        let gage: Vec<String> = swmm
+            .raingage_line(10.0)
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            gage,
+            ["RG1", "INTENSITY", "0:10", "1.0", "TIMESERIES", "design"]
+        );
+        assert_eq!(clock(90.5), "1:30:30");
+    }
+
+    #[test]
+    fn patch_replaces_named_series_and_gage() {
+        let content = "[TITLE]\nexample\n\n[RAINGAGES]\n;;Name Format\nRG1 VOLUME 1:00 1.0 TIMESERIES old\nRG2 INTENSITY 0:05 1.0 TIMESERIES other\n\n\
+                       [TIMESERIES]\n;;Name Time Value\nother 0:00 1.0\ndesign 0:00 99\ndesign 1:00 99\n\n[REPORT]\nINPUT NO\n";
+        let swmm = export(SwmmRainFormat::Intensity, None);
+        let patched = swmm.patch(content, &sample_data(), 10.0);
+
+        assert!(!patched.contains("99"), "{}", patched);
+        assert!(!patched.contains("old"), "{}", patched);
+        assert!(patched.contains("RG2 INTENSITY 0:05 1.0 TIMESERIES other"));
+        assert!(patched.contains("other 0:00 1.0"));
+        assert_eq!(patched.matches("design").count(), 4 + 1);
+        // 新しい時系列は [TIMESERIES] セクション内、[REPORT] の前に入る
+        let series = patched.find("design           0:00").unwrap();
+        assert!(series > patched.find(TIMESERIES_SECTION).unwrap());
+        assert!(series < patched.find("[REPORT]").unwrap());
+        assert!(patched.ends_with("INPUT NO\n"));
+    }
+
+    #[test]
+    fn patch_appends_missing_sections() {
+        let swmm = export(SwmmRainFormat::Intensity, None);
+        let patched = swmm.patch("[TITLE]\nexample\n", &sample_data(), 10.0);
+        assert!(patched.contains("[RAINGAGES]\nRG1"));
+        assert!(patched.contains("[TIMESERIES]\ndesign"));
+    }
+
+    #[test]
+    fn write_creates_inp_sections() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("storm.png");
+        let path = export(SwmmRainFormat::Volume, None)
+            .write(&sample_data(), 10.0, &output)
+            .unwrap();
+        assert_eq!(path, dir.path().join("storm.inp"));
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("[RAINGAGES]\n"));
+        assert!(content.contains("\n[TIMESERIES]\n"));
+    }
+}
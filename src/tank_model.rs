@@ -0,0 +1,294 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::runoff::{RunoffModel, UNIT_CONVERSION};
+
+/// タンクの側方流出孔
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct SideOutlet {
+    /// 流出孔の高さ[mm]
+    pub height: f64,
+    /// 流出係数[1/h]
+    pub coefficient: f64,
+}
+
+/// 直列タンクの1段
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Tank {
+    /// 初期貯留高[mm]
+    #[serde(default)]
+    pub initial_storage: f64,
+    /// 側方流出孔（河道への流出）
+    #[serde(default)]
+    pub outlets: Vec<SideOutlet>,
+    /// 底面流出孔の浸透係数[1/h]（下段タンクへの浸透。最下段では系外への損失）
+    #[serde(default)]
+    pub bottom: f64,
+}
+
+/// 菅原のタンクモデル（直列タンク）
+/// 各ステップで流入を加えた貯留高 S から、側方流出 Σ a (S - h)+ Δt と底面浸透 b S Δt を
+/// 求める（合計が S を超える場合は比例配分で S に制限する）。底面浸透は次段の流入とし、
+/// 全タンクの側方流出の合計を流出高とする。
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TankModel {
+    /// 上段から順のタンク
+    pub tanks: Vec<Tank>,
+    /// 流域面積[km²]（パラメータファイルでは省略し、CLIで指定する）
+    #[serde(skip)]
+    pub area: f64,
+}
+
+/// 1ステップ終了時の各タンクの状態
+#[derive(Debug, Clone, PartialEq)]
+pub struct TankState {
+    /// 貯留高[mm]
+    pub storage: f64,
+    /// 側方流出強度[mm/h]
+    pub side: f64,
+    /// 底面浸透強度[mm/h]
+    pub bottom: f64,
+}
+
+impl TankModel {
+    fn check(self) -> Result<Self> {
+        if self.tanks.is_empty() {
+            bail!("Tank model has no tanks");
+        }
+        for (i, tank) in self.tanks.iter().enumerate() {
+            let n = i + 1;
+            if tank.initial_storage < 0.0 {
+                bail!(
+                    "Tank {}: initial storage must be non-negative, got {}",
+                    n,
+                    tank.initial_storage
+                );
+            }
+            if tank.bottom < 0.0 {
+                bail!(
+                    "Tank {}: bottom coefficient must be non-negative, got {}",
+                    n,
+                    tank.bottom
+                );
+            }
+            for outlet in &tank.outlets {
+                if outlet.height < 0.0 || outlet.coefficient < 0.0 {
+                    bail!(
+                        "Tank {}: outlet height and coefficient must be non-negative, got ({}, {})",
+                        n,
+                        outlet.height,
+                        outlet.coefficient
+                    );
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// 降雨強度 rainfall[mm/h] の時系列に対する各ステップのタンクの状態
+    pub fn simulate(&self, rainfall: &[f64], t: f64, steps: usize) -> Vec<Vec<TankState>> {
+        let hours = t / 60.0;
+        let mut storages: Vec<f64> = self.tanks.iter().map(|tank| tank.initial_storage).collect();
+        (0..steps)
+            .map(|i| {
+                let mut inflow = rainfall.get(i).copied().unwrap_or(0.0) * hours;
+                self.tanks
+                    .iter()
+                    .zip(storages.iter_mut())
+                    .map(|(tank, storage)| {
+                        *storage += inflow;
+                        let mut side: f64 = tank
+                            .outlets
+                            .iter()
+                            .map(|o| o.coefficient * (*storage - o.height).max(0.0) * hours)
+                            .sum();
+                        let mut bottom = tank.bottom * *storage * hours;
+                        let total = side + bottom;
+                        if total > *storage {
+                            let scale = *storage / total;
+                            side *= scale;
+                            bottom *= scale;
+                        }
+                        *storage -= side + bottom;
+                        inflow = bottom;
+                        TankState {
+                            storage: *storage,
+                            side: side / hours,
+                            bottom: bottom / hours,
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl RunoffModel for TankModel {
+    fn discharge(&self, rainfall: &[f64], t: f64, steps: usize) -> Vec<f64> {
+        self.simulate(rainfall, t, steps)
+            .iter()
+            .map(|states| {
+                states.iter().map(|s| s.side).sum::<f64>() * self.area / UNIT_CONVERSION
+            })
+            .collect()
+    }
+}
+
+/// タンクモデルのパラメータをTOMLファイルから読み込む
+///
+/// ```toml
+/// [[tanks]]
+/// initial_storage = 5.0
+/// bottom = 0.12
+/// outlets = [{ height = 15.0, coefficient = 0.1 }, { height = 40.0, coefficient = 0.2 }]
+///
+/// [[tanks]]
+/// bottom = 0.05
+/// outlets = [{ height = 0.0, coefficient = 0.05 }]
+/// ```
+pub fn load(path: &Path, area: f64) -> Result<TankModel> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to open tank model: {}", path.display()))?;
+    let model: TankModel = toml::from_str(&content)
+        .with_context(|| format!("Failed to parse tank model: {}", path.display()))?;
+    let model = model
+        .check()
+        .with_context(|| format!("Invalid tank model: {}", path.display()))?;
+    Ok(TankModel { area, ..model })
+}
+
+/// 各ステップのタンクの状態をCSVファイルに出力する
+/// 列: time_minutes, tank{n}_storage_mm, tank{n}_side_mm_per_h, tank{n}_bottom_mm_per_h, ...
+pub fn write_states(states: &[Vec<TankState>], t: f64, path: &Path) -> Result<()> {
+    let mut wtr = csv::Writer::from_path(path)
+        .with_context(|| format!("Failed to create CSV file: {}", path.display()))?;
+
+    let tanks = states.first().map_or(0, |s| s.len());
+    let mut header = vec!["time_minutes".to_string()];
+    for n in 1..=tanks {
+        header.push(format!("tank{}_storage_mm", n));
+        header.push(format!("tank{}_side_mm_per_h", n));
+        header.push(format!("tank{}_bottom_mm_per_h", n));
+    }
+    wtr.write_record(&header)
+        .with_context(|| format!("Failed to write CSV header to {}", path.display()))?;
+
+    for (i, step) in states.iter().enumerate() {
+        let mut record = vec![(t * (i + 1) as f64).to_string()];
+        for state in step {
+            record.push(state.storage.to_string());
+            record.push(state.side.to_string());
+            record.push(state.bottom.to_string());
+        }
+        wtr.write_record(&record)
+            .with_context(|| format!("Failed to write CSV record to {}", path.display()))?;
+    }
+
+    wtr.flush()
+        .with_context(|| format!("Failed to flush CSV file: {}", path.display()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_tanks() -> TankModel {
+        TankModel {
+            tanks: vec![
+                Tank {
+                    initial_storage: 0.0,
+                    outlets: vec![SideOutlet {
+                        height: 10.0,
+                        coefficient: 0.2,
+                    }],
+                    bottom: 0.1,
+                },
+                Tank {
+                    initial_storage: 20.0,
+                    outlets: vec![SideOutlet {
+                        height: 0.0,
+                        coefficient: 0.05,
+                    }],
+                    bottom: 0.0,
+                },
+            ],
+            area: 36.0,
+        }
+    }
+
+    #[test]
+    fn first_step_follows_outlet_equations() {
+        let states = two_tanks().simulate(&[30.0], 60.0, 1);
+        // 上段: S = 30、側方 0.2 * (30 - 10) = 4、底面 0.1 * 30 = 3 → 残り 23
+        assert!((states[0][0].side - 4.0).abs() < 1e-12);
+        assert!((states[0][0].bottom - 3.0).abs() < 1e-12);
+        assert!((states[0][0].storage - 23.0).abs() < 1e-12);
+        // 下段: S = 20 + 3、側方 0.05 * 23 → 残り 21.85
+        assert!((states[0][1].side - 1.15).abs() < 1e-12);
+        assert!((states[0][1].storage - 21.85).abs() < 1e-12);
+    }
+
+    #[test]
+    fn water_balance_is_closed() {
+        let model = two_tanks();
+        let rain = [30.0, 10.0, 0.0, 5.0];
+        let states = model.simulate(&rain, 30.0, 40);
+        let side: f64 = states.iter().flatten().map(|s| s.side).sum::<f64>() * 0.5;
+        let final_storage: f64 = states.last().unwrap().iter().map(|s| s.storage).sum();
+        let rain_depth: f64 = rain.iter().sum::<f64>() * 0.5;
+        assert!((20.0 + rain_depth - side - final_storage).abs() < 1e-9);
+    }
+
+    #[test]
+    fn outflow_never_exceeds_storage() {
+        let mut model = two_tanks();
+        model.tanks[0].outlets[0].coefficient = 5.0;
+        model.tanks[0].bottom = 5.0;
+        for step in model.simulate(&[100.0; 3], 60.0, 3) {
+            assert!(step.iter().all(|s| s.storage >= 0.0));
+        }
+    }
+
+    #[test]
+    fn discharge_converts_side_outflow() {
+        let model = two_tanks();
+        let q = model.discharge(&[30.0], 60.0, 1);
+        assert!((q[0] - (4.0 + 1.15) * 36.0 / 3.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn load_toml_and_write_states() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tank.toml");
+        fs::write(
+            &path,
+            "[[tanks]]\nbottom = 0.1\noutlets = [{ height = 10.0, coefficient = 0.2 }]\n\n\
+             [[tanks]]\ninitial_storage = 20.0\noutlets = [{ height = 0.0, coefficient = 0.05 }]\n",
+        )
+        .unwrap();
+        let model = load(&path, 36.0).unwrap();
+        assert_eq!(model, two_tanks());
+
+        let csv_path = dir.path().join("tanks.csv");
+        write_states(&model.simulate(&[30.0], 60.0, 2), 60.0, &csv_path).unwrap();
+        let content = fs::read_to_string(&csv_path).unwrap();
+        assert!(content.starts_with(
+            "time_minutes,tank1_storage_mm,tank1_side_mm_per_h,tank1_bottom_mm_per_h,tank2_storage_mm"
+        ));
+        assert_eq!(content.lines().count(), 3);
+    }
+
+    #[test]
+    fn load_rejects_empty_model() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tank.toml");
+        fs::write(&path, "tanks = []\n").unwrap();
+        let err = format!("{:#}", load(&path, 1.0).unwrap_err());
+        assert!(err.contains("no tanks"), "Error: {}", err);
+    }
+}
@@ -1,90 +1,411 @@
+use std::str::FromStr;
+
 use clap::ValueEnum;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+use crate::formula::Formula;
 
 /// 降雨強度計算のパラメータ
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct RainfallParams {
-    /// べき乗指数
-    pub a: f64,
-    /// 加算定数
-    pub b: f64,
-    /// 分子定数
-    pub c: f64,
+    /// 降雨強度式
+    pub formula: Formula,
     /// 計算時間刻み[分]
+    #[serde(rename = "t_minutes")]
     pub t: f64,
     /// 降雨継続時間[時間]
+    #[serde(rename = "tt_hours")]
     pub tt: f64,
 }
 
 /// ハイエトグラフの1エントリ（経過時間と降雨強度のペア）
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct HyetographEntry {
     /// 経過時間[分]（T * index）
     pub time_minutes: f64,
     /// 降雨強度[mm/h]
     #[serde(rename = "intensity_mm_per_h")]
     pub intensity: f64,
+    /// このステップの増分雨量を求めた継続時間に対する面積低減係数（流域面積の指定時のみ）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub areal_reduction_factor: Option<f64>,
+    /// 損失[mm/h]（損失モデルの指定時のみ）
+    #[serde(rename = "loss_mm_per_h", skip_serializing_if = "Option::is_none")]
+    pub loss: Option<f64>,
+    /// 有効降雨強度[mm/h]（損失モデルの指定時のみ）
+    #[serde(rename = "effective_mm_per_h", skip_serializing_if = "Option::is_none")]
+    pub effective: Option<f64>,
 }
 
-/// 雨量分布パターン
+/// ハイドログラフの1エントリ（流出モデルの計算結果）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HydrographEntry {
+    /// 経過時間[分]（ステップ終了時刻）
+    pub time_minutes: f64,
+    /// 流出モデルに与えた降雨強度[mm/h]（損失モデルの指定時は有効降雨）
+    #[serde(rename = "rainfall_mm_per_h")]
+    pub rainfall: f64,
+    /// 流量[m³/s]
+    #[serde(rename = "discharge_m3_per_s")]
+    pub discharge: f64,
+}
+
+/// 調整池追跡の1エントリ（修正プルス法の計算結果）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RoutedEntry {
+    /// 経過時間[分]
+    pub time_minutes: f64,
+    /// 流入量[m³/s]
+    #[serde(rename = "inflow_m3_per_s")]
+    pub inflow: f64,
+    /// 放流量[m³/s]
+    #[serde(rename = "outflow_m3_per_s")]
+    pub outflow: f64,
+    /// 水位[m]
+    #[serde(rename = "stage_m")]
+    pub stage: f64,
+    /// 貯留量[m³]
+    #[serde(rename = "storage_m3")]
+    pub storage: f64,
+}
+
+/// 降雨強度式の型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FormulaType {
+    /// Talbot型 K = C / (t + B)
+    Talbot,
+    /// Sherman型 K = C / t^A
+    Sherman,
+    /// Cleveland型 K = C / (t^A + B)
+    #[default]
+    Cleveland,
+    /// 君島型 K = C / (t^A + B)^M
+    Kimijima,
+}
+
+/// IDF表の補間方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Interpolation {
+    /// 両対数補間
+    #[default]
+    LogLog,
+    /// 線形補間
+    Linear,
+}
+
+/// 確率降雨量の算定に用いる確率分布
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ProbabilityDistribution {
+    /// Gumbel分布
+    Gumbel,
+    /// 一般化極値分布
+    Gev,
+    /// 対数正規分布
+    LogNormal,
+    /// 対数ピアソンIII型分布
+    LogPearson3,
+}
+
+impl ProbabilityDistribution {
+    /// 全ての確率分布
+    pub const ALL: &'static [Self] = &[
+        Self::Gumbel,
+        Self::Gev,
+        Self::LogNormal,
+        Self::LogPearson3,
+    ];
+}
+
+/// 雨量分布パターン
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
 pub enum DistributionPattern {
     /// 前方集中型（パターン1）
     Front,
     /// 中央集中型（パターン2）
+    #[default]
     Center,
     /// 後方集中型（パターン3）
     Rear,
+    /// NRCS Type I 24時間降雨
+    ScsI,
+    /// NRCS Type IA 24時間降雨
+    ScsIa,
+    /// NRCS Type II 24時間降雨
+    ScsII,
+    /// NRCS Type III 24時間降雨
+    ScsIII,
+    /// Huff 第1四分位降雨
+    HuffQ1,
+    /// Huff 第2四分位降雨
+    HuffQ2,
+    /// Huff 第3四分位降雨
+    HuffQ3,
+    /// Huff 第4四分位降雨
+    HuffQ4,
+    /// ユーザー定義の無次元累加雨量曲線（--mass-curve）
+    Custom,
+    /// 多峰型（--peaks）
+    MultiPeak,
+    /// 三角形型（Yen & Chow、--advancement）
+    Triangular,
+    /// 台形型（--trapezoid）
+    Trapezoidal,
+}
+
+/// 多峰型降雨の1つのピーク
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Peak {
+    /// ピーク位置（降雨継続時間に対する比率 0〜1）
+    pub position: f64,
+    /// 総雨量に対する配分比率
+    pub share: f64,
 }
 
-impl Default for DistributionPattern {
-    fn default() -> Self {
-        Self::Center
+impl FromStr for Peak {
+    type Err = String;
+
+    /// "位置:配分" 形式（例: 0.3:0.4）
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (position, share) = s
+            .split_once(':')
+            .ok_or_else(|| format!("expected POSITION:SHARE, got '{}'", s))?;
+        let parse = |v: &str| {
+            v.trim()
+                .parse::<f64>()
+                .map_err(|e| format!("invalid number '{}': {}", v, e))
+        };
+        Ok(Self {
+            position: parse(position)?,
+            share: parse(share)?,
+        })
     }
 }
 
-/// 出力形式
+/// 合理式の土地利用別の流出係数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LandUse {
+    /// 流出係数
+    pub coefficient: f64,
+    /// 面積（加重平均の重み）
+    pub area: f64,
+}
+
+impl FromStr for LandUse {
+    type Err = String;
+
+    /// "流出係数:面積" 形式（例: 0.9:1.2）
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (coefficient, area) = s
+            .split_once(':')
+            .ok_or_else(|| format!("expected COEFFICIENT:AREA, got '{}'", s))?;
+        let parse = |v: &str| {
+            v.trim()
+                .parse::<f64>()
+                .map_err(|e| format!("invalid number '{}': {}", v, e))
+        };
+        Ok(Self {
+            coefficient: parse(coefficient)?,
+            area: parse(area)?,
+        })
+    }
+}
+
+/// 洪水到達時間の推定式
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TcMethod {
+    /// Kirpich式 tc = 0.0195 L^0.77 S^-0.385
+    Kirpich,
+    /// Kraven式（勾配別の流速で流路長を除す）
+    Kraven,
+}
+
+/// 面積低減係数の算定方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ArfMethod {
+    /// 米国気象局 TP-29 曲線の近似式
+    #[default]
+    Tp29,
+    /// 英国 Flood Studies Report の経験式（面積と継続時間に依存）
+    Fsr,
+    /// ホートン型の経験式 exp(-k A^n)（--arf-k, --arf-n。継続時間に依存しない）
+    Horton,
+    /// ユーザー指定の係数表（--arf-table）
+    Table,
+}
+
+/// 先行降雨条件（AMC）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum AntecedentMoisture {
+    /// AMC I（乾燥）
+    Dry,
+    /// AMC II（標準）
+    #[default]
+    Normal,
+    /// AMC III（湿潤）
+    Wet,
+}
+
+/// 損失モデル
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LossMethod {
+    /// SCS カーブナンバー法（--cn, --ia-ratio, --amc）
+    ScsCn,
+    /// Horton 式（--horton-f0, --horton-fc, --horton-k）
+    Horton,
+    /// Green-Ampt 式（--ksat, --suction, --moisture-deficit）
+    GreenAmpt,
+    /// φインデックス法（--phi）
+    PhiIndex,
+    /// 初期損失・継続損失法（--initial-loss, --continuing-loss）
+    InitialContinuing,
+}
+
+/// 流出モデル
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RunoffMethod {
+    /// 貯留関数法（--sf-k, --sf-p, --sf-tl, --sf-f1, --sf-rsa）
+    StorageFunction,
+    /// 単位図法（--uh-method）
+    UnitHydrograph,
+    /// 菅原のタンクモデル（--tank-model）
+    Tank,
+    /// キネマティックウェーブ法による斜面表面流（--kw-slope, --kw-length, --kw-n, --kw-width）
+    KinematicWave,
+}
+
+/// 単位図の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum UnitHydrographMethod {
+    /// SCS 無次元単位図（--uh-lag または --uh-tc）
+    #[default]
+    Scs,
+    /// Snyder 単位図（--snyder-ct, --snyder-cp, --snyder-length, --snyder-lc）
+    Snyder,
+    /// Clark 単位図（--uh-tc, --clark-r）
+    Clark,
+    /// ユーザー指定の単位図CSV（--uh-file）
+    File,
+}
+
+/// 出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
 pub enum OutputFormat {
     /// PNGのみ
+    #[default]
     Png,
     /// CSVのみ
     Csv,
     /// PNG + CSV
     Both,
+    /// EPA SWMM の [RAINGAGES]・[TIMESERIES] セクション（.inp）
+    Swmm,
+    /// HEC-HMS の時系列ゲージ表と HEC-DSS インポート用テキスト（--start-time が必要）
+    HecHms,
+    /// HEC-RAS の降雨ハイドログラフと HEC-DSS インポート用テキスト（--start-time が必要）
+    HecRas,
+    /// 実行メタデータとハイエトグラフのJSON
+    Json,
+    /// 実行メタデータとハイエトグラフを1行のJSONとして追記（NDJSON）
+    Ndjson,
+}
+
+/// グラフの出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ChartFormat {
+    /// PNG画像（ラスタ）
+    Png,
+    /// SVG（ベクター）
+    Svg,
+    /// PDF（ベクター）
+    Pdf,
 }
 
-impl Default for OutputFormat {
-    fn default() -> Self {
-        Self::Png
+impl ChartFormat {
+    /// 出力ファイルの拡張子から形式を判定する（大文字小文字は区別しない）
+    pub fn from_extension(path: &std::path::Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        Self::value_variants()
+            .iter()
+            .copied()
+            .find(|format| format.extension() == extension)
     }
+
+    /// 出力ファイルの拡張子
+    pub fn extension(self) -> &'static str {
+        match self {
+            ChartFormat::Png => "png",
+            ChartFormat::Svg => "svg",
+            ChartFormat::Pdf => "pdf",
+        }
+    }
+}
+
+/// SWMM 雨量計の雨量形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum SwmmRainFormat {
+    /// 降雨強度[mm/h]
+    #[default]
+    Intensity,
+    /// 計算時間刻みごとの雨量[mm]
+    Volume,
+    /// 累加雨量[mm]
+    Cumulative,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::formula::Cleveland;
 
     #[test]
     fn rainfall_params_holds_values() {
-        let params = RainfallParams {
-            a: 0.75,
+        let cleveland = Cleveland {
+            a: 1557.825,
             b: 5.411,
-            c: 1557.825,
+            n: 0.75,
+        };
+        let params = RainfallParams {
+            formula: Formula::Cleveland(cleveland),
             t: 10.0,
             tt: 2.0,
         };
-        assert_eq!(params.a, 0.75);
-        assert_eq!(params.b, 5.411);
-        assert_eq!(params.c, 1557.825);
+        assert_eq!(params.formula, Formula::Cleveland(cleveland));
         assert_eq!(params.t, 10.0);
         assert_eq!(params.tt, 2.0);
     }
 
+    #[test]
+    fn rainfall_params_serializes_with_stable_names() {
+        let params = RainfallParams {
+            formula: Formula::Cleveland(Cleveland {
+                a: 1557.825,
+                b: 5.411,
+                n: 0.75,
+            }),
+            t: 10.0,
+            tt: 2.0,
+        };
+        let json = serde_json::to_value(&params).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "formula": { "type": "cleveland", "A": 0.75, "B": 5.411, "C": 1557.825 },
+                "t_minutes": 10.0,
+                "tt_hours": 2.0,
+            })
+        );
+    }
+
     #[test]
     fn hyetograph_entry_holds_values() {
         let entry = HyetographEntry {
             time_minutes: 10.0,
             intensity: 141.179,
+            areal_reduction_factor: None,
+            loss: None,
+            effective: None,
         };
         assert_eq!(entry.time_minutes, 10.0);
         assert_eq!(entry.intensity, 141.179);
@@ -95,6 +416,9 @@ mod tests {
         let entry = HyetographEntry {
             time_minutes: 10.0,
             intensity: 141.179,
+            areal_reduction_factor: None,
+            loss: None,
+            effective: None,
         };
         let mut wtr = csv::Writer::from_writer(vec![]);
         wtr.serialize(&entry).unwrap();
@@ -105,15 +429,45 @@ mod tests {
         assert!(data.contains("141.179"));
     }
 
+    #[test]
+    fn formula_type_default_is_cleveland() {
+        assert_eq!(FormulaType::default(), FormulaType::Cleveland);
+    }
+
+    #[test]
+    fn formula_type_has_four_variants() {
+        let variants = FormulaType::value_variants();
+        assert_eq!(variants.len(), 4);
+    }
+
+    #[test]
+    fn interpolation_default_is_log_log() {
+        assert_eq!(Interpolation::default(), Interpolation::LogLog);
+    }
+
     #[test]
     fn distribution_pattern_default_is_center() {
         assert_eq!(DistributionPattern::default(), DistributionPattern::Center);
     }
 
     #[test]
-    fn distribution_pattern_has_three_variants() {
+    fn distribution_pattern_has_fifteen_variants() {
         let variants = DistributionPattern::value_variants();
-        assert_eq!(variants.len(), 3);
+        assert_eq!(variants.len(), 15);
+    }
+
+    #[test]
+    fn mass_curve_patterns_use_kebab_case_names() {
+        let names: Vec<String> = DistributionPattern::value_variants()
+            .iter()
+            .filter_map(|v| v.to_possible_value())
+            .map(|v| v.get_name().to_string())
+            .collect();
+        for name in [
+            "scs-i", "scs-ia", "scs-ii", "scs-iii", "huff-q1", "huff-q2", "huff-q3", "huff-q4",
+        ] {
+            assert!(names.iter().any(|n| n == name), "missing {}", name);
+        }
     }
 
     #[test]
@@ -122,8 +476,50 @@ mod tests {
     }
 
     #[test]
-    fn output_format_has_three_variants() {
+    fn output_format_has_eight_variants() {
         let variants = OutputFormat::value_variants();
-        assert_eq!(variants.len(), 3);
+        assert_eq!(variants.len(), 8);
+    }
+
+    #[test]
+    fn chart_format_from_extension() {
+        use std::path::Path;
+        assert_eq!(
+            ChartFormat::from_extension(Path::new("fig.SVG")),
+            Some(ChartFormat::Svg)
+        );
+        assert_eq!(
+            ChartFormat::from_extension(Path::new("out/fig.pdf")),
+            Some(ChartFormat::Pdf)
+        );
+        assert_eq!(ChartFormat::from_extension(Path::new("fig.csv")), None);
+        assert_eq!(ChartFormat::from_extension(Path::new("fig")), None);
+    }
+
+    #[test]
+    fn peak_parses_position_and_share() {
+        let peak: Peak = "0.3:0.4".parse().unwrap();
+        assert_eq!(
+            peak,
+            Peak {
+                position: 0.3,
+                share: 0.4
+            }
+        );
+        assert!("0.3".parse::<Peak>().is_err());
+        assert!("a:0.4".parse::<Peak>().is_err());
+    }
+
+    #[test]
+    fn land_use_parses_coefficient_and_area() {
+        let land_use: LandUse = "0.9:1.2".parse().unwrap();
+        assert_eq!(
+            land_use,
+            LandUse {
+                coefficient: 0.9,
+                area: 1.2
+            }
+        );
+        assert!("0.9".parse::<LandUse>().is_err());
     }
 }
@@ -0,0 +1,388 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::runoff::{RunoffModel, UNIT_CONVERSION};
+
+/// SCS 無次元単位図 (t/Tp, q/qp)（NEH Part 630 Chapter 16）
+const SCS_DIMENSIONLESS: [(f64, f64); 33] = [
+    (0.0, 0.000),
+    (0.1, 0.030),
+    (0.2, 0.100),
+    (0.3, 0.190),
+    (0.4, 0.310),
+    (0.5, 0.470),
+    (0.6, 0.660),
+    (0.7, 0.820),
+    (0.8, 0.930),
+    (0.9, 0.990),
+    (1.0, 1.000),
+    (1.1, 0.990),
+    (1.2, 0.930),
+    (1.3, 0.860),
+    (1.4, 0.780),
+    (1.5, 0.680),
+    (1.6, 0.560),
+    (1.7, 0.460),
+    (1.8, 0.390),
+    (1.9, 0.330),
+    (2.0, 0.280),
+    (2.2, 0.207),
+    (2.4, 0.147),
+    (2.6, 0.107),
+    (2.8, 0.077),
+    (3.0, 0.055),
+    (3.2, 0.040),
+    (3.4, 0.029),
+    (3.6, 0.021),
+    (3.8, 0.015),
+    (4.0, 0.011),
+    (4.5, 0.005),
+    (5.0, 0.000),
+];
+
+/// Clark 単位図の追跡を打ち切る相対流量（ピークに対する比）
+const CLARK_CUTOFF: f64 = 1e-6;
+
+/// Clark 単位図の最大ステップ数
+const CLARK_MAX_STEPS: usize = 100_000;
+
+/// 単位図の形状
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// SCS 無次元単位図（遅れ時間[h]）
+    Scs { lag: f64 },
+    /// Snyder 単位図（SI単位: 流路長・重心までの流路長[km]）
+    Snyder {
+        ct: f64,
+        cp: f64,
+        length: f64,
+        centroid_length: f64,
+    },
+    /// Clark 単位図（時間-面積曲線 + 線形貯水池、到達時間・貯留係数[h]）
+    Clark { tc: f64, storage: f64 },
+    /// ユーザー指定の単位図 (経過時間[分], 縦距)
+    Table(Vec<(f64, f64)>),
+}
+
+/// 単位図法
+/// 計算時間刻み T の有効降雨 1mm に対する単位図を T 間隔で求め、有効降雨と畳み込む。
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitHydrograph {
+    pub shape: Shape,
+    /// 流域面積[km²]
+    pub area: f64,
+    /// 基底流量[m³/s]
+    pub base_flow: f64,
+}
+
+impl UnitHydrograph {
+    /// 計算時間刻み t[分] の単位図の縦距[m³/s/mm]（時刻 t, 2t, ... の値）
+    /// 離散化による誤差を除くため、体積が 面積 × 1mm となるよう正規化する。
+    pub fn ordinates(&self, t: f64) -> Vec<f64> {
+        let hours = t / 60.0;
+        let raw = match &self.shape {
+            Shape::Scs { lag } => self.scs(*lag, hours),
+            Shape::Snyder {
+                ct,
+                cp,
+                length,
+                centroid_length,
+            } => self.snyder(*ct, *cp, *length, *centroid_length, hours),
+            Shape::Clark { tc, storage } => clark(*tc, *storage, hours),
+            Shape::Table(points) => sample_table(points, t),
+        };
+        let volume: f64 = raw.iter().sum::<f64>() * hours;
+        let target = self.area / UNIT_CONVERSION;
+        if volume > 0.0 {
+            raw.iter().map(|u| u * target / volume).collect()
+        } else {
+            raw
+        }
+    }
+
+    /// SCS 無次元単位図: Tp = D/2 + 遅れ時間、qp = 0.208 A / Tp
+    fn scs(&self, lag: f64, hours: f64) -> Vec<f64> {
+        let tp = hours / 2.0 + lag;
+        let qp = 0.208 * self.area / tp;
+        let (last, _) = SCS_DIMENSIONLESS[SCS_DIMENSIONLESS.len() - 1];
+        let n = (last * tp / hours).ceil() as usize;
+        (1..=n)
+            .map(|k| qp * interpolate(&SCS_DIMENSIONLESS, k as f64 * hours / tp))
+            .collect()
+    }
+
+    /// Snyder 単位図
+    /// tp = 0.75 Ct (L Lc)^0.3、継続時間 tR への補正 tpR = tp + (tR - tp / 5.5) / 4、
+    /// qpR = 0.275 Cp A / tpR [m³/s/mm]、W50 = 2.14 (q/A)^-1.08、W75 = 1.22 (q/A)^-1.08（q: m³/s/cm）。
+    /// 幅の1/3をピーク前、2/3をピーク後に置き、基底時間は体積が 1mm となるよう定める。
+    fn snyder(&self, ct: f64, cp: f64, length: f64, centroid_length: f64, hours: f64) -> Vec<f64> {
+        let tp = 0.75 * ct * (length * centroid_length).powf(0.3);
+        let tpr = tp + (hours - tp / 5.5) / 4.0;
+        let qp = 0.275 * cp * self.area / tpr;
+        let specific = qp * 10.0 / self.area;
+        let w50 = 2.14 * specific.powf(-1.08);
+        let w75 = 1.22 * specific.powf(-1.08);
+        let peak = hours / 2.0 + tpr;
+
+        let mut points = vec![
+            (0.0, 0.0),
+            ((peak - w50 / 3.0).max(0.0), 0.5 * qp),
+            ((peak - w75 / 3.0).max(0.0), 0.75 * qp),
+            (peak, qp),
+            (peak + 2.0 * w75 / 3.0, 0.75 * qp),
+            (peak + 2.0 * w50 / 3.0, 0.5 * qp),
+        ];
+        points.dedup_by(|b, a| b.0 <= a.0);
+        let area: f64 = points
+            .windows(2)
+            .map(|w| (w[1].0 - w[0].0) * (w[0].1 + w[1].1) / 2.0)
+            .sum();
+        let target = self.area / UNIT_CONVERSION;
+        let (last_t, last_q) = points[points.len() - 1];
+        let tail = (2.0 * (target - area) / last_q).max(hours);
+        points.push((last_t + tail, 0.0));
+
+        let n = ((last_t + tail) / hours).ceil() as usize;
+        (1..=n)
+            .map(|k| interpolate(&points, k as f64 * hours))
+            .collect()
+    }
+}
+
+/// Clark 単位図
+/// 時間-面積曲線 A(t)/A = 1.414 (t/tc)^1.5（t ≤ tc/2）、1 - 1.414 (1 - t/tc)^1.5（tc/2 < t ≤ tc）
+/// による流入を線形貯水池 O = C_A I + (1 - C_A) O'（C_A = Δt / (R + Δt/2)）で追跡し、
+/// 隣接する縦距の平均を継続時間 Δt の単位図とする。面積は正規化時に乗じる。
+fn clark(tc: f64, storage: f64, hours: f64) -> Vec<f64> {
+    let time_area = |t: f64| {
+        let x = (t / tc).clamp(0.0, 1.0);
+        if x <= 0.5 {
+            1.414 * x.powf(1.5)
+        } else {
+            1.0 - 1.414 * (1.0 - x).powf(1.5)
+        }
+    };
+    let ca = hours / (storage + 0.5 * hours);
+    let mut ordinates = Vec::new();
+    let mut outflow = 0.0;
+    let mut peak: f64 = 0.0;
+    for i in 1..=CLARK_MAX_STEPS {
+        let t = i as f64 * hours;
+        let inflow = (time_area(t) - time_area(t - hours)) / hours;
+        let next = ca * inflow + (1.0 - ca) * outflow;
+        ordinates.push((outflow + next) / 2.0);
+        outflow = next;
+        peak = peak.max(outflow);
+        if t >= tc && outflow < peak * CLARK_CUTOFF {
+            break;
+        }
+    }
+    ordinates
+}
+
+/// ユーザー指定の単位図を時刻 t, 2t, ... で線形補間する（範囲外は 0）
+fn sample_table(points: &[(f64, f64)], t: f64) -> Vec<f64> {
+    let mut curve = vec![(0.0, 0.0)];
+    curve.extend(points.iter().filter(|p| p.0 > 0.0));
+    let last = curve[curve.len() - 1].0;
+    let n = (last / t).ceil() as usize;
+    (1..=n)
+        .map(|k| {
+            let x = k as f64 * t;
+            if x > last { 0.0 } else { interpolate(&curve, x) }
+        })
+        .collect()
+}
+
+/// 昇順の折れ線 points 上で x の値を線形補間する（範囲外は 0）
+fn interpolate(points: &[(f64, f64)], x: f64) -> f64 {
+    match points.iter().position(|&(px, _)| px >= x) {
+        Some(0) => points[0].1,
+        Some(i) => {
+            let (x0, y0) = points[i - 1];
+            let (x1, y1) = points[i];
+            y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+        }
+        None => 0.0,
+    }
+}
+
+impl RunoffModel for UnitHydrograph {
+    fn discharge(&self, rainfall: &[f64], t: f64, steps: usize) -> Vec<f64> {
+        let hours = t / 60.0;
+        let ordinates = self.ordinates(t);
+        (0..steps)
+            .map(|n| {
+                let direct: f64 = rainfall
+                    .iter()
+                    .take(n + 1)
+                    .enumerate()
+                    .filter_map(|(m, &r)| ordinates.get(n - m).map(|u| r * hours * u))
+                    .sum();
+                direct + self.base_flow
+            })
+            .collect()
+    }
+}
+
+/// ユーザー指定単位図のCSVの1行
+#[derive(Debug, Deserialize)]
+struct UnitHydrographRow {
+    time_minutes: f64,
+    discharge_m3_per_s: f64,
+}
+
+/// 単位図をCSVファイルから読み込む
+/// 列: time_minutes, discharge_m3_per_s（1mm あたりの流量。流域面積で正規化するため形状として扱う）
+pub fn load(path: &Path) -> Result<Vec<(f64, f64)>> {
+    let mut rdr = csv::Reader::from_path(path)
+        .with_context(|| format!("Failed to open unit hydrograph: {}", path.display()))?;
+    let mut points = Vec::new();
+    for record in rdr.deserialize() {
+        let row: UnitHydrographRow = record.with_context(|| {
+            format!("Failed to read unit hydrograph row in {}", path.display())
+        })?;
+        points.push((row.time_minutes, row.discharge_m3_per_s));
+    }
+    validate_table(&points).with_context(|| format!("Invalid unit hydrograph: {}", path.display()))?;
+    Ok(points)
+}
+
+/// ユーザー指定の単位図を検証する
+fn validate_table(points: &[(f64, f64)]) -> Result<()> {
+    if points.is_empty() {
+        bail!("Unit hydrograph has no rows");
+    }
+    for (i, pair) in points.windows(2).enumerate() {
+        if pair[1].0 <= pair[0].0 {
+            bail!(
+                "Row {}: time must be strictly increasing, got {} after {}",
+                i + 2,
+                pair[1].0,
+                pair[0].0
+            );
+        }
+    }
+    if let Some((i, &(time, q))) = points
+        .iter()
+        .enumerate()
+        .find(|(_, (time, q))| *time < 0.0 || *q < 0.0)
+    {
+        bail!(
+            "Row {}: time and discharge must be non-negative, got ({}, {})",
+            i + 1,
+            time,
+            q
+        );
+    }
+    if points.iter().all(|&(_, q)| q == 0.0) {
+        bail!("Unit hydrograph ordinates are all zero");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn unit(shape: Shape) -> UnitHydrograph {
+        UnitHydrograph {
+            shape,
+            area: 36.0,
+            base_flow: 0.0,
+        }
+    }
+
+    fn assert_unit_volume(ordinates: &[f64], t: f64) {
+        // 36 km² × 1 mm = 36000 m³
+        let volume: f64 = ordinates.iter().sum::<f64>() * t * 60.0;
+        assert!((volume - 36000.0).abs() < 1e-6, "volume = {}", volume);
+    }
+
+    #[test]
+    fn scs_peak_near_tp() {
+        let uh = unit(Shape::Scs { lag: 0.75 });
+        let ordinates = uh.ordinates(30.0);
+        assert_unit_volume(&ordinates, 30.0);
+        // Tp = 0.25 + 0.75 = 1 h → ピークは2番目の縦距、qp ≈ 0.208 * 36 / 1
+        let peak = ordinates
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap();
+        assert_eq!(peak.0, 1);
+        assert!((peak.1 - 7.49).abs() < 0.3, "qp = {}", peak.1);
+    }
+
+    #[test]
+    fn snyder_conserves_volume_and_peaks_after_lag() {
+        let uh = unit(Shape::Snyder {
+            ct: 1.5,
+            cp: 0.6,
+            length: 10.0,
+            centroid_length: 5.0,
+        });
+        let ordinates = uh.ordinates(30.0);
+        assert_unit_volume(&ordinates, 30.0);
+        let tp = 0.75 * 1.5 * 50.0_f64.powf(0.3);
+        let peak_index = ordinates
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap()
+            .0;
+        let peak_time = (peak_index + 1) as f64 * 0.5;
+        assert!((peak_time - (tp + 0.25)).abs() <= 0.5, "peak at {} h", peak_time);
+    }
+
+    #[test]
+    fn clark_with_small_storage_follows_time_area() {
+        let uh = unit(Shape::Clark {
+            tc: 2.0,
+            storage: 1.0,
+        });
+        let ordinates = uh.ordinates(10.0);
+        assert_unit_volume(&ordinates, 10.0);
+        assert!(ordinates[0] < ordinates[5]);
+        // 貯留により到達時間を過ぎても減衰しながら流出が続く
+        assert!(ordinates[15] > 0.0);
+        assert!(ordinates.last().unwrap() < &1e-3);
+    }
+
+    #[test]
+    fn table_is_resampled_and_normalized() {
+        let uh = unit(Shape::Table(vec![(20.0, 10.0), (40.0, 0.0)]));
+        let ordinates = uh.ordinates(10.0);
+        assert_eq!(ordinates.len(), 4);
+        assert_unit_volume(&ordinates, 10.0);
+        assert!((ordinates[0] - ordinates[2]).abs() < 1e-9);
+        assert!(ordinates[1] > ordinates[0]);
+    }
+
+    #[test]
+    fn convolution_superposes_unit_responses() {
+        let uh = unit(Shape::Table(vec![(10.0, 1.0), (20.0, 2.0), (30.0, 1.0), (40.0, 0.0)]));
+        let u = uh.ordinates(10.0);
+        // 6mm/h × 10分 = 1mm、12mm/h × 10分 = 2mm
+        let q = uh.discharge(&[6.0, 12.0], 10.0, 5);
+        assert!((q[0] - u[0]).abs() < 1e-9);
+        assert!((q[1] - (u[1] + 2.0 * u[0])).abs() < 1e-9);
+        assert!((q[3] - (u[3] + 2.0 * u[2])).abs() < 1e-9);
+        assert!((q[4] - 2.0 * u[3]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn load_reads_csv_and_rejects_decreasing_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("uh.csv");
+        fs::write(&path, "time_minutes,discharge_m3_per_s\n30,5\n60,3\n90,0\n").unwrap();
+        assert_eq!(load(&path).unwrap(), vec![(30.0, 5.0), (60.0, 3.0), (90.0, 0.0)]);
+
+        fs::write(&path, "time_minutes,discharge_m3_per_s\n30,5\n20,3\n").unwrap();
+        let err = format!("{:#}", load(&path).unwrap_err());
+        assert!(err.contains("strictly increasing"), "Error: {}", err);
+    }
+}
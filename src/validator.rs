@@ -1,15 +1,54 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
 
-use crate::cli::Cli;
-use crate::types::{DistributionPattern, RainfallParams};
+use crate::cli::{ChartArgs, Cli, PondArgs, RationalArgs};
+use crate::areal_reduction::{self, ArealReduction};
+use crate::chart::{self, ChartOptions};
+use crate::coefficient_table;
+use crate::distribution::PatternOptions;
+use crate::formula::{Cleveland, Formula, Kimijima, Sherman, Talbot};
+use crate::hec::HecExport;
+use crate::idf;
+use crate::kinematic_wave::OverlandPlane;
+use crate::loss::{GreenAmpt, Horton, InitialContinuing, Loss, PhiIndex, ScsCurveNumber};
+use crate::mass_curve::{self, Trapezoid, HUFF_PROBABILITIES};
+use crate::pond::{self, StageTable};
+use crate::presets::{self, Preset};
+use crate::rational;
+use crate::runoff::{self, Runoff};
+use crate::storage_function::StorageFunction;
+use crate::swmm::SwmmExport;
+use crate::tank_model;
+use crate::unit_hydrograph::{self, Shape, UnitHydrograph};
+use crate::types::{
+    ArfMethod, ChartFormat, DistributionPattern, FormulaType, Interpolation, LossMethod, OutputFormat, Peak,
+    RainfallParams, RunoffMethod, TcMethod, UnitHydrographMethod,
+};
 
 /// バリデーション済みパラメータ
 #[derive(Debug)]
 pub struct ValidatedParams {
     pub rainfall_params: RainfallParams,
+    /// 係数の出典となったプリセット
+    pub preset: Option<Preset>,
     pub pattern: DistributionPattern,
+    pub pattern_options: PatternOptions,
+    /// シカゴ型降雨のピーク位置係数
+    pub peak_ratio: Option<f64>,
+    /// グラフに重ねて描く比較パターン
+    pub compare: Option<DistributionPattern>,
+    /// 面積低減係数（算定方法, 流域面積[km²]）
+    pub areal_reduction: Option<(ArealReduction, f64)>,
+    /// 損失モデル
+    pub loss: Option<Loss>,
+    /// 流出モデル（モデル, 計算ステップ数）
+    pub runoff: Option<(Runoff, usize)>,
+    /// 調整池（水位-貯留量-放流量表, 自動設計の許容放流量[m³/s]）
+    pub pond: Option<(StageTable, Option<f64>)>,
+    /// 直接指定された総雨量[mm]
+    pub total_depth: Option<f64>,
     pub output_config: OutputConfig,
 }
 
@@ -18,77 +57,901 @@ pub struct ValidatedParams {
 pub struct OutputConfig {
     pub output_path: PathBuf,
     pub format: crate::types::OutputFormat,
+    /// SWMM 出力の設定（--format swmm のとき）
+    pub swmm: Option<SwmmExport>,
+    /// HEC-HMS・HEC-RAS 出力の設定（--format hec-hms・hec-ras のとき）
+    pub hec: Option<HecExport>,
+    /// グラフの出力形式・大きさ・解像度・文字の倍率
+    pub chart: ChartOptions,
+}
+
+/// バリデーション済みの合理式パラメータ
+#[derive(Debug)]
+pub struct RationalParams {
+    pub formula: Formula,
+    /// 係数の出典となったプリセット
+    pub preset: Option<Preset>,
+    /// 流出係数
+    pub runoff_coefficient: f64,
+    /// 流域面積[km²]
+    pub area: f64,
+    /// 洪水到達時間[分]
+    pub tc: f64,
+    /// 洪水到達時間の推定式と流路長[m]・勾配（直接指定時は None）
+    pub tc_estimate: Option<(TcMethod, f64, f64)>,
+    /// ピーク流量表の継続時間[分]
+    pub durations: Vec<f64>,
+    /// ピーク流量表CSVの出力先
+    pub output: Option<PathBuf>,
+}
+
+/// バリデーション済みの調整池追跡パラメータ
+#[derive(Debug)]
+pub struct PondParams {
+    pub table: StageTable,
+    /// 流入ハイドログラフの時刻[分]
+    pub times: Vec<f64>,
+    /// 流入量[m³/s]
+    pub inflow: Vec<f64>,
+    /// 自動設計の許容放流量[m³/s]
+    pub target_release: Option<f64>,
+    /// 追跡結果CSVの出力先
+    pub output: Option<PathBuf>,
+}
+
+/// 位置引数で指定された降雨強度係数
+#[derive(Debug, Clone, Copy)]
+struct Coefficients {
+    /// べき乗指数
+    a: f64,
+    /// 加算定数
+    b: f64,
+    /// 分子定数
+    c: f64,
 }
 
 /// CLI引数のドメインバリデーション
 pub fn validate(cli: &Cli) -> Result<ValidatedParams> {
-    if cli.a <= 0.0 {
+    let (coefficients, t, tt) = match cli.values.as_slice() {
+        &[a, b, c, t, tt] => (Some(Coefficients { a, b, c }), t, tt),
+        &[t, tt] => (None, t, tt),
+        values => bail!(
+            "Expected 5 positional values (A B C T TT), \
+             or 2 (T TT) with --idf-table, --coefficients or --preset, got {}",
+            values.len()
+        ),
+    };
+
+    if t <= 0.0 {
         bail!(
-            "Parameter A must be positive (> 0), got {}. Valid range: A > 0",
-            cli.a
+            "Parameter T must be positive (> 0), got {}. Valid range: T > 0",
+            t
         );
     }
-    if cli.b <= 0.0 {
+    if tt <= 0.0 {
         bail!(
-            "Parameter B must be positive (> 0), got {}. Valid range: B > 0",
-            cli.b
+            "Parameter TT must be positive (> 0), got {}. Valid range: TT > 0",
+            tt
         );
     }
-    if cli.c <= 0.0 {
+
+    let nt = tt * 60.0 / t;
+    if (nt - nt.round()).abs() > 1e-9 {
         bail!(
-            "Parameter C must be positive (> 0), got {}. Valid range: C > 0",
-            cli.c
+            "TT * 60 / T must be an integer. TT={}, T={} gives NT={:.4}, which is not an integer. \
+             Adjust T or TT so that the duration divides evenly into time steps.",
+            tt,
+            t,
+            nt
         );
     }
-    if cli.t <= 0.0 {
+
+    if let Some(depth) = cli.total_depth
+        && depth <= 0.0
+    {
         bail!(
-            "Parameter T must be positive (> 0), got {}. Valid range: T > 0",
-            cli.t
+            "Total depth must be positive (> 0), got {}. Valid range: --total-depth > 0",
+            depth
         );
     }
-    if cli.tt <= 0.0 {
+
+    if let Some(r) = cli.peak_ratio
+        && !(0.0..=1.0).contains(&r)
+    {
         bail!(
-            "Parameter TT must be positive (> 0), got {}. Valid range: TT > 0",
-            cli.tt
+            "Peak ratio must be between 0 and 1, got {}. Valid range: 0 <= --peak-ratio <= 1",
+            r
         );
     }
 
-    let nt = cli.tt * 60.0 / cli.t;
-    if (nt - nt.round()).abs() > 1e-9 {
+    if !HUFF_PROBABILITIES.contains(&cli.huff_probability) {
+        let levels: Vec<String> = HUFF_PROBABILITIES.iter().map(|p| p.to_string()).collect();
         bail!(
-            "TT * 60 / T must be an integer. TT={}, T={} gives NT={:.4}, which is not an integer. \
-             Adjust T or TT so that the duration divides evenly into time steps.",
-            cli.tt,
-            cli.t,
-            nt
+            "Huff probability must be one of {}, got {}",
+            levels.join(", "),
+            cli.huff_probability
+        );
+    }
+
+    // 比較パターンも含めて、追加設定を必要とするパターンが使われているか
+    let uses = |pattern: DistributionPattern| cli.pattern == pattern || cli.compare == Some(pattern);
+
+    // NRCS の累加雨量曲線は24時間降雨として定義されているため他の継続時間には適用しない
+    let scs = [
+        DistributionPattern::ScsI,
+        DistributionPattern::ScsIa,
+        DistributionPattern::ScsII,
+        DistributionPattern::ScsIII,
+    ];
+    if let Some(pattern) = scs.into_iter().find(|&p| uses(p))
+        && (tt - 24.0).abs() > 1e-9
+    {
+        bail!(
+            "--pattern {} is the NRCS 24-hour storm and requires TT = 24, got TT = {}",
+            pattern
+                .to_possible_value()
+                .map(|v| v.get_name().to_string())
+                .unwrap_or_default(),
+            tt
+        );
+    }
+
+    let mass_curve = match (&cli.mass_curve, uses(DistributionPattern::Custom)) {
+        (Some(path), true) => Some(mass_curve::load(path)?),
+        (None, true) => bail!("--pattern custom requires --mass-curve"),
+        (Some(_), false) => bail!("--mass-curve requires --pattern custom"),
+        (None, false) => None,
+    };
+
+    if !(0.0..=1.0).contains(&cli.advancement) {
+        bail!(
+            "Storm advancement must be between 0 and 1, got {}. Valid range: 0 <= --advancement <= 1",
+            cli.advancement
         );
     }
 
-    if let Some(parent) = cli.output.parent() {
-        if !parent.as_os_str().is_empty() && !parent.exists() {
+    let trapezoid = match cli.trapezoid[..] {
+        [rise, plateau, fall] if rise >= 0.0 && plateau >= 0.0 && fall >= 0.0 => {
+            let total = rise + plateau + fall;
+            if (total - 1.0).abs() > 1e-3 {
+                bail!("Trapezoid fractions must sum to 1, got {}", total);
+            }
+            Trapezoid {
+                rise: rise / total,
+                plateau: plateau / total,
+                fall: fall / total,
+            }
+        }
+        _ => bail!(
+            "--trapezoid takes three non-negative fractions RISE,PLATEAU,FALL, got {:?}",
+            cli.trapezoid
+        ),
+    };
+
+    if uses(DistributionPattern::MultiPeak) {
+        validate_peaks(&cli.peaks, nt.round() as usize)?;
+    } else if !cli.peaks.is_empty() {
+        bail!("--peaks requires --pattern multi-peak");
+    }
+
+    let areal_reduction = match cli.area {
+        Some(area) => Some((build_areal_reduction(cli, area)?, area)),
+        None => None,
+    };
+
+    let loss = build_loss(cli)?;
+    let runoff = build_runoff(cli, t, tt)?;
+    let pond = build_pond(cli)?;
+
+    let (formula, preset) = resolve_formula(&FormulaSource::from(cli), coefficients)?;
+    if let Formula::Table(table) = &formula {
+        if t < table.min_duration() {
             bail!(
-                "Output directory does not exist: {}",
-                parent.display()
+                "T={} min is shorter than the first IDF table duration ({} min)",
+                t,
+                table.min_duration()
+            );
+        }
+        if tt * 60.0 > table.max_duration() {
+            bail!(
+                "TT={} h ({} min) exceeds the last IDF table duration ({} min). \
+                 Extend the table or shorten TT.",
+                tt,
+                tt * 60.0,
+                table.max_duration()
             );
         }
     }
 
+    if let Some(parent) = cli.output.parent()
+        && !parent.as_os_str().is_empty()
+        && !parent.exists()
+    {
+        bail!(
+            "Output directory does not exist: {}",
+            parent.display()
+        );
+    }
+
     Ok(ValidatedParams {
-        rainfall_params: RainfallParams {
-            a: cli.a,
-            b: cli.b,
-            c: cli.c,
-            t: cli.t,
-            tt: cli.tt,
-        },
+        rainfall_params: RainfallParams { formula, t, tt },
+        preset,
         pattern: cli.pattern,
+        pattern_options: PatternOptions {
+            huff_probability: cli.huff_probability,
+            mass_curve,
+            peaks: cli.peaks.clone(),
+            advancement: cli.advancement,
+            trapezoid,
+        },
+        peak_ratio: cli.peak_ratio,
+        compare: cli.compare,
+        areal_reduction,
+        loss,
+        runoff,
+        pond,
+        total_depth: cli.total_depth,
         output_config: OutputConfig {
             output_path: cli.output.clone(),
             format: cli.format,
+            swmm: build_swmm(cli)?,
+            hec: build_hec(cli, t)?,
+            chart: build_chart(&cli.chart, &cli.output)?,
         },
     })
 }
 
+/// rationalサブコマンドの引数のドメインバリデーション
+pub fn validate_rational(args: &RationalArgs) -> Result<RationalParams> {
+    let coefficients = match args.values.as_slice() {
+        &[a, b, c] => Some(Coefficients { a, b, c }),
+        [] => None,
+        values => bail!(
+            "Expected 3 positional values (A B C), \
+             or none with --idf-table, --coefficients or --preset, got {}",
+            values.len()
+        ),
+    };
+
+    let runoff_coefficient = match (args.runoff_coefficient, args.land_use.as_slice()) {
+        (Some(c), _) => {
+            if !(0.0..=1.0).contains(&c) {
+                bail!(
+                    "Runoff coefficient must be between 0 and 1, got {}. Valid range: 0 <= --runoff-coefficient <= 1",
+                    c
+                );
+            }
+            c
+        }
+        (None, []) => bail!("Either --runoff-coefficient or --land-use is required"),
+        (None, land_uses) => {
+            for (i, land_use) in land_uses.iter().enumerate() {
+                if !(0.0..=1.0).contains(&land_use.coefficient) {
+                    bail!(
+                        "Land use {}: runoff coefficient must be between 0 and 1, got {}",
+                        i + 1,
+                        land_use.coefficient
+                    );
+                }
+                if land_use.area <= 0.0 {
+                    bail!(
+                        "Land use {}: area must be positive (> 0), got {}",
+                        i + 1,
+                        land_use.area
+                    );
+                }
+            }
+            rational::weighted_coefficient(land_uses)
+        }
+    };
+
+    if args.area <= 0.0 {
+        bail!(
+            "Catchment area must be positive (> 0), got {}. Valid range: --area > 0",
+            args.area
+        );
+    }
+
+    let (tc, tc_estimate) = match (args.tc, args.tc_method) {
+        (Some(tc), _) => (tc, None),
+        (None, Some(method)) => {
+            let (Some(length), Some(slope)) = (args.length, args.slope) else {
+                bail!("--tc-method requires --length and --slope");
+            };
+            if length <= 0.0 || slope <= 0.0 {
+                bail!(
+                    "Channel length and slope must be positive (> 0), got L={}, S={}",
+                    length,
+                    slope
+                );
+            }
+            let tc = match method {
+                TcMethod::Kirpich => rational::kirpich(length, slope),
+                TcMethod::Kraven => rational::kraven(length, slope),
+            };
+            (tc, Some((method, length, slope)))
+        }
+        (None, None) => bail!("Either --tc or --tc-method is required"),
+    };
+    if tc <= 0.0 {
+        bail!(
+            "Time of concentration must be positive (> 0), got {}. Valid range: --tc > 0",
+            tc
+        );
+    }
+
+    if let Some(&d) = args.durations.iter().find(|&&d| d <= 0.0) {
+        bail!("Durations must be positive (> 0), got {}", d);
+    }
+    if let Some(path) = &args.output {
+        if args.durations.is_empty() {
+            bail!("--output requires --durations");
+        }
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+            && !parent.exists()
+        {
+            bail!("Output directory does not exist: {}", parent.display());
+        }
+    }
+
+    let (formula, preset) = resolve_formula(&FormulaSource::from(args), coefficients)?;
+
+    Ok(RationalParams {
+        formula,
+        preset,
+        runoff_coefficient,
+        area: args.area,
+        tc,
+        tc_estimate,
+        durations: args.durations.clone(),
+        output: args.output.clone(),
+    })
+}
+
+/// pondサブコマンドの引数のドメインバリデーション
+pub fn validate_pond(args: &PondArgs) -> Result<PondParams> {
+    check_target_release(args.target_release)?;
+    if let Some(path) = &args.output
+        && let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+        && !parent.exists()
+    {
+        bail!("Output directory does not exist: {}", parent.display());
+    }
+
+    let table = pond::load_table(&args.table)?;
+    let (times, inflow) = pond::load_inflow(&args.inflow)?;
+
+    Ok(PondParams {
+        table,
+        times,
+        inflow,
+        target_release: args.target_release,
+        output: args.output.clone(),
+    })
+}
+
+/// 降雨強度式の指定元（係数表・IDF表・プリセット・位置引数の係数）
+pub struct FormulaSource<'a> {
+    pub idf_table: Option<&'a Path>,
+    pub interpolation: Interpolation,
+    pub coefficients: Option<&'a Path>,
+    pub return_period: Option<u32>,
+    pub preset: Option<&'a str>,
+    /// ユーザー定義プリセットファイル（None なら同梱ライブラリのみ）
+    pub preset_file: Option<PathBuf>,
+    pub formula: FormulaType,
+    pub exponent_m: Option<f64>,
+    /// 係数が不足しているときに示す使い方
+    pub usage: &'static str,
+}
+
+impl<'a> From<&'a Cli> for FormulaSource<'a> {
+    fn from(cli: &'a Cli) -> Self {
+        Self {
+            idf_table: cli.idf_table.as_deref(),
+            interpolation: cli.interpolation,
+            coefficients: cli.coefficients.as_deref(),
+            return_period: cli.return_period,
+            preset: cli.preset.as_deref(),
+            preset_file: presets::user_file(),
+            formula: cli.formula,
+            exponent_m: cli.exponent_m,
+            usage: "hyetograph-cli A B C T TT",
+        }
+    }
+}
+
+impl<'a> From<&'a RationalArgs> for FormulaSource<'a> {
+    fn from(args: &'a RationalArgs) -> Self {
+        Self {
+            idf_table: args.idf_table.as_deref(),
+            interpolation: args.interpolation,
+            coefficients: args.coefficients.as_deref(),
+            return_period: args.return_period,
+            preset: args.preset.as_deref(),
+            preset_file: presets::user_file(),
+            formula: args.formula,
+            exponent_m: args.exponent_m,
+            usage: "hyetograph-cli rational A B C --area KM2 --runoff-coefficient C --tc MINUTES",
+        }
+    }
+}
+
+/// 降雨強度式を組み立てる（プリセット指定時はそのプリセットも返す）
+fn resolve_formula(
+    source: &FormulaSource,
+    coefficients: Option<Coefficients>,
+) -> Result<(Formula, Option<Preset>)> {
+    if source.return_period.is_some() && source.coefficients.is_none() {
+        bail!("--return-period requires --coefficients");
+    }
+
+    let mut preset = None;
+    let formula = if let Some(path) = &source.idf_table {
+        reject_positional_coefficients(coefficients, "--idf-table")?;
+        Formula::Table(idf::load(path, source.interpolation)?)
+    } else if let Some(path) = &source.coefficients {
+        reject_positional_coefficients(coefficients, "--coefficients")?;
+        let table = coefficient_table::load(path)?;
+        let periods = table
+            .periods()
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let Some(return_period) = source.return_period else {
+            bail!(
+                "--return-period is required with --coefficients. Available return periods: {}",
+                periods
+            );
+        };
+        let Some(set) = table.get(return_period) else {
+            bail!(
+                "Return period {} is not in the coefficient table {}. Available return periods: {}",
+                return_period,
+                path.display(),
+                periods
+            );
+        };
+        let coefficients = Coefficients {
+            a: set.a,
+            b: set.b,
+            c: set.c,
+        };
+        build_formula(source.formula, coefficients, set.m.or(source.exponent_m))?
+    } else if let Some(name) = &source.preset {
+        reject_positional_coefficients(coefficients, "--preset")?;
+        let catalog = presets::load_with(source.preset_file.as_deref())?;
+        let entry = catalog.find(name)?.clone();
+        let coefficients = Coefficients {
+            a: entry.preset.a,
+            b: entry.preset.b,
+            c: entry.preset.c,
+        };
+        let formula = build_formula(entry.preset.formula, coefficients, entry.preset.m)
+            .with_context(|| format!("Invalid preset '{}'", name))?;
+        preset = Some(entry.preset);
+        formula
+    } else {
+        let Some(coefficients) = coefficients else {
+            bail!(
+                "Coefficients A B C are required unless --idf-table, --coefficients \
+                 or --preset is given. \
+                 Usage: {}",
+                source.usage
+            );
+        };
+        build_formula(source.formula, coefficients, source.exponent_m)?
+    };
+
+    Ok((formula, preset))
+}
+
+/// 面積低減係数の算定方法を組み立てる
+fn build_areal_reduction(cli: &Cli, area: f64) -> Result<ArealReduction> {
+    if area <= 0.0 {
+        bail!(
+            "Catchment area must be positive (> 0), got {}. Valid range: --area > 0",
+            area
+        );
+    }
+    match cli.arf_method {
+        ArfMethod::Tp29 => Ok(ArealReduction::Tp29),
+        ArfMethod::Fsr => Ok(ArealReduction::Fsr),
+        ArfMethod::Horton => match (cli.arf_k, cli.arf_n) {
+            (Some(k), Some(n)) if k >= 0.0 && n > 0.0 => Ok(ArealReduction::Horton { k, n }),
+            (Some(k), Some(n)) => bail!(
+                "Horton coefficients must satisfy k >= 0 and n > 0, got k={}, n={}",
+                k,
+                n
+            ),
+            _ => bail!("--arf-method horton requires --arf-k and --arf-n"),
+        },
+        ArfMethod::Table => match &cli.arf_table {
+            Some(path) => Ok(ArealReduction::Table(areal_reduction::load_table(path)?)),
+            None => bail!("--arf-method table requires --arf-table"),
+        },
+    }
+}
+
+/// 損失モデルを構築する
+/// --loss の省略時は --cn の指定があれば SCS カーブナンバー法とする。
+fn build_loss(cli: &Cli) -> Result<Option<Loss>> {
+    let method = match (cli.loss, cli.cn) {
+        (Some(method), _) => method,
+        (None, Some(_)) => LossMethod::ScsCn,
+        (None, None) => return Ok(None),
+    };
+    let loss = match method {
+        LossMethod::ScsCn => match cli.cn {
+            Some(cn) => Loss::ScsCurveNumber(ScsCurveNumber::new(cn, cli.ia_ratio, cli.amc)?),
+            None => bail!("--loss scs-cn requires --cn"),
+        },
+        LossMethod::Horton => match (cli.horton_f0, cli.horton_fc, cli.horton_k) {
+            (Some(f0), Some(fc), Some(k)) => Loss::Horton(Horton::new(f0, fc, k)?),
+            _ => bail!("--loss horton requires --horton-f0, --horton-fc and --horton-k"),
+        },
+        LossMethod::GreenAmpt => match (cli.ksat, cli.suction, cli.moisture_deficit) {
+            (Some(ksat), Some(suction), Some(deficit)) => {
+                Loss::GreenAmpt(GreenAmpt::new(ksat, suction, deficit)?)
+            }
+            _ => bail!("--loss green-ampt requires --ksat, --suction and --moisture-deficit"),
+        },
+        LossMethod::PhiIndex => match cli.phi {
+            Some(phi) if phi >= 0.0 => Loss::PhiIndex(PhiIndex { phi }),
+            Some(phi) => bail!(
+                "Phi index must be non-negative, got {}. Valid range: --phi >= 0",
+                phi
+            ),
+            None => bail!("--loss phi-index requires --phi"),
+        },
+        LossMethod::InitialContinuing => match (cli.initial_loss, cli.continuing_loss) {
+            (Some(initial), Some(continuing)) if initial >= 0.0 && continuing >= 0.0 => {
+                Loss::InitialContinuing(InitialContinuing {
+                    initial,
+                    continuing,
+                })
+            }
+            (Some(initial), Some(continuing)) => bail!(
+                "Initial and continuing losses must be non-negative, got {} mm and {} mm/h",
+                initial,
+                continuing
+            ),
+            _ => bail!("--loss initial-continuing requires --initial-loss and --continuing-loss"),
+        },
+    };
+    Ok(Some(loss))
+}
+
+/// 流出モデルと計算ステップ数を構築する
+fn build_runoff(cli: &Cli, t: f64, tt: f64) -> Result<Option<(Runoff, usize)>> {
+    let Some(method) = cli.runoff else {
+        return Ok(None);
+    };
+    // キネマティックウェーブ法の面積は斜面長 × 幅で決まる
+    let catchment_area = || -> Result<f64> {
+        // --area は降雨の面積低減を有効にするため、流出計算の面積とは兼用しない
+        match cli.runoff_area {
+            Some(area) => Ok(area),
+            None => bail!(
+                "--runoff requires --runoff-area (--area only applies areal reduction to the rainfall)"
+            ),
+        }
+    };
+    let duration = cli
+        .runoff_duration
+        .unwrap_or(tt * runoff::DEFAULT_DURATION_FACTOR);
+    if duration < tt {
+        bail!(
+            "Runoff duration must not be shorter than TT ({} h), got {}. Valid range: --runoff-duration >= TT",
+            tt,
+            duration
+        );
+    }
+    let steps = (duration * 60.0 / t).round() as usize;
+
+    let runoff = match method {
+        RunoffMethod::StorageFunction => {
+            let area = catchment_area()?;
+            let (Some(k), Some(p)) = (cli.sf_k, cli.sf_p) else {
+                bail!("--runoff storage-function requires --sf-k and --sf-p");
+            };
+            Runoff::StorageFunction(StorageFunction::new(
+                k,
+                p,
+                cli.sf_tl,
+                cli.sf_f1,
+                cli.sf_rsa,
+                area,
+                cli.base_flow,
+            )?)
+        }
+        RunoffMethod::UnitHydrograph => {
+            let area = catchment_area()?;
+            if cli.base_flow < 0.0 {
+                bail!("Base flow must be non-negative, got {}", cli.base_flow);
+            }
+            if area <= 0.0 {
+                bail!("Catchment area must be positive (> 0), got {}", area);
+            }
+            Runoff::UnitHydrograph(UnitHydrograph {
+                shape: build_unit_hydrograph_shape(cli)?,
+                area,
+                base_flow: cli.base_flow,
+            })
+        }
+        RunoffMethod::Tank => {
+            let area = catchment_area()?;
+            if area <= 0.0 {
+                bail!("Catchment area must be positive (> 0), got {}", area);
+            }
+            match &cli.tank_model {
+                Some(path) => Runoff::Tank(tank_model::load(path, area)?),
+                None => bail!("--runoff tank requires --tank-model"),
+            }
+        }
+        RunoffMethod::KinematicWave => {
+            let (Some(slope), Some(length), Some(roughness), Some(width)) =
+                (cli.kw_slope, cli.kw_length, cli.kw_n, cli.kw_width)
+            else {
+                bail!(
+                    "--runoff kinematic-wave requires --kw-slope, --kw-length, --kw-n and --kw-width"
+                );
+            };
+            Runoff::KinematicWave(OverlandPlane::new(slope, length, roughness, width)?)
+        }
+    };
+    Ok(Some((runoff, steps)))
+}
+
+/// SWMM 出力の設定を構築する
+fn build_swmm(cli: &Cli) -> Result<Option<SwmmExport>> {
+    if cli.format != OutputFormat::Swmm {
+        if cli.swmm_inp.is_some() {
+            bail!("--swmm-inp requires --format swmm");
+        }
+        return Ok(None);
+    }
+    for (flag, name) in [("--swmm-series", &cli.swmm_series), ("--swmm-gage", &cli.swmm_gage)] {
+        if name.is_empty() || name.contains(char::is_whitespace) || name.starts_with(';') {
+            bail!(
+                "{} must be a non-empty name without spaces or a leading ';', got '{}'",
+                flag,
+                name
+            );
+        }
+    }
+    if let Some(path) = &cli.swmm_inp
+        && !path.is_file()
+    {
+        bail!("SWMM input file not found: {}", path.display());
+    }
+    Ok(Some(SwmmExport {
+        series: cli.swmm_series.clone(),
+        gage: cli.swmm_gage.clone(),
+        format: cli.swmm_rain_format,
+        start: cli.start_time,
+        inp: cli.swmm_inp.clone(),
+    }))
+}
+
+/// HEC-HMS・HEC-RAS 出力の設定を構築する
+fn build_hec(cli: &Cli, t: f64) -> Result<Option<HecExport>> {
+    if !matches!(cli.format, OutputFormat::HecHms | OutputFormat::HecRas) {
+        return Ok(None);
+    }
+    let Some(start) = cli.start_time else {
+        bail!("--format hec-hms and hec-ras require --start-time");
+    };
+    Ok(Some(HecExport::new(start, &cli.hec_location, t)?))
+}
+
+/// グラフの体裁を構築する
+/// --dpi はPNGの画素数と解像度情報にのみ作用するため、SVG・PDFへの出力では既定値以外を拒否する。
+pub fn build_chart(args: &ChartArgs, output_path: &Path) -> Result<ChartOptions> {
+    if args.chart_width == 0 || args.chart_height == 0 {
+        bail!(
+            "--chart-width and --chart-height must be positive, got {}x{}",
+            args.chart_width,
+            args.chart_height
+        );
+    }
+    if !args.dpi.is_finite() || args.dpi <= 0.0 {
+        bail!("--dpi must be positive (> 0), got {}", args.dpi);
+    }
+    if !args.font_scale.is_finite() || args.font_scale <= 0.0 {
+        bail!("--font-scale must be positive (> 0), got {}", args.font_scale);
+    }
+    let options = ChartOptions {
+        format: args.chart_format,
+        width: args.chart_width,
+        height: args.chart_height,
+        dpi: args.dpi,
+        font_scale: args.font_scale,
+    };
+    let format = options.format_for(output_path);
+    if format != ChartFormat::Png && args.dpi != chart::BASE_DPI {
+        bail!(
+            "--dpi only applies to PNG charts; {} charts are sized by --chart-width and --chart-height",
+            format.extension().to_uppercase()
+        );
+    }
+    Ok(options)
+}
+
+/// 調整池の追跡設定を構築する
+fn build_pond(cli: &Cli) -> Result<Option<(StageTable, Option<f64>)>> {
+    let Some(path) = &cli.pond else {
+        if cli.target_release.is_some() {
+            bail!("--target-release requires --pond");
+        }
+        return Ok(None);
+    };
+    if cli.runoff.is_none() {
+        bail!("--pond requires --runoff");
+    }
+    check_target_release(cli.target_release)?;
+    Ok(Some((pond::load_table(path)?, cli.target_release)))
+}
+
+/// 許容放流量の範囲チェック
+fn check_target_release(target: Option<f64>) -> Result<()> {
+    if let Some(target) = target
+        && target <= 0.0
+    {
+        bail!("Allowable release must be positive (> 0), got {}", target);
+    }
+    Ok(())
+}
+
+/// 単位図の形状を組み立てる
+fn build_unit_hydrograph_shape(cli: &Cli) -> Result<Shape> {
+    let positive = |name: &str, value: f64| {
+        if value > 0.0 {
+            Ok(value)
+        } else {
+            bail!("{} must be positive (> 0), got {}", name, value)
+        }
+    };
+    match cli.uh_method {
+        UnitHydrographMethod::Scs => {
+            let lag = match (cli.uh_lag, cli.uh_tc) {
+                (Some(lag), _) => lag,
+                (None, Some(tc)) => 0.6 * tc,
+                (None, None) => bail!("--uh-method scs requires --uh-lag or --uh-tc"),
+            };
+            Ok(Shape::Scs {
+                lag: positive("Lag time", lag)?,
+            })
+        }
+        UnitHydrographMethod::Snyder => {
+            let (Some(ct), Some(cp), Some(length), Some(centroid_length)) =
+                (cli.snyder_ct, cli.snyder_cp, cli.snyder_length, cli.snyder_lc)
+            else {
+                bail!(
+                    "--uh-method snyder requires --snyder-ct, --snyder-cp, --snyder-length and --snyder-lc"
+                );
+            };
+            Ok(Shape::Snyder {
+                ct: positive("Snyder Ct", ct)?,
+                cp: positive("Snyder Cp", cp)?,
+                length: positive("Snyder L", length)?,
+                centroid_length: positive("Snyder Lc", centroid_length)?,
+            })
+        }
+        UnitHydrographMethod::Clark => {
+            let (Some(tc), Some(storage)) = (cli.uh_tc, cli.clark_r) else {
+                bail!("--uh-method clark requires --uh-tc and --clark-r");
+            };
+            Ok(Shape::Clark {
+                tc: positive("Clark tc", tc)?,
+                storage: positive("Clark R", storage)?,
+            })
+        }
+        UnitHydrographMethod::File => match &cli.uh_file {
+            Some(path) => Ok(Shape::Table(unit_hydrograph::load(path)?)),
+            None => bail!("--uh-method file requires --uh-file"),
+        },
+    }
+}
+
+/// 多峰型のピーク指定を検証する
+fn validate_peaks(peaks: &[Peak], nt: usize) -> Result<()> {
+    if peaks.is_empty() {
+        bail!("--pattern multi-peak requires --peaks");
+    }
+    if peaks.len() > nt {
+        bail!(
+            "{} peaks do not fit into NT={} time steps",
+            peaks.len(),
+            nt
+        );
+    }
+    for (i, peak) in peaks.iter().enumerate() {
+        if !(0.0..=1.0).contains(&peak.position) {
+            bail!(
+                "Peak {} position must be between 0 and 1, got {}",
+                i + 1,
+                peak.position
+            );
+        }
+        if peak.share <= 0.0 {
+            bail!("Peak {} share must be positive (> 0), got {}", i + 1, peak.share);
+        }
+    }
+    if peaks.windows(2).any(|pair| pair[1].position <= pair[0].position) {
+        bail!("Peak positions must be in increasing order");
+    }
+    let total: f64 = peaks.iter().map(|p| p.share).sum();
+    if (total - 1.0).abs() > 1e-3 {
+        bail!("Peak shares must sum to 1, got {}", total);
+    }
+    Ok(())
+}
+
+/// 係数表などの入力と位置引数の係数A B Cの併用を拒否する
+fn reject_positional_coefficients(coefficients: Option<Coefficients>, source: &str) -> Result<()> {
+    if coefficients.is_some() {
+        bail!(
+            "Coefficients A B C cannot be combined with {}; pass only T TT",
+            source
+        );
+    }
+    Ok(())
+}
+
+/// 係数A, B, Cを選択された降雨強度式の係数に割り当てる
+/// A: べき乗指数, B: 加算定数, C: 分子定数
+fn build_formula(
+    formula_type: FormulaType,
+    coefficients: Coefficients,
+    exponent_m: Option<f64>,
+) -> Result<Formula> {
+    let Coefficients { a, b, c } = coefficients;
+
+    // Talbot型はAを、Sherman型はBを使わないため検査しない
+    if formula_type != FormulaType::Talbot && a <= 0.0 {
+        bail!(
+            "Parameter A must be positive (> 0), got {}. Valid range: A > 0",
+            a
+        );
+    }
+    if formula_type != FormulaType::Sherman && b <= 0.0 {
+        bail!(
+            "Parameter B must be positive (> 0), got {}. Valid range: B > 0",
+            b
+        );
+    }
+    if c <= 0.0 {
+        bail!(
+            "Parameter C must be positive (> 0), got {}. Valid range: C > 0",
+            c
+        );
+    }
+
+    let formula = match formula_type {
+        FormulaType::Talbot => Formula::Talbot(Talbot { a: c, b }),
+        FormulaType::Sherman => Formula::Sherman(Sherman { a: c, n: a }),
+        FormulaType::Cleveland => Formula::Cleveland(Cleveland { a: c, b, n: a }),
+        FormulaType::Kimijima => {
+            let Some(m) = exponent_m else {
+                bail!("--exponent-m is required when --formula kimijima is selected");
+            };
+            if m <= 0.0 {
+                bail!(
+                    "Parameter M must be positive (> 0), got {}. Valid range: M > 0",
+                    m
+                );
+            }
+            Formula::Kimijima(Kimijima { a: c, b, n: a, m })
+        }
+    };
+
+    Ok(formula)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -110,7 +973,14 @@ mod tests {
         let result = validate(&cli);
         assert!(result.is_ok());
         let params = result.unwrap();
-        assert_eq!(params.rainfall_params.a, 0.75);
+        assert_eq!(
+            params.rainfall_params.formula,
+            Formula::Cleveland(Cleveland {
+                a: 1557.825,
+                b: 5.411,
+                n: 0.75,
+            })
+        );
         assert_eq!(params.rainfall_params.tt, 2.0);
         assert_eq!(params.pattern, DistributionPattern::Center);
     }
@@ -189,4 +1059,633 @@ mod tests {
         let err = validate(&cli).unwrap_err().to_string();
         assert!(err.contains("does not exist"), "Error: {}", err);
     }
+
+    #[test]
+    fn talbot_ignores_a() {
+        let cli = cli_with_args(&["0", "40", "5000", "10", "1", "--formula", "talbot"]);
+        let params = validate(&cli).unwrap();
+        assert_eq!(
+            params.rainfall_params.formula,
+            Formula::Talbot(Talbot { a: 5000.0, b: 40.0 })
+        );
+    }
+
+    #[test]
+    fn sherman_ignores_b() {
+        let cli = cli_with_args(&["0.5", "0", "400", "10", "1", "--formula", "sherman"]);
+        let params = validate(&cli).unwrap();
+        assert_eq!(
+            params.rainfall_params.formula,
+            Formula::Sherman(Sherman { a: 400.0, n: 0.5 })
+        );
+    }
+
+    #[test]
+    fn kimijima_without_m_fails() {
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--formula", "kimijima"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("--exponent-m"), "Error: {}", err);
+    }
+
+    #[test]
+    fn kimijima_with_non_positive_m_fails() {
+        let cli = cli_with_args(&[
+            "0.75",
+            "5.411",
+            "1557.825",
+            "10",
+            "2",
+            "--formula",
+            "kimijima",
+            "--exponent-m",
+            "0",
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("Parameter M"), "Error: {}", err);
+    }
+
+    fn write_idf_table(dir: &std::path::Path) -> PathBuf {
+        let path = dir.join("idf.csv");
+        std::fs::write(
+            &path,
+            "duration_minutes,intensity_mm_per_h\n10,141.179\n30,85.456\n60,59.124\n120,38.103\n",
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn idf_table_with_t_and_tt_passes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_idf_table(dir.path());
+        let cli = cli_with_args(&["10", "2", "--idf-table", path.to_str().unwrap()]);
+        let params = validate(&cli).unwrap();
+        assert!(matches!(params.rainfall_params.formula, Formula::Table(_)));
+        assert_eq!(params.rainfall_params.t, 10.0);
+        assert_eq!(params.rainfall_params.tt, 2.0);
+    }
+
+    #[test]
+    fn idf_table_shorter_than_tt_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_idf_table(dir.path());
+        let cli = cli_with_args(&["10", "3", "--idf-table", path.to_str().unwrap()]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("exceeds the last IDF table duration"), "Error: {}", err);
+    }
+
+    #[test]
+    fn idf_table_with_coefficients_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_idf_table(dir.path());
+        let cli = cli_with_args(&[
+            "0.75",
+            "5.411",
+            "1557.825",
+            "10",
+            "2",
+            "--idf-table",
+            path.to_str().unwrap(),
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("cannot be combined"), "Error: {}", err);
+    }
+
+    #[test]
+    fn missing_coefficients_fails() {
+        let cli = cli_with_args(&["10", "2"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("Coefficients A B C are required"), "Error: {}", err);
+    }
+
+    #[test]
+    fn wrong_positional_count_fails() {
+        let cli = cli_with_args(&["0.75", "5.411", "10", "2"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("Expected 5 positional values"), "Error: {}", err);
+    }
+
+    fn write_coefficient_table(dir: &std::path::Path) -> PathBuf {
+        let path = dir.join("coefficients.toml");
+        std::fs::write(
+            &path,
+            "sets = [\n\
+             { return_period = 10, a = 0.75, b = 5.411, c = 1557.825 },\n\
+             { return_period = 50, a = 0.74, b = 5.102, c = 1983.551 },\n\
+             ]\n",
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn return_period_selects_coefficient_set() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_coefficient_table(dir.path());
+        let cli = cli_with_args(&[
+            "10",
+            "2",
+            "--coefficients",
+            path.to_str().unwrap(),
+            "--return-period",
+            "10",
+        ]);
+        let params = validate(&cli).unwrap();
+        assert_eq!(
+            params.rainfall_params.formula,
+            Formula::Cleveland(Cleveland {
+                a: 1557.825,
+                b: 5.411,
+                n: 0.75,
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_return_period_fails_with_available_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_coefficient_table(dir.path());
+        let cli = cli_with_args(&[
+            "10",
+            "2",
+            "--coefficients",
+            path.to_str().unwrap(),
+            "--return-period",
+            "30",
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("Return period 30"), "Error: {}", err);
+        assert!(err.contains("10, 50"), "Error: {}", err);
+    }
+
+    #[test]
+    fn coefficients_without_return_period_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_coefficient_table(dir.path());
+        let cli = cli_with_args(&["10", "2", "--coefficients", path.to_str().unwrap()]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("--return-period is required"), "Error: {}", err);
+    }
+
+    #[test]
+    fn return_period_without_coefficients_fails() {
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--return-period", "10"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("requires --coefficients"), "Error: {}", err);
+    }
+
+    /// ユーザー定義ファイルを読まず同梱ライブラリだけでプリセットを解決する
+    fn resolve_bundled_preset(name: &str) -> Result<(Formula, Option<Preset>)> {
+        let cli = cli_with_args(&["10", "1", "--preset", name]);
+        let source = FormulaSource {
+            preset_file: None,
+            ..FormulaSource::from(&cli)
+        };
+        resolve_formula(&source, None)
+    }
+
+    #[test]
+    fn preset_supplies_formula_and_coefficients() {
+        let (formula, preset) = resolve_bundled_preset("tokyo-ku-50mm").unwrap();
+        assert_eq!(formula, Formula::Talbot(Talbot { a: 5000.0, b: 40.0 }));
+        assert_eq!(preset.unwrap().name, "tokyo-ku-50mm");
+    }
+
+    #[test]
+    fn unknown_preset_fails() {
+        let err = resolve_bundled_preset("nowhere").unwrap_err().to_string();
+        assert!(err.contains("Unknown preset"), "Error: {}", err);
+    }
+
+    #[test]
+    fn preset_conflicts_with_explicit_formula() {
+        for args in [["--formula", "talbot"], ["--exponent-m", "1.2"]] {
+            let mut full = vec!["hyetograph-cli", "10", "1", "--preset", "tokyo-ku-50mm"];
+            full.extend_from_slice(&args);
+            assert!(Cli::try_parse_from(full).is_err(), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn non_positive_total_depth_fails() {
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--total-depth", "0"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("Total depth"), "Error: {}", err);
+    }
+
+    #[test]
+    fn peak_ratio_out_of_range_fails() {
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--peak-ratio", "1.5"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("Peak ratio"), "Error: {}", err);
+    }
+
+    #[test]
+    fn huff_probability_must_be_tabulated() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--pattern", "huff-q2", "--huff-probability", "55",
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("Huff probability"), "Error: {}", err);
+    }
+
+    #[test]
+    fn custom_pattern_requires_mass_curve() {
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--pattern", "custom"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("requires --mass-curve"), "Error: {}", err);
+    }
+
+    #[test]
+    fn mass_curve_requires_custom_pattern() {
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--mass-curve", "curve.csv"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("requires --pattern custom"), "Error: {}", err);
+    }
+
+    #[test]
+    fn multi_peak_requires_peaks() {
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--pattern", "multi-peak"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("requires --peaks"), "Error: {}", err);
+    }
+
+    #[test]
+    fn peak_shares_must_sum_to_one() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--pattern", "multi-peak", "--peaks", "0.3:0.4,0.7:0.4",
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("sum to 1"), "Error: {}", err);
+    }
+
+    #[test]
+    fn peak_positions_must_increase() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--pattern", "multi-peak", "--peaks", "0.7:0.5,0.3:0.5",
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("increasing order"), "Error: {}", err);
+    }
+
+    #[test]
+    fn trapezoid_fractions_must_sum_to_one() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--pattern", "trapezoidal", "--trapezoid", "0.3,0.3,0.3",
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("sum to 1"), "Error: {}", err);
+    }
+
+    #[test]
+    fn trapezoid_needs_three_fractions() {
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--trapezoid", "0.5,0.5"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("RISE,PLATEAU,FALL"), "Error: {}", err);
+    }
+
+    #[test]
+    fn advancement_out_of_range_fails() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--pattern", "triangular", "--advancement", "-0.1",
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("Storm advancement"), "Error: {}", err);
+    }
+
+    #[test]
+    fn scs_pattern_requires_24_hour_duration() {
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--pattern", "scs-ii"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("scs-ii") && err.contains("TT = 24"), "Error: {}", err);
+
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--compare", "scs-ia"]);
+        assert!(validate(&cli).is_err());
+
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "60", "24", "--pattern", "scs-iii"]);
+        assert!(validate(&cli).is_ok());
+    }
+
+    #[test]
+    fn compare_pattern_needs_its_settings() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--pattern", "triangular", "--compare", "multi-peak",
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("requires --peaks"), "Error: {}", err);
+    }
+
+    #[test]
+    fn area_uses_tp29_by_default() {
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--area", "50"]);
+        let params = validate(&cli).unwrap();
+        assert_eq!(params.areal_reduction, Some((ArealReduction::Tp29, 50.0)));
+    }
+
+    #[test]
+    fn horton_requires_coefficients() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--area", "50", "--arf-method", "horton",
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("requires --arf-k and --arf-n"), "Error: {}", err);
+    }
+
+    #[test]
+    fn non_positive_area_fails() {
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--area", "0"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("Catchment area"), "Error: {}", err);
+    }
+
+    #[test]
+    fn curve_number_builds_loss_model() {
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--cn", "80", "--amc", "wet"]);
+        let params = validate(&cli).unwrap();
+        let Some(Loss::ScsCurveNumber(loss)) = params.loss else {
+            panic!("expected SCS curve number model, got {:?}", params.loss);
+        };
+        assert!(loss.cn > 80.0);
+        assert_eq!(loss.ia_ratio, 0.2);
+    }
+
+    #[test]
+    fn loss_method_builds_selected_model() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--loss", "initial-continuing",
+            "--initial-loss", "10", "--continuing-loss", "2.5",
+        ]);
+        let params = validate(&cli).unwrap();
+        assert_eq!(
+            params.loss,
+            Some(Loss::InitialContinuing(InitialContinuing {
+                initial: 10.0,
+                continuing: 2.5
+            }))
+        );
+    }
+
+    #[test]
+    fn loss_method_without_parameters_fails() {
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--loss", "horton"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("requires --horton-f0"), "Error: {}", err);
+
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--loss", "scs-cn"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("requires --cn"), "Error: {}", err);
+    }
+
+    #[test]
+    fn negative_phi_index_fails() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--loss", "phi-index", "--phi", "-1",
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("Phi index"), "Error: {}", err);
+    }
+
+    #[test]
+    fn curve_number_out_of_range_fails() {
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--cn", "120"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("Curve number"), "Error: {}", err);
+    }
+
+    fn rational_args(args: &[&str]) -> RationalArgs {
+        let mut full_args = vec!["hyetograph-cli", "rational"];
+        full_args.extend_from_slice(args);
+        match Cli::try_parse_from(full_args).unwrap().command {
+            Some(crate::cli::Command::Rational(args)) => *args,
+            other => panic!("expected rational subcommand, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn storage_function_uses_runoff_area_and_default_duration() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--runoff", "storage-function", "--sf-k",
+            "20", "--sf-p", "0.6", "--runoff-area", "50",
+        ]);
+        let params = validate(&cli).unwrap();
+        let Some((Runoff::StorageFunction(model), steps)) = params.runoff else {
+            panic!("expected storage function model");
+        };
+        assert_eq!(model.area, 50.0);
+        assert_eq!(steps, 36);
+    }
+
+    #[test]
+    fn runoff_without_area_fails() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--runoff", "storage-function", "--sf-k",
+            "20", "--sf-p", "0.6",
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("--runoff-area"), "Error: {}", err);
+    }
+
+    #[test]
+    fn runoff_does_not_reuse_arf_area() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--runoff", "storage-function", "--sf-k",
+            "20", "--sf-p", "0.6", "--area", "50",
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("--runoff-area"), "Error: {}", err);
+    }
+
+    #[test]
+    fn storage_function_requires_k_and_p() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--runoff", "storage-function",
+            "--runoff-area", "50",
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("--sf-k and --sf-p"), "Error: {}", err);
+    }
+
+    #[test]
+    fn unit_hydrograph_scs_lag_defaults_to_tc_ratio() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--runoff", "unit-hydrograph", "--uh-tc",
+            "2", "--runoff-area", "20",
+        ]);
+        let params = validate(&cli).unwrap();
+        let Some((Runoff::UnitHydrograph(model), _)) = params.runoff else {
+            panic!("expected unit hydrograph model");
+        };
+        assert_eq!(model.shape, Shape::Scs { lag: 1.2 });
+    }
+
+    #[test]
+    fn unit_hydrograph_missing_parameters_fail() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--runoff", "unit-hydrograph", "--uh-method",
+            "clark", "--uh-tc", "2", "--runoff-area", "20",
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("--clark-r"), "Error: {}", err);
+    }
+
+    #[test]
+    fn tank_requires_parameter_file() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--runoff", "tank", "--runoff-area", "20",
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("--tank-model"), "Error: {}", err);
+    }
+
+    #[test]
+    fn kinematic_wave_does_not_need_catchment_area() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--runoff", "kinematic-wave", "--kw-slope",
+            "0.02", "--kw-length", "80", "--kw-n", "0.013", "--kw-width", "40",
+        ]);
+        let params = validate(&cli).unwrap();
+        let Some((Runoff::KinematicWave(plane), _)) = params.runoff else {
+            panic!("expected kinematic wave model");
+        };
+        assert_eq!(plane.length, 80.0);
+    }
+
+    #[test]
+    fn kinematic_wave_requires_plane_parameters() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--runoff", "kinematic-wave", "--kw-slope",
+            "0.02",
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("--kw-length"), "Error: {}", err);
+    }
+
+    #[test]
+    fn swmm_inp_requires_swmm_format() {
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--swmm-inp", "a.inp"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("--swmm-inp requires --format swmm"), "Error: {}", err);
+    }
+
+    #[test]
+    fn swmm_series_name_must_not_contain_spaces() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--format", "swmm", "--swmm-series",
+            "design storm",
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("--swmm-series"), "Error: {}", err);
+    }
+
+    #[test]
+    fn hec_format_requires_start_time() {
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--format", "hec-hms"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("require --start-time"), "Error: {}", err);
+    }
+
+    #[test]
+    fn hec_format_requires_regular_interval() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "7", "2.1", "--format", "hec-ras", "--start-time",
+            "2024-06-01T09:00",
+        ]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("HEC-DSS regular interval"), "Error: {}", err);
+    }
+
+    #[test]
+    fn chart_options_are_validated() {
+        let cli = cli_with_args(&[
+            "0.75", "5.411", "1557.825", "10", "2", "--chart-format", "png", "--dpi", "300",
+            "--font-scale", "1.5",
+        ]);
+        let chart = validate(&cli).unwrap().output_config.chart;
+        assert_eq!(chart.format, Some(ChartFormat::Png));
+        assert_eq!((chart.width, chart.height), (800, 600));
+        assert_eq!(chart.dpi, 300.0);
+        assert_eq!(chart.font_scale, 1.5);
+
+        for args in [["--dpi", "0"], ["--font-scale", "-1"], ["--chart-width", "0"]] {
+            let mut full = vec!["0.75", "5.411", "1557.825", "10", "2"];
+            full.extend_from_slice(&args);
+            let err = validate(&cli_with_args(&full)).unwrap_err().to_string();
+            assert!(err.contains(args[0]), "Error: {}", err);
+        }
+    }
+
+    #[test]
+    fn dpi_is_rejected_for_vector_charts() {
+        for args in [["--chart-format", "svg"], ["--output", "storm.pdf"]] {
+            let mut full = vec!["0.75", "5.411", "1557.825", "10", "2", "--dpi", "300"];
+            full.extend_from_slice(&args);
+            let err = validate(&cli_with_args(&full)).unwrap_err().to_string();
+            assert!(err.contains("--dpi only applies to PNG"), "Error: {}", err);
+        }
+    }
+
+    #[test]
+    fn pond_requires_runoff() {
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--pond", "pond.csv"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("--pond requires --runoff"), "Error: {}", err);
+    }
+
+    #[test]
+    fn target_release_requires_pond() {
+        let cli = cli_with_args(&["0.75", "5.411", "1557.825", "10", "2", "--target-release", "3"]);
+        let err = validate(&cli).unwrap_err().to_string();
+        assert!(err.contains("--target-release requires --pond"), "Error: {}", err);
+    }
+
+    #[test]
+    fn pond_subcommand_rejects_non_positive_release() {
+        let cli = cli_with_args(&[
+            "pond", "--table", "pond.csv", "--inflow", "inflow.csv", "--target-release", "0",
+        ]);
+        let Some(crate::cli::Command::Pond(args)) = cli.command else {
+            panic!("expected pond subcommand");
+        };
+        let err = validate_pond(&args).unwrap_err().to_string();
+        assert!(err.contains("Allowable release must be positive"), "Error: {}", err);
+    }
+
+    #[test]
+    fn rational_weights_land_use_and_estimates_tc() {
+        let args = rational_args(&[
+            "0.75", "5.411", "1557.825", "--land-use", "0.9:1,0.3:3", "--area", "4",
+            "--tc-method", "kraven", "--length", "2100", "--slope", "0.02",
+        ]);
+        let params = validate_rational(&args).unwrap();
+        assert!((params.runoff_coefficient - 0.45).abs() < 1e-12);
+        assert!((params.tc - 10.0).abs() < 1e-12);
+        assert_eq!(params.tc_estimate, Some((TcMethod::Kraven, 2100.0, 0.02)));
+    }
+
+    #[test]
+    fn rational_requires_runoff_coefficient() {
+        let args = rational_args(&["0.75", "5.411", "1557.825", "--area", "2", "--tc", "10"]);
+        let err = validate_rational(&args).unwrap_err().to_string();
+        assert!(err.contains("--runoff-coefficient or --land-use"), "Error: {}", err);
+    }
+
+    #[test]
+    fn rational_tc_method_requires_length_and_slope() {
+        let args = rational_args(&[
+            "0.75", "5.411", "1557.825", "--runoff-coefficient", "0.5", "--area", "2",
+            "--tc-method", "kirpich",
+        ]);
+        let err = validate_rational(&args).unwrap_err().to_string();
+        assert!(err.contains("requires --length and --slope"), "Error: {}", err);
+    }
+
+    #[test]
+    fn rational_output_requires_durations() {
+        let args = rational_args(&[
+            "0.75", "5.411", "1557.825", "--runoff-coefficient", "0.5", "--area", "2", "--tc",
+            "10", "--output", "q.csv",
+        ]);
+        let err = validate_rational(&args).unwrap_err().to_string();
+        assert!(err.contains("--output requires --durations"), "Error: {}", err);
+    }
 }
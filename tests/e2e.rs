@@ -146,6 +146,1617 @@ mod vba_compatibility {
     }
 }
 
+mod formulas {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn talbot_formula_total_matches_k_times_nt() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("talbot.csv");
+
+        let status = cargo_bin()
+            .args(["0", "40", "5000", "10", "1", "--formula", "talbot", "--format", "csv"])
+            .arg("--output")
+            .arg(csv_path.to_str().unwrap())
+            .status()
+            .expect("Failed to execute binary");
+        assert!(status.success(), "Process should exit with code 0");
+
+        let content = fs::read_to_string(&csv_path).expect("CSV file should exist");
+        let mut reader = csv::Reader::from_reader(content.as_bytes());
+        let intensities: Vec<f64> = reader
+            .records()
+            .map(|r| r.unwrap()[1].parse().unwrap())
+            .collect();
+
+        // K(60) = 5000 / (60 + 40) = 50 mm/h, NT = 6
+        assert_eq!(intensities.len(), 6);
+        let total: f64 = intensities.iter().sum();
+        assert!((total - 300.0).abs() < 1e-6, "total = {}", total);
+    }
+
+    #[test]
+    fn kimijima_without_m_returns_error() {
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--formula", "kimijima"])
+            .output()
+            .expect("Failed to execute binary");
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("--exponent-m"), "stderr: {}", stderr);
+    }
+}
+
+mod idf_table {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn idf_table_matches_formula_at_table_durations() {
+        let dir = tempfile::tempdir().unwrap();
+        let idf_path = dir.path().join("idf.csv");
+        let csv_path = dir.path().join("output.csv");
+
+        // 表の値は K = 1557.825 / (t^0.75 + 5.411) から作成
+        let mut table = String::from("duration_minutes,intensity_mm_per_h\n");
+        for d in [10.0_f64, 20.0, 30.0, 60.0, 120.0] {
+            let k = 1557.825 / (d.powf(0.75) + 5.411);
+            table.push_str(&format!("{},{}\n", d, k));
+        }
+        fs::write(&idf_path, table).unwrap();
+
+        let status = cargo_bin()
+            .args(["10", "2", "--pattern", "front", "--format", "csv"])
+            .arg("--idf-table")
+            .arg(idf_path.to_str().unwrap())
+            .arg("--output")
+            .arg(csv_path.to_str().unwrap())
+            .status()
+            .expect("Failed to execute binary");
+        assert!(status.success(), "Process should exit with code 0");
+
+        let content = fs::read_to_string(&csv_path).expect("CSV file should exist");
+        let mut reader = csv::Reader::from_reader(content.as_bytes());
+        let intensities: Vec<f64> = reader
+            .records()
+            .map(|r| r.unwrap()[1].parse().unwrap())
+            .collect();
+        assert_eq!(intensities.len(), 12);
+
+        // 先頭ステップは K(10)、合計は K(120) * 12 に一致する
+        let k10 = 1557.825 / (10.0_f64.powf(0.75) + 5.411);
+        let k120 = 1557.825 / (120.0_f64.powf(0.75) + 5.411);
+        assert!((intensities[0] - k10).abs() < 1e-6);
+        let total: f64 = intensities.iter().sum();
+        assert!((total - k120 * 12.0).abs() < 1e-6, "total = {}", total);
+    }
+
+    #[test]
+    fn duration_past_table_returns_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let idf_path = dir.path().join("idf.csv");
+        fs::write(
+            &idf_path,
+            "duration_minutes,intensity_mm_per_h\n10,141.179\n60,59.124\n",
+        )
+        .unwrap();
+
+        let output = cargo_bin()
+            .args(["10", "2", "--idf-table"])
+            .arg(idf_path.to_str().unwrap())
+            .output()
+            .expect("Failed to execute binary");
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("exceeds"), "stderr: {}", stderr);
+    }
+}
+
+mod fit {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn fit_prints_coefficients_and_renders_plot() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("observed.csv");
+        let plot = dir.path().join("fit.png");
+
+        let mut table = String::from("duration_minutes,intensity_mm_per_h\n");
+        for d in [10.0_f64, 20.0, 30.0, 60.0, 120.0, 180.0] {
+            let k = 1557.825 / (d.powf(0.75) + 5.411);
+            table.push_str(&format!("{},{}\n", d, k));
+        }
+        fs::write(&input, table).unwrap();
+
+        let output = cargo_bin()
+            .arg("fit")
+            .arg(input.to_str().unwrap())
+            .arg("--plot")
+            .arg(plot.to_str().unwrap())
+            .output()
+            .expect("Failed to execute binary");
+
+        assert!(output.status.success(), "fit should exit with code 0");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("A = 0.750"), "stdout: {}", stdout);
+        assert!(stdout.contains("RMSE"), "stdout: {}", stdout);
+        assert!(stdout.contains("R^2"), "stdout: {}", stdout);
+        assert!(plot.exists(), "Plot should be created");
+    }
+
+    #[test]
+    fn fit_plot_accepts_chart_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("observed.csv");
+        let mut table = String::from("duration_minutes,intensity_mm_per_h\n");
+        for d in [10.0_f64, 30.0, 60.0, 120.0] {
+            table.push_str(&format!("{},{}\n", d, 5000.0 / (d + 40.0)));
+        }
+        fs::write(&input, table).unwrap();
+
+        let output = cargo_bin()
+            .arg("fit")
+            .arg(&input)
+            .arg("--plot")
+            .arg(dir.path().join("fit.png"))
+            .args(["--chart-format", "svg", "--chart-width", "640", "--chart-height", "480"])
+            .output()
+            .expect("Failed to execute binary");
+
+        assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("SVG output:"), "stdout: {}", stdout);
+        let content =
+            fs::read_to_string(dir.path().join("fit.svg")).expect("SVG file should exist");
+        assert!(content.starts_with("<svg width=\"640\" height=\"480\""));
+        assert!(!dir.path().join("fit.png").exists());
+    }
+}
+
+mod frequency {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn frequency_output_feeds_idf_table_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let maxima = dir.path().join("maxima.csv");
+        let idf = dir.path().join("idf50.csv");
+        let csv_path = dir.path().join("hyetograph.csv");
+
+        // Gumbel分布のプロット位置に沿った合成年最大値
+        let mut content = String::from("year,10,30,60,120\n");
+        let n = 30;
+        for i in 1..=n {
+            let p = (i as f64 - 0.4) / (n as f64 + 0.2);
+            let y = -(-p.ln()).ln();
+            content.push_str(&format!(
+                "{},{},{},{},{}\n",
+                1990 + i,
+                110.0 + 18.0 * y,
+                70.0 + 12.0 * y,
+                48.0 + 8.0 * y,
+                32.0 + 5.0 * y
+            ));
+        }
+        fs::write(&maxima, content).unwrap();
+
+        let output = cargo_bin()
+            .arg("frequency")
+            .arg(maxima.to_str().unwrap())
+            .args(["--return-period", "50", "--distribution", "gumbel", "--output"])
+            .arg(idf.to_str().unwrap())
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success(), "frequency should exit with code 0");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("SLSC"), "stdout: {}", stdout);
+        assert!(stdout.contains("jackknife"), "stdout: {}", stdout);
+
+        let status = cargo_bin()
+            .args(["10", "2", "--format", "csv", "--idf-table"])
+            .arg(idf.to_str().unwrap())
+            .arg("--output")
+            .arg(csv_path.to_str().unwrap())
+            .status()
+            .expect("Failed to execute binary");
+        assert!(status.success(), "hyetograph run should exit with code 0");
+        assert!(csv_path.exists());
+    }
+
+    #[test]
+    fn writes_idf_table_when_coefficient_fit_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let maxima = dir.path().join("maxima.csv");
+        let idf = dir.path().join("idf.csv");
+
+        // 継続時間とともに強度が増える（降雨強度式が当てはまらない）年最大値
+        let mut content = String::from("year,10,30,60\n");
+        for i in 1..=20 {
+            let y = i as f64;
+            content.push_str(&format!(
+                "{},{},{},{}\n",
+                2000 + i,
+                20.0 + y,
+                40.0 + 2.0 * y,
+                80.0 + 4.0 * y
+            ));
+        }
+        fs::write(&maxima, content).unwrap();
+
+        let output = cargo_bin()
+            .arg("frequency")
+            .arg(&maxima)
+            .args(["--return-period", "10", "--distribution", "gumbel", "--output"])
+            .arg(&idf)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Warning: skipped fitting"), "stderr: {}", stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("duration_min  intensity_mm_h"), "stdout: {}", stdout);
+        assert!(idf.exists(), "IDF table should be written");
+    }
+}
+
+mod rational {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn reports_peak_discharge_at_tc() {
+        let output = cargo_bin()
+            .args(["rational", "0.75", "5.411", "1557.825"])
+            .args(["--runoff-coefficient", "0.8", "--area", "2", "--tc", "10"])
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success(), "Process should exit with code 0");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        // K(10) = 141.179 mm/h → Q = 0.8 * 141.179 * 2 / 3.6 = 62.746 m³/s
+        assert!(stdout.contains("K(tc) = 141.179 mm/h"), "stdout: {}", stdout);
+        assert!(stdout.contains("= 62.746 m³/s"), "stdout: {}", stdout);
+    }
+
+    #[test]
+    fn writes_discharge_table_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("rational.csv");
+
+        let status = cargo_bin()
+            .args(["rational", "0.75", "5.411", "1557.825"])
+            .args(["--land-use", "0.9:0.4,0.3:1.6", "--area", "2"])
+            .args(["--tc-method", "kirpich", "--length", "1200", "--slope", "0.02"])
+            .args(["--durations", "10,30,60", "--output"])
+            .arg(&csv_path)
+            .status()
+            .expect("Failed to execute binary");
+        assert!(status.success(), "Process should exit with code 0");
+
+        let content = fs::read_to_string(&csv_path).expect("CSV file should exist");
+        let mut reader = csv::Reader::from_reader(content.as_bytes());
+        assert_eq!(
+            reader.headers().unwrap().iter().collect::<Vec<_>>(),
+            ["duration_minutes", "intensity_mm_per_h", "discharge_m3_per_s"]
+        );
+        let discharges: Vec<f64> = reader
+            .records()
+            .map(|r| r.unwrap()[2].parse().unwrap())
+            .collect();
+        assert_eq!(discharges.len(), 3);
+        assert!(discharges.windows(2).all(|w| w[1] < w[0]), "{:?}", discharges);
+    }
+
+    #[test]
+    fn missing_time_of_concentration_fails() {
+        let output = cargo_bin()
+            .args(["rational", "0.75", "5.411", "1557.825"])
+            .args(["--runoff-coefficient", "0.8", "--area", "2"])
+            .output()
+            .expect("Failed to execute binary");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("--tc"), "stderr: {}", stderr);
+    }
+}
+
+mod coefficient_table {
+    use super::*;
+    use std::fs;
+
+    fn write_table(dir: &std::path::Path) -> std::path::PathBuf {
+        let path = dir.join("coefficients.csv");
+        fs::write(
+            &path,
+            "return_period,a,b,c\n10,0.75,5.411,1557.825\n50,0.74,5.102,1983.551\n",
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn return_period_reproduces_positional_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let table = write_table(dir.path());
+        let csv_path = dir.path().join("output.csv");
+
+        let status = cargo_bin()
+            .args(["10", "2", "--format", "csv", "--return-period", "10", "--coefficients"])
+            .arg(table.to_str().unwrap())
+            .arg("--output")
+            .arg(csv_path.to_str().unwrap())
+            .status()
+            .expect("Failed to execute binary");
+        assert!(status.success(), "Process should exit with code 0");
+
+        let content = fs::read_to_string(&csv_path).expect("CSV file should exist");
+        let mut reader = csv::Reader::from_reader(content.as_bytes());
+        let intensities: Vec<f64> = reader
+            .records()
+            .map(|r| r.unwrap()[1].parse().unwrap())
+            .collect();
+        assert_eq!(intensities.len(), 12);
+        assert!((intensities[6] - 141.179).abs() < 1e-3);
+    }
+
+    #[test]
+    fn help_shows_available_periods() {
+        let dir = tempfile::tempdir().unwrap();
+        let table = write_table(dir.path());
+
+        let output = cargo_bin()
+            .arg("--coefficients")
+            .arg(table.to_str().unwrap())
+            .arg("--help")
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("10, 50"), "stdout: {}", stdout);
+    }
+
+    #[test]
+    fn missing_return_period_returns_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let table = write_table(dir.path());
+
+        let output = cargo_bin()
+            .args(["10", "2", "--return-period", "100", "--coefficients"])
+            .arg(table.to_str().unwrap())
+            .output()
+            .expect("Failed to execute binary");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Available return periods: 10, 50"), "stderr: {}", stderr);
+    }
+}
+
+mod presets {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn vba_sample_preset_matches_positional_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("output.csv");
+
+        let output = cargo_bin()
+            .env("XDG_CONFIG_HOME", dir.path())
+            .args(["10", "2", "--preset", "vba-sample", "--format", "csv", "--output"])
+            .arg(csv_path.to_str().unwrap())
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success(), "Process should exit with code 0");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Preset: vba-sample"), "stdout: {}", stdout);
+
+        let content = fs::read_to_string(&csv_path).expect("CSV file should exist");
+        let mut reader = csv::Reader::from_reader(content.as_bytes());
+        let intensities: Vec<f64> = reader
+            .records()
+            .map(|r| r.unwrap()[1].parse().unwrap())
+            .collect();
+        assert!((intensities[6] - 141.179).abs() < 1e-3);
+    }
+
+    #[test]
+    fn user_presets_are_listed_and_shown() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("hyetograph-cli");
+        fs::create_dir_all(&config).unwrap();
+        fs::write(
+            config.join("presets.toml"),
+            "version = 1\n\n[[presets]]\nname = \"my-city-10y\"\nsource = \"City storm manual 2020\"\n\
+             formula = \"cleveland\"\nregion = \"My City\"\nreturn_period = 10\n\
+             a = 0.7\nb = 4.0\nc = 900.0\n",
+        )
+        .unwrap();
+
+        let output = cargo_bin()
+            .env("XDG_CONFIG_HOME", dir.path())
+            .args(["presets", "list"])
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("vba-sample"), "stdout: {}", stdout);
+        assert!(stdout.contains("my-city-10y"), "stdout: {}", stdout);
+
+        let output = cargo_bin()
+            .env("XDG_CONFIG_HOME", dir.path())
+            .args(["presets", "show", "my-city-10y"])
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("City storm manual 2020"), "stdout: {}", stdout);
+        assert!(stdout.contains("10 years"), "stdout: {}", stdout);
+    }
+
+    #[test]
+    fn unknown_preset_returns_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = cargo_bin()
+            .env("XDG_CONFIG_HOME", dir.path())
+            .args(["10", "2", "--preset", "nowhere"])
+            .output()
+            .expect("Failed to execute binary");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Unknown preset"), "stderr: {}", stderr);
+    }
+}
+
+mod scs_patterns {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn scs_type_ii_scaled_to_total_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("output.csv");
+
+        let output = cargo_bin()
+            .args(["0.75", "5.411", "1557.825", "60", "24", "--pattern", "scs-ii"])
+            .args(["--total-depth", "250", "--format", "csv", "--output"])
+            .arg(csv_path.to_str().unwrap())
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success(), "Process should exit with code 0");
+
+        let content = fs::read_to_string(&csv_path).expect("CSV file should exist");
+        let mut reader = csv::Reader::from_reader(content.as_bytes());
+        let intensities: Vec<f64> = reader
+            .records()
+            .map(|r| r.unwrap()[1].parse().unwrap())
+            .collect();
+        assert_eq!(intensities.len(), 24);
+
+        // 60分ステップなので強度[mm/h]の合計が総雨量[mm]
+        let total: f64 = intensities.iter().sum();
+        assert!((total - 250.0).abs() < 1e-2, "total = {}", total);
+
+        // 11〜12時のステップが最大
+        let peak = intensities
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, _)| i)
+            .unwrap();
+        assert_eq!(peak, 11);
+    }
+
+    #[test]
+    fn non_positive_total_depth_returns_error() {
+        let output = cargo_bin()
+            .args(["0.75", "5.411", "1557.825", "60", "24", "--pattern", "scs-i"])
+            .args(["--total-depth", "0"])
+            .output()
+            .expect("Failed to execute binary");
+        assert!(!output.status.success());
+    }
+}
+
+mod huff_patterns {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn huff_quartiles_preserve_formula_total() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut totals = Vec::new();
+        for pattern in ["center", "huff-q1", "huff-q4"] {
+            let csv_path = dir.path().join(format!("{}.csv", pattern));
+            let status = cargo_bin()
+                .args(base_args())
+                .args(["--pattern", pattern, "--huff-probability", "30"])
+                .args(["--format", "csv", "--output"])
+                .arg(csv_path.to_str().unwrap())
+                .status()
+                .expect("Failed to execute binary");
+            assert!(status.success(), "Process should exit with code 0");
+
+            let content = fs::read_to_string(&csv_path).expect("CSV file should exist");
+            let mut reader = csv::Reader::from_reader(content.as_bytes());
+            let total: f64 = reader
+                .records()
+                .map(|r| r.unwrap()[1].parse::<f64>().unwrap())
+                .sum();
+            totals.push(total);
+        }
+        for total in &totals[1..] {
+            assert!((total - totals[0]).abs() < 1e-2, "totals: {:?}", totals);
+        }
+    }
+
+    #[test]
+    fn untabulated_probability_returns_error() {
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--pattern", "huff-q1", "--huff-probability", "95"])
+            .output()
+            .expect("Failed to execute binary");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Huff probability"), "stderr: {}", stderr);
+    }
+}
+
+mod custom_pattern {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn custom_curve_scales_to_formula_total() {
+        let dir = tempfile::tempdir().unwrap();
+        let curve_path = dir.path().join("curve.json");
+        fs::write(&curve_path, r#"{ "step_percent": [10, 20, 40, 20, 5, 5] }"#).unwrap();
+
+        let mut totals = Vec::new();
+        let mut custom = Vec::new();
+        for extra in [vec!["--pattern", "center"], vec!["--pattern", "custom", "--mass-curve"]] {
+            let csv_path = dir.path().join("output.csv");
+            let mut cmd = cargo_bin();
+            cmd.args(base_args()).args(&extra);
+            if extra.contains(&"--mass-curve") {
+                cmd.arg(&curve_path);
+            }
+            let status = cmd
+                .args(["--format", "csv", "--output"])
+                .arg(&csv_path)
+                .status()
+                .expect("Failed to execute binary");
+            assert!(status.success(), "Process should exit with code 0");
+
+            let content = fs::read_to_string(&csv_path).expect("CSV file should exist");
+            let mut reader = csv::Reader::from_reader(content.as_bytes());
+            custom = reader
+                .records()
+                .map(|r| r.unwrap()[1].parse::<f64>().unwrap())
+                .collect::<Vec<f64>>();
+            totals.push(custom.iter().sum::<f64>());
+        }
+
+        assert!((totals[0] - totals[1]).abs() < 1e-2, "totals: {:?}", totals);
+        // 12ステップに補間すると3番目の区間（40%）が5〜6ステップ目に当たる
+        assert!((custom[4] - totals[1] * 0.2).abs() < 1e-2);
+    }
+
+    #[test]
+    fn non_monotonic_curve_returns_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let curve_path = dir.path().join("curve.csv");
+        fs::write(
+            &curve_path,
+            "time_fraction,cumulative_fraction\n0,0\n0.5,0.8\n0.75,0.6\n1,1\n",
+        )
+        .unwrap();
+
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--pattern", "custom", "--mass-curve"])
+            .arg(&curve_path)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("monotonic"), "stderr: {}", stderr);
+    }
+}
+
+mod multi_peak {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn two_peaks_preserve_total_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("output.csv");
+
+        let status = cargo_bin()
+            .args(base_args())
+            .args(["--pattern", "multi-peak", "--peaks", "0.25:0.3,0.75:0.7"])
+            .args(["--format", "csv", "--output"])
+            .arg(&csv_path)
+            .status()
+            .expect("Failed to execute binary");
+        assert!(status.success(), "Process should exit with code 0");
+
+        let content = fs::read_to_string(&csv_path).expect("CSV file should exist");
+        let mut reader = csv::Reader::from_reader(content.as_bytes());
+        let intensities: Vec<f64> = reader
+            .records()
+            .map(|r| r.unwrap()[1].parse().unwrap())
+            .collect();
+
+        let total: f64 = intensities.iter().sum();
+        let expected: f64 = [
+            141.179, 68.369, 46.819, 35.957, 29.354, 24.900, 21.684, 19.249, 17.339, 15.799,
+            14.530, 13.465,
+        ]
+        .iter()
+        .sum();
+        assert!((total - expected).abs() < 1e-2, "total = {}", total);
+
+        // 最大強度は後半（配分0.7）のピークの位置 round(0.75 × 12) = 9 に置かれる
+        let peak = intensities
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, _)| i)
+            .unwrap();
+        assert_eq!(peak, 9, "peak at {}", peak);
+    }
+}
+
+mod screening_patterns {
+    use super::*;
+    use std::fs;
+
+    fn total_for(extra: &[&str]) -> f64 {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("output.csv");
+        let status = cargo_bin()
+            .args(base_args())
+            .args(extra)
+            .args(["--format", "csv", "--output"])
+            .arg(&csv_path)
+            .status()
+            .expect("Failed to execute binary");
+        assert!(status.success(), "Process should exit with code 0");
+
+        let content = fs::read_to_string(&csv_path).expect("CSV file should exist");
+        let mut reader = csv::Reader::from_reader(content.as_bytes());
+        reader
+            .records()
+            .map(|r| r.unwrap()[1].parse::<f64>().unwrap())
+            .sum()
+    }
+
+    #[test]
+    fn triangular_and_trapezoidal_use_formula_total() {
+        let center = total_for(&["--pattern", "center"]);
+        let triangular = total_for(&["--pattern", "triangular", "--advancement", "0.3"]);
+        let trapezoidal = total_for(&["--pattern", "trapezoidal", "--trapezoid", "0.2,0.5,0.3"]);
+        assert!((triangular - center).abs() < 1e-2);
+        assert!((trapezoidal - center).abs() < 1e-2);
+    }
+
+    #[test]
+    fn compare_renders_png() {
+        let dir = tempfile::tempdir().unwrap();
+        let png_path = dir.path().join("compare.png");
+        let status = cargo_bin()
+            .args(base_args())
+            .args(["--pattern", "triangular", "--compare", "center", "--output"])
+            .arg(&png_path)
+            .status()
+            .expect("Failed to execute binary");
+        assert!(status.success(), "Process should exit with code 0");
+        assert!(fs::metadata(&png_path).unwrap().len() > 0);
+    }
+}
+
+mod areal_reduction {
+    use super::*;
+    use std::fs;
+
+    fn run_csv(extra: &[&str], dir: &std::path::Path) -> Vec<Vec<f64>> {
+        let csv_path = dir.join("output.csv");
+        let output = cargo_bin()
+            .args(base_args())
+            .args(extra)
+            .args(["--format", "both", "--output"])
+            .arg(dir.join("output.png"))
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+        let content = fs::read_to_string(&csv_path).expect("CSV file should exist");
+        let mut reader = csv::Reader::from_reader(content.as_bytes());
+        reader
+            .records()
+            .map(|r| r.unwrap().iter().map(|v| v.parse().unwrap()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn table_arf_reduces_and_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let table = dir.path().join("arf.csv");
+        fs::write(
+            &table,
+            "area_km2,duration_minutes,factor\n0,10,1\n0,120,1\n100,10,0.8\n100,120,0.8\n",
+        )
+        .unwrap();
+
+        let point = run_csv(&[], dir.path());
+        let reduced = run_csv(
+            &["--area", "100", "--arf-method", "table", "--arf-table", table.to_str().unwrap()],
+            dir.path(),
+        );
+
+        assert_eq!(point[0].len(), 2);
+        assert_eq!(reduced[0].len(), 3);
+        for (p, r) in point.iter().zip(&reduced) {
+            assert!((r[1] - 0.8 * p[1]).abs() < 1e-2, "{:?} vs {:?}", p, r);
+            assert_eq!(r[2], 0.8);
+        }
+        assert!(dir.path().join("output.png").exists());
+    }
+
+    #[test]
+    fn center_pattern_reports_factor_for_each_rank() {
+        let dir = tempfile::tempdir().unwrap();
+        let table = dir.path().join("arf.csv");
+        fs::write(
+            &table,
+            "area_km2,duration_minutes,factor\n0,10,1\n0,120,1\n100,10,0.6\n100,120,0.9\n",
+        )
+        .unwrap();
+
+        let point = run_csv(&[], dir.path());
+        let reduced = run_csv(
+            &["--area", "100", "--arf-method", "table", "--arf-table", table.to_str().unwrap()],
+            dir.path(),
+        );
+
+        // 中央集中型のピーク（70分）は最大の増分で、継続時間 T = 10 分の係数が掛かる
+        let peak = 6;
+        assert!((reduced[peak][2] - 0.6).abs() < 1e-9, "{:?}", reduced[peak]);
+        assert!((reduced[peak][1] - 0.6 * point[peak][1]).abs() < 1e-2);
+        for r in &reduced {
+            assert!(r[2] >= reduced[peak][2], "{:?}", r);
+        }
+        // 最小の増分（順位 11）は TT = 120 分の係数
+        assert!(reduced.iter().any(|r| (r[2] - 0.9).abs() < 1e-9));
+    }
+
+    #[test]
+    fn tp29_prints_storm_duration_factor() {
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--area", "50", "--format", "csv", "--output"])
+            .arg(tempfile::tempdir().unwrap().path().join("out.csv"))
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("ARF (A = 50 km², 120 min)"), "stdout: {}", stdout);
+    }
+}
+
+mod curve_number {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn cn_adds_loss_and_effective_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("output.csv");
+
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--cn", "80", "--amc", "wet", "--format", "csv", "--output"])
+            .arg(&csv_path)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success(), "Process should exit with code 0");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Effective rainfall"), "stdout: {}", stdout);
+
+        let content = fs::read_to_string(&csv_path).expect("CSV file should exist");
+        let mut reader = csv::Reader::from_reader(content.as_bytes());
+        assert_eq!(
+            reader.headers().unwrap().iter().collect::<Vec<_>>(),
+            ["time_minutes", "intensity_mm_per_h", "loss_mm_per_h", "effective_mm_per_h"]
+        );
+        for record in reader.records() {
+            let values: Vec<f64> = record.unwrap().iter().map(|v| v.parse().unwrap()).collect();
+            assert!((values[2] + values[3] - values[1]).abs() < 1e-2, "{:?}", values);
+            assert!(values[2] >= 0.0 && values[3] >= 0.0);
+        }
+    }
+}
+
+mod loss_models {
+    use super::*;
+    use std::fs;
+
+    fn run_and_get_losses(extra: &[&str]) -> Vec<(f64, f64, f64)> {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("output.csv");
+
+        let output = cargo_bin()
+            .args(base_args())
+            .args(extra)
+            .args(["--format", "csv", "--output"])
+            .arg(&csv_path)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let content = fs::read_to_string(&csv_path).expect("CSV file should exist");
+        let mut reader = csv::Reader::from_reader(content.as_bytes());
+        reader
+            .records()
+            .map(|record| {
+                let values: Vec<f64> = record.unwrap().iter().map(|v| v.parse().unwrap()).collect();
+                (values[1], values[2], values[3])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_model_keeps_loss_within_rainfall() {
+        let cases: [&[&str]; 4] = [
+            &["--loss", "horton", "--horton-f0", "80", "--horton-fc", "10", "--horton-k", "4"],
+            &["--loss", "green-ampt", "--ksat", "10", "--suction", "110", "--moisture-deficit", "0.3"],
+            &["--loss", "phi-index", "--phi", "20"],
+            &["--loss", "initial-continuing", "--initial-loss", "15", "--continuing-loss", "3"],
+        ];
+        for extra in cases {
+            for (rain, loss, effective) in run_and_get_losses(extra) {
+                assert!(loss >= 0.0 && loss <= rain + 1e-6, "{:?}: {} > {}", extra, loss, rain);
+                assert!((loss + effective - rain).abs() < 1e-2, "{:?}", extra);
+            }
+        }
+    }
+
+    #[test]
+    fn phi_index_caps_loss_at_phi() {
+        for (rain, loss, _) in run_and_get_losses(&["--loss", "phi-index", "--phi", "20"]) {
+            assert!((loss - rain.min(20.0)).abs() < 1e-6, "rain {} loss {}", rain, loss);
+        }
+    }
+
+    #[test]
+    fn missing_model_parameters_fail() {
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--loss", "green-ampt", "--ksat", "10"])
+            .output()
+            .expect("Failed to execute binary");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("--suction"), "stderr: {}", stderr);
+    }
+}
+
+mod storage_function {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn writes_hydrograph_csv_and_conserves_volume() {
+        let dir = tempfile::tempdir().unwrap();
+        let png_path = dir.path().join("storm.png");
+
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--runoff", "storage-function", "--sf-k", "10", "--sf-p", "0.6"])
+            .args(["--runoff-area", "36", "--runoff-duration", "120", "--format", "both"])
+            .args(["--output"])
+            .arg(&png_path)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(output.status.success(), "Process should exit with code 0");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Peak discharge"), "stdout: {}", stdout);
+        assert!(png_path.exists(), "PNG file should exist");
+
+        let content = fs::read_to_string(dir.path().join("storm_runoff.csv"))
+            .expect("Runoff CSV file should exist");
+        let mut reader = csv::Reader::from_reader(content.as_bytes());
+        assert_eq!(
+            reader.headers().unwrap().iter().collect::<Vec<_>>(),
+            ["time_minutes", "rainfall_mm_per_h", "discharge_m3_per_s"]
+        );
+        let rows: Vec<Vec<f64>> = reader
+            .records()
+            .map(|r| r.unwrap().iter().map(|v| v.parse().unwrap()).collect())
+            .collect();
+        assert_eq!(rows.len(), 720);
+
+        // 流出量[m³] = 降雨量[mm] × 面積[km²] × 1000（f1 = 1 のため全量が流出する）
+        let rain_mm: f64 = rows.iter().map(|r| r[1]).sum::<f64>() * 10.0 / 60.0;
+        let volume: f64 = rows.iter().map(|r| r[2]).sum::<f64>() * 600.0;
+        assert!(
+            (volume / (rain_mm * 36.0 * 1000.0) - 1.0).abs() < 0.02,
+            "volume {} rain {}",
+            volume,
+            rain_mm
+        );
+    }
+
+    #[test]
+    fn missing_area_fails() {
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--runoff", "storage-function", "--sf-k", "10", "--sf-p", "0.6"])
+            .output()
+            .expect("Failed to execute binary");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("--runoff-area"), "stderr: {}", stderr);
+    }
+}
+
+mod unit_hydrograph {
+    use super::*;
+    use std::fs;
+
+    fn run(dir: &std::path::Path, extra: &[&str]) -> (String, Vec<Vec<f64>>) {
+        let png_path = dir.join("storm.png");
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--runoff", "unit-hydrograph", "--runoff-area", "36"])
+            .args(extra)
+            .args(["--format", "csv", "--output"])
+            .arg(&png_path)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let content = fs::read_to_string(dir.join("storm_runoff.csv"))
+            .expect("Runoff CSV file should exist");
+        let mut reader = csv::Reader::from_reader(content.as_bytes());
+        let rows = reader
+            .records()
+            .map(|r| r.unwrap().iter().map(|v| v.parse().unwrap()).collect())
+            .collect();
+        (String::from_utf8_lossy(&output.stdout).into_owned(), rows)
+    }
+
+    #[test]
+    fn every_method_reports_summary_and_conserves_volume() {
+        let dir = tempfile::tempdir().unwrap();
+        let uh_path = dir.path().join("uh.csv");
+        fs::write(&uh_path, "time_minutes,discharge_m3_per_s
+30,4
+60,10
+120,3
+180,0
+").unwrap();
+
+        let cases: [&[&str]; 4] = [
+            &["--uh-method", "scs", "--uh-lag", "0.5"],
+            &["--uh-method", "snyder", "--snyder-ct", "1.5", "--snyder-cp", "0.6",
+              "--snyder-length", "8", "--snyder-lc", "4"],
+            &["--uh-method", "clark", "--uh-tc", "1.5", "--clark-r", "1"],
+            &["--uh-method", "file", "--uh-file", uh_path.to_str().unwrap()],
+        ];
+        for extra in cases {
+            let mut args = extra.to_vec();
+            args.extend(["--runoff-duration", "48"]);
+            let (stdout, rows) = run(dir.path(), &args);
+            assert!(stdout.contains("Peak discharge"), "stdout: {}", stdout);
+            assert!(stdout.contains("Runoff volume"), "stdout: {}", stdout);
+
+            let rain_mm: f64 = rows.iter().map(|r| r[1]).sum::<f64>() * 10.0 / 60.0;
+            let volume: f64 = rows.iter().map(|r| r[2]).sum::<f64>() * 600.0;
+            assert!(
+                (volume / (rain_mm * 36.0 * 1000.0) - 1.0).abs() < 1e-3,
+                "{:?}: volume {} rain {}",
+                extra,
+                volume,
+                rain_mm
+            );
+        }
+    }
+
+    #[test]
+    fn missing_unit_hydrograph_file_fails() {
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--runoff", "unit-hydrograph", "--runoff-area", "36", "--uh-method", "file"])
+            .output()
+            .expect("Failed to execute binary");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("--uh-file"), "stderr: {}", stderr);
+    }
+}
+
+mod tank_model {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn writes_tank_states_and_hydrograph() {
+        let dir = tempfile::tempdir().unwrap();
+        let model_path = dir.path().join("tank.toml");
+        fs::write(
+            &model_path,
+            "[[tanks]]\ninitial_storage = 5.0\nbottom = 0.12\n\
+             outlets = [{ height = 15.0, coefficient = 0.1 }, { height = 40.0, coefficient = 0.2 }]\n\n\
+             [[tanks]]\ninitial_storage = 30.0\nbottom = 0.05\noutlets = [{ height = 0.0, coefficient = 0.05 }]\n\n\
+             [[tanks]]\ninitial_storage = 100.0\noutlets = [{ height = 0.0, coefficient = 0.01 }]\n",
+        )
+        .unwrap();
+        let png_path = dir.path().join("storm.png");
+
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--runoff", "tank", "--tank-model"])
+            .arg(&model_path)
+            .args(["--runoff-area", "50", "--format", "csv", "--output"])
+            .arg(&png_path)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("tank model: 3 tanks"), "stdout: {}", stdout);
+
+        let content = fs::read_to_string(dir.path().join("storm_tanks.csv"))
+            .expect("Tank CSV file should exist");
+        let mut reader = csv::Reader::from_reader(content.as_bytes());
+        assert_eq!(reader.headers().unwrap().len(), 1 + 3 * 3);
+        let first: Vec<f64> = reader
+            .records()
+            .next()
+            .unwrap()
+            .unwrap()
+            .iter()
+            .map(|v| v.parse().unwrap())
+            .collect();
+        assert_eq!(first[0], 10.0);
+        assert!(first[7] > 0.0, "third tank should hold its initial storage");
+
+        let runoff = fs::read_to_string(dir.path().join("storm_runoff.csv"))
+            .expect("Runoff CSV file should exist");
+        // 流出計算期間は降雨継続時間 2 時間の3倍 → 10分刻みで36ステップ
+        assert_eq!(runoff.lines().count(), 1 + 36);
+    }
+}
+
+mod kinematic_wave {
+    use super::*;
+    use std::fs;
+
+    /// 標準出力の "<prefix>... = <値> m³/s" から流量を取り出す
+    fn discharge_after(stdout: &str, prefix: &str) -> f64 {
+        let line = stdout
+            .lines()
+            .find(|l| l.starts_with(prefix))
+            .unwrap_or_else(|| panic!("missing '{}' in stdout: {}", prefix, stdout));
+        let value = line.split(" m³/s").next().unwrap();
+        value
+            .rsplit([' ', '='])
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn plane_outflow_compares_with_rational_peak() {
+        let dir = tempfile::tempdir().unwrap();
+        let png_path = dir.path().join("plane.png");
+
+        let output = cargo_bin()
+            .args(["0.75", "5.411", "1557.825", "1", "1"])
+            .args(["--runoff", "kinematic-wave", "--kw-slope", "0.01", "--kw-length", "100"])
+            .args(["--kw-n", "0.015", "--kw-width", "50", "--format", "csv", "--output"])
+            .arg(&png_path)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let peak = discharge_after(&stdout, "Peak discharge:");
+        let rational = discharge_after(&stdout, "Rational method:");
+        // 交互ブロック法の降雨では到達時間内の平均強度が K(tc) となるため、
+        // 斜面のピーク流量は合理式と同程度になる
+        assert!((peak - rational).abs() / rational < 0.2, "{} vs {}", peak, rational);
+
+        let runoff = fs::read_to_string(dir.path().join("plane_runoff.csv"))
+            .expect("Runoff CSV file should exist");
+        // 降雨継続時間 1 時間の3倍 → 1分刻みで180ステップ
+        assert_eq!(runoff.lines().count(), 1 + 180);
+    }
+}
+
+mod swmm {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn writes_raingage_and_timeseries_sections() {
+        let dir = tempfile::tempdir().unwrap();
+        let png_path = dir.path().join("storm.png");
+
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--format", "swmm", "--swmm-rain-format", "volume"])
+            .args(["--swmm-series", "design10", "--start-time", "2024-06-01T09:00"])
+            .arg("--output")
+            .arg(&png_path)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let content = fs::read_to_string(dir.path().join("storm.inp"))
+            .expect("SWMM file should exist");
+        assert!(!png_path.exists());
+        let gage = content
+            .lines()
+            .find(|l| l.starts_with("RG1"))
+            .expect("raingage line");
+        assert_eq!(
+            gage.split_whitespace().collect::<Vec<_>>(),
+            ["RG1", "VOLUME", "0:10", "1.0", "TIMESERIES", "design10"]
+        );
+        let series: Vec<&str> = content
+            .lines()
+            .filter(|l| l.starts_with("design10"))
+            .collect();
+        // 12ステップ + 降雨終了時刻の 0
+        assert_eq!(series.len(), 12 + 1);
+        assert!(series[0].contains("06/01/2024 09:00"));
+        assert!(series[12].contains("06/01/2024 11:00"));
+        let total: f64 = series
+            .iter()
+            .map(|l| l.split_whitespace().last().unwrap().parse::<f64>().unwrap())
+            .sum();
+        assert!(total > 0.0);
+    }
+
+    #[test]
+    fn patches_existing_inp_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let inp = dir.path().join("network.inp");
+        fs::write(
+            &inp,
+            "[TITLE]\nnetwork\n\n[RAINGAGES]\nRG1 INTENSITY 1:00 1.0 TIMESERIES hyetograph\n\n\
+             [TIMESERIES]\nhyetograph 0:00 5\nhyetograph 1:00 0\nother 0:00 1\n\n[JUNCTIONS]\nJ1 10 2 0 0 0\n",
+        )
+        .unwrap();
+
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--format", "swmm", "--swmm-inp"])
+            .arg(&inp)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("SWMM input patched"), "stdout: {}", stdout);
+
+        let content = fs::read_to_string(&inp).unwrap();
+        assert!(content.contains("other 0:00 1"));
+        assert!(content.contains("[JUNCTIONS]\nJ1 10 2 0 0 0"));
+        assert!(!content.contains("hyetograph 0:00 5"));
+        assert_eq!(content.lines().filter(|l| l.starts_with("hyetograph")).count(), 13);
+        assert_eq!(content.lines().filter(|l| l.starts_with("RG1")).count(), 1);
+        assert!(content.contains("RG1              INTENSITY  0:10"));
+    }
+}
+
+mod hec {
+    use super::*;
+    use std::fs;
+
+    fn run(dir: &std::path::Path, format: &str, start: &str) -> std::process::Output {
+        cargo_bin()
+            .args(base_args())
+            .args(["--format", format, "--start-time", start, "--hec-location", "basin1"])
+            .arg("--output")
+            .arg(dir.join("storm.png"))
+            .output()
+            .expect("Failed to execute binary")
+    }
+
+    #[test]
+    fn hms_writes_timestamped_gage_table_and_dss_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = run(dir.path(), "hec-hms", "2024-06-01T09:00");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("//BASIN1/PRECIP-INC/01JUN2024/10MIN/HYETOGRAPH-CLI/"),
+            "stdout: {}",
+            stdout
+        );
+
+        let hms = fs::read_to_string(dir.path().join("storm_202406010900_hms.txt"))
+            .expect("HMS gage file should exist");
+        assert!(hms.contains("End Time: 1 June 2024, 11:00"));
+        let rows: Vec<&str> = hms.lines().filter(|l| l.starts_with("01Jun2024\t")).collect();
+        assert_eq!(rows.len(), 12);
+        assert!(rows[0].starts_with("01Jun2024\t09:10\t"));
+
+        let dss = fs::read_to_string(dir.path().join("storm_202406010900_dss.txt"))
+            .expect("DSS text file should exist");
+        assert!(dss.contains("Type\tPER-CUM"));
+        assert!(dss.contains("01Jun2024 1100\t"));
+    }
+
+    #[test]
+    fn ras_writes_precipitation_hydrograph() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = run(dir.path(), "hec-ras", "2024-06-01T22:00");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let ras = fs::read_to_string(dir.path().join("storm_202406012200_ras.txt"))
+            .expect("RAS file should exist");
+        assert!(ras.contains("Fixed Start Date/Time=01JUN2024,2200"));
+        assert!(ras.contains("Precipitation Hydrograph= 13 "));
+        let dss = fs::read_to_string(dir.path().join("storm_202406012200_dss.txt")).unwrap();
+        assert!(dss.contains("01Jun2024 2400\t"));
+    }
+
+    #[test]
+    fn missing_start_time_fails() {
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--format", "hec-ras"])
+            .output()
+            .expect("Failed to execute binary");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("--start-time"), "stderr: {}", stderr);
+    }
+}
+
+mod json {
+    use super::*;
+    use std::fs;
+
+    fn run(dir: &std::path::Path, format: &str) -> std::process::Output {
+        cargo_bin()
+            .args(base_args())
+            .args(["--format", format])
+            .arg("--output")
+            .arg(dir.join("storm.png"))
+            .output()
+            .expect("Failed to execute binary")
+    }
+
+    #[test]
+    fn writes_run_document() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = run(dir.path(), "json");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let content = fs::read_to_string(dir.path().join("storm.json"))
+            .expect("JSON file should exist");
+        let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(json["tool"], "hyetograph-cli");
+        assert!(json["version"].is_string());
+        assert_eq!(json["formula"]["type"], "cleveland");
+        assert_eq!(json["formula"]["A"], 0.75);
+        assert_eq!(json["formula"]["B"], 5.411);
+        assert_eq!(json["formula"]["C"], 1557.825);
+        assert_eq!(json["t_minutes"], 10.0);
+        assert_eq!(json["tt_hours"], 2.0);
+        assert_eq!(json["nt"], 12);
+        assert_eq!(json["series"].as_array().unwrap().len(), 12);
+        assert!(json["total_depth_mm"].as_f64().unwrap() > 0.0);
+        assert!(json["series"][0]["time_minutes"].is_number());
+        assert!(!dir.path().join("storm.png").exists());
+    }
+
+    #[test]
+    fn ndjson_appends_one_line_per_run() {
+        let dir = tempfile::tempdir().unwrap();
+        for _ in 0..2 {
+            let output = run(dir.path(), "ndjson");
+            assert!(
+                output.status.success(),
+                "stderr: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        let content = fs::read_to_string(dir.path().join("storm.ndjson"))
+            .expect("NDJSON file should exist");
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        for line in lines {
+            let json: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(json["nt"], 12);
+        }
+    }
+}
+
+mod chart_format {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn svg_is_chosen_from_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let svg_path = dir.path().join("storm.svg");
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--chart-width", "1000", "--chart-height", "500"])
+            .arg("--output")
+            .arg(&svg_path)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("SVG output:"), "stdout: {}", stdout);
+
+        let content = fs::read_to_string(&svg_path).expect("SVG file should exist");
+        assert!(content.starts_with("<svg width=\"1000\" height=\"500\""));
+    }
+
+    #[test]
+    fn explicit_pdf_replaces_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--chart-format", "pdf", "--font-scale", "1.2", "--format", "both"])
+            .arg("--output")
+            .arg(dir.path().join("storm.png"))
+            .output()
+            .expect("Failed to execute binary");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let content = fs::read(dir.path().join("storm.pdf")).expect("PDF file should exist");
+        assert!(content.starts_with(b"%PDF-"));
+        assert!(!dir.path().join("storm.png").exists());
+        assert!(dir.path().join("storm.csv").exists());
+    }
+
+    #[test]
+    fn invalid_dpi_fails() {
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--dpi", "0"])
+            .output()
+            .expect("Failed to execute binary");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("--dpi"), "stderr: {}", stderr);
+    }
+
+    #[test]
+    fn dpi_with_vector_chart_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--dpi", "300", "--output"])
+            .arg(dir.path().join("storm.svg"))
+            .output()
+            .expect("Failed to execute binary");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("--dpi only applies to PNG"), "stderr: {}", stderr);
+        assert!(!dir.path().join("storm.svg").exists());
+    }
+}
+
+mod pond {
+    use super::*;
+    use std::fs;
+
+    /// 平面積 20000 m² の池と Q = 1.5 h^1.5 のオリフィス
+    fn write_table(dir: &std::path::Path) -> std::path::PathBuf {
+        let path = dir.join("pond.csv");
+        let mut content = String::from("stage_m,storage_m3,discharge_m3_per_s\n");
+        for i in 0..=40 {
+            let h = i as f64 * 0.25;
+            content.push_str(&format!("{},{},{}\n", h, h * 20000.0, 1.5 * h.powf(1.5)));
+        }
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn peak_outflow(content: &str) -> f64 {
+        let mut reader = csv::Reader::from_reader(content.as_bytes());
+        reader
+            .records()
+            .map(|r| r.unwrap()[2].parse::<f64>().unwrap())
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn routes_hydrograph_and_writes_pond_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let table = write_table(dir.path());
+        let png_path = dir.path().join("storm.png");
+
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--runoff", "unit-hydrograph", "--uh-tc", "1", "--runoff-area", "2"])
+            .arg("--pond")
+            .arg(&table)
+            .args(["--format", "csv", "--output"])
+            .arg(&png_path)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Maximum stage:"), "stdout: {}", stdout);
+        assert!(stdout.contains("Required volume:"), "stdout: {}", stdout);
+
+        let content = fs::read_to_string(dir.path().join("storm_pond.csv"))
+            .expect("Pond CSV file should exist");
+        assert_eq!(
+            content.lines().next().unwrap(),
+            "time_minutes,inflow_m3_per_s,outflow_m3_per_s,stage_m,storage_m3"
+        );
+        // 初期時刻の行 + 流出計算の36ステップ
+        assert_eq!(content.lines().count(), 1 + 1 + 36);
+        let peak_inflow = csv::Reader::from_reader(content.as_bytes())
+            .records()
+            .map(|r| r.unwrap()[1].parse::<f64>().unwrap())
+            .fold(0.0, f64::max);
+        assert!(peak_outflow(&content) < peak_inflow);
+    }
+
+    #[test]
+    fn subcommand_sizes_pond_for_allowable_release() {
+        let dir = tempfile::tempdir().unwrap();
+        let table = write_table(dir.path());
+        let inflow = dir.path().join("inflow.csv");
+        let mut content = String::from("time_minutes,discharge_m3_per_s\n");
+        for i in 0..=48 {
+            // 60分でピーク 20 m³/s、180分で0になる三角形ハイドログラフ
+            let t = i as f64 * 10.0;
+            let q = 20.0 * (t / 60.0).min((180.0 - t).max(0.0) / 120.0);
+            content.push_str(&format!("{},{}\n", t, q));
+        }
+        fs::write(&inflow, content).unwrap();
+        let routed = dir.path().join("routed.csv");
+
+        let output = cargo_bin()
+            .arg("pond")
+            .arg("--table")
+            .arg(&table)
+            .arg("--inflow")
+            .arg(&inflow)
+            .args(["--target-release", "2", "--output"])
+            .arg(&routed)
+            .output()
+            .expect("Failed to execute binary");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Pond sizing: allowable release 2"), "stdout: {}", stdout);
+
+        let content = fs::read_to_string(&routed).expect("Routed CSV file should exist");
+        assert_eq!(content.lines().count(), 1 + 49);
+        let peak = peak_outflow(&content);
+        assert!(peak <= 2.0 && peak > 1.99, "peak outflow {}", peak);
+    }
+
+    #[test]
+    fn pond_requires_runoff() {
+        let output = cargo_bin()
+            .args(base_args())
+            .args(["--pond", "pond.csv"])
+            .output()
+            .expect("Failed to execute binary");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("--pond requires --runoff"), "stderr: {}", stderr);
+    }
+}
+
+mod peak_ratio {
+    use super::*;
+    use std::fs;
+
+    fn run_and_get_intensities(extra: &[&str]) -> Vec<f64> {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("output.csv");
+
+        let status = cargo_bin()
+            .args(base_args())
+            .args(extra)
+            .args(["--format", "csv", "--output"])
+            .arg(csv_path.to_str().unwrap())
+            .status()
+            .expect("Failed to execute binary");
+        assert!(status.success(), "Process should exit with code 0");
+
+        let content = fs::read_to_string(&csv_path).expect("CSV file should exist");
+        let mut reader = csv::Reader::from_reader(content.as_bytes());
+        reader
+            .records()
+            .map(|r| r.unwrap()[1].parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn peak_ratio_reproduces_builtin_patterns() {
+        for (r, pattern) in [("0", "front"), ("0.5", "center"), ("1", "rear")] {
+            assert_eq!(
+                run_and_get_intensities(&["--peak-ratio", r]),
+                run_and_get_intensities(&["--pattern", pattern]),
+                "r = {} should match {}",
+                r,
+                pattern
+            );
+        }
+    }
+
+    #[test]
+    fn peak_ratio_moves_peak() {
+        let intensities = run_and_get_intensities(&["--peak-ratio", "0.25"]);
+        // floor(0.25 * 12) = 3
+        assert!((intensities[3] - 141.179).abs() < 1e-3);
+    }
+}
+
 mod error_cases {
     use super::*;
 